@@ -0,0 +1,47 @@
+//! Decodes arbitrary input as [Amount] and [Balance] in every form they accept, and checks that
+//! whatever decodes encodes back to an equal value, within the range of an [Amount].
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_examples::fastset_types::{Amount, Balance};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(amount) = s.parse::<Amount>() {
+            check_amount(amount);
+        }
+        if let Ok(balance) = s.parse::<Balance>() {
+            check_balance(balance);
+        }
+    }
+    if let Ok(amount) = bcs::from_bytes::<Amount>(data) {
+        assert_eq!(bcs::to_bytes(&amount).unwrap(), data);
+        check_amount(amount);
+    }
+    if let Ok(balance) = bcs::from_bytes::<Balance>(data) {
+        assert_eq!(bcs::to_bytes(&balance).unwrap(), data);
+        check_balance(balance);
+    }
+    if let Ok(amount) = serde_json::from_slice::<Amount>(data) {
+        check_amount(amount);
+    }
+    if let Ok(balance) = serde_json::from_slice::<Balance>(data) {
+        check_balance(balance);
+    }
+});
+
+fn check_amount(amount: Amount) {
+    let json = serde_json::to_string(&amount).unwrap();
+    assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), amount);
+    let bytes = bcs::to_bytes(&amount).unwrap();
+    assert_eq!(bcs::from_bytes::<Amount>(&bytes).unwrap(), amount);
+}
+
+fn check_balance(balance: Balance) {
+    assert!(Balance::min() <= balance && balance <= Balance::max());
+    let json = serde_json::to_string(&balance).unwrap();
+    assert_eq!(serde_json::from_str::<Balance>(&json).unwrap(), balance);
+    let bytes = bcs::to_bytes(&balance).unwrap();
+    assert_eq!(bcs::from_bytes::<Balance>(&bytes).unwrap(), balance);
+}
@@ -0,0 +1,318 @@
+//! The Solidity ABI form of a transaction, as found in [CrossSignResponse::transaction].
+//!
+//! The proxy API only documents that field as "the ABI encoded transaction". The layout below is
+//! an **unverified assumption** of these examples: it is not taken from the proxy's source or
+//! specification, and has not been checked against a response of a real proxy. It assumes
+//! `abi.encode` of a static tuple, one 32-byte word per field:
+//!
+//! ```solidity
+//! struct FastSetTransaction {
+//!     bytes32 sender;
+//!     bytes32 recipient;
+//!     uint64 nonce;
+//!     uint128 timestampNanos;
+//!     uint8 claim;      // the index of the ClaimType variant, 0 for TokenTransfer
+//!     bytes32 tokenId;
+//!     uint256 amount;
+//! }
+//! ```
+//!
+//! with the user data and the archival flag of the transaction left out, so that a check of an ABI
+//! transaction says nothing about them: [AbiCheck] lists them as unchecked. Until responses
+//! captured from a proxy confirm the layout (see `tests/evm_sign_certificate/`), an [AbiError] may
+//! mean that the assumption is wrong rather than that the proxy signed another transaction.
+//!
+//! [AbiTransaction::encode] writes this assumed layout. It is not a reference for what the proxy
+//! signs, only a way to find where a captured response departs from the assumption with
+//! [diff_words]. Nothing here passes its output off as the proxy's: settlement calldata always
+//! carries the bytes signed by the proxy.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::fastset_types::{
+    Amount, ClaimType, CrossSignResponse, FastSetAddress, Nonce, PublicKeyBytes, TokenId,
+    Transaction, TransactionCertificate,
+};
+
+const WORD: usize = 32;
+/// Names of the words of an ABI-encoded transaction, as in the Solidity struct
+pub const ABI_FIELDS: [&str; 7] = [
+    "sender",
+    "recipient",
+    "nonce",
+    "timestampNanos",
+    "claim",
+    "tokenId",
+    "amount",
+];
+/// Length of an ABI-encoded transaction
+pub const ABI_TRANSACTION_LEN: usize = ABI_FIELDS.len() * WORD;
+/// Fields of a [Transaction] left out of the assumed layout
+pub const UNCHECKED_FIELDS: [&str; 2] = ["user_data", "archival"];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AbiError {
+    #[error(
+        "The ABI transaction is {0} bytes long, expected {ABI_TRANSACTION_LEN} in the assumed layout."
+    )]
+    Length(usize),
+    #[error("The `{0}` word of the ABI transaction does not fit its type.")]
+    OutOfRange(&'static str),
+    #[error("Unknown claim type {0} in the ABI transaction.")]
+    UnknownClaim(u8),
+    #[error("{0} claims have no ABI encoding here, only TokenTransfer claims.")]
+    UnsupportedClaim(&'static str),
+    #[error("The ABI transaction differs from the certificate in: {}.", .0.join(", "))]
+    Mismatch(Vec<&'static str>),
+}
+
+/// A match between an ABI transaction and a certificate, in the fields of the assumed layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct AbiCheck {
+    /// Fields of the certified transaction that the ABI transaction does not cover, so that they
+    /// may differ from what the proxy signed
+    pub unchecked: Vec<&'static str>,
+}
+
+/// The kind of claim of an ABI transaction, numbered like the [ClaimType] variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiClaim {
+    TokenTransfer = 0,
+}
+
+impl TryFrom<u8> for AbiClaim {
+    type Error = AbiError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::TokenTransfer),
+            _ => Err(AbiError::UnknownClaim(value)),
+        }
+    }
+}
+
+/// A typed view of an ABI-encoded transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiTransaction {
+    pub sender: FastSetAddress,
+    pub recipient: FastSetAddress,
+    pub nonce: Nonce,
+    pub timestamp_nanos: u128,
+    pub claim: AbiClaim,
+    pub token_id: TokenId,
+    pub amount: Amount,
+}
+
+impl AbiTransaction {
+    /// The view of `transaction` in the assumed layout.
+    pub fn from_transaction(transaction: &Transaction) -> Result<Self, AbiError> {
+        let ClaimType::TokenTransfer(transfer) = &transaction.claim else {
+            return Err(AbiError::UnsupportedClaim(transaction.claim.name()));
+        };
+        Ok(Self {
+            sender: transaction.sender,
+            recipient: transaction.recipient,
+            nonce: transaction.nonce,
+            timestamp_nanos: transaction.timestamp_nanos,
+            claim: AbiClaim::TokenTransfer,
+            token_id: transfer.token_id,
+            amount: transfer.amount,
+        })
+    }
+
+    /// Decode `abi.encode(FastSetTransaction)`. Every word must be in range of its Solidity type.
+    pub fn decode(bytes: &[u8]) -> Result<Self, AbiError> {
+        if bytes.len() != ABI_TRANSACTION_LEN {
+            return Err(AbiError::Length(bytes.len()));
+        }
+        let mut words = bytes
+            .chunks_exact(WORD)
+            .map(|chunk| <[u8; WORD]>::try_from(chunk).expect("chunks of a word"));
+        let mut next = || words.next().expect("seven words");
+        Ok(Self {
+            sender: PublicKeyBytes(next()),
+            recipient: PublicKeyBytes(next()),
+            nonce: Nonce(u64::from_be_bytes(uint::<8>(next(), "nonce")?)),
+            timestamp_nanos: u128::from_be_bytes(uint::<16>(next(), "timestampNanos")?),
+            claim: AbiClaim::try_from(uint::<1>(next(), "claim")?[0])?,
+            token_id: TokenId(next()),
+            amount: Amount::from_be_bytes(next()),
+        })
+    }
+
+    /// Encode as `abi.encode(FastSetTransaction)`, in the assumed layout.
+    pub fn encode(&self) -> Vec<u8> {
+        let words: [[u8; WORD]; 7] = [
+            self.sender.0,
+            self.recipient.0,
+            left_pad(&self.nonce.0.to_be_bytes()),
+            left_pad(&self.timestamp_nanos.to_be_bytes()),
+            left_pad(&[self.claim as u8]),
+            self.token_id.0,
+            self.amount.to_be_bytes(),
+        ];
+        words.concat()
+    }
+
+    /// Check that this is the transaction of `certificate`, naming every field that differs. The
+    /// fields left out of the assumed layout are reported as unchecked.
+    pub fn check_certificate(
+        &self,
+        certificate: &TransactionCertificate,
+    ) -> Result<AbiCheck, AbiError> {
+        let expected = Self::from_transaction(&certificate.envelope.transaction)?;
+        let equal = [
+            self.sender == expected.sender,
+            self.recipient == expected.recipient,
+            self.nonce == expected.nonce,
+            self.timestamp_nanos == expected.timestamp_nanos,
+            self.claim == expected.claim,
+            self.token_id == expected.token_id,
+            self.amount == expected.amount,
+        ];
+        let mismatches: Vec<_> = ABI_FIELDS
+            .into_iter()
+            .zip(equal)
+            .filter(|(_, equal)| !equal)
+            .map(|(name, _)| name)
+            .collect();
+        if !mismatches.is_empty() {
+            return Err(AbiError::Mismatch(mismatches));
+        }
+        Ok(AbiCheck {
+            unchecked: UNCHECKED_FIELDS.to_vec(),
+        })
+    }
+}
+
+/// The ABI encoding of the transaction of `certificate` in the assumed layout, which
+/// `proxy_evmSignCertificate` may not follow.
+pub fn encode_certificate(certificate: &TransactionCertificate) -> Result<Vec<u8>, AbiError> {
+    AbiTransaction::from_transaction(&certificate.envelope.transaction)
+        .map(|transaction| transaction.encode())
+}
+
+/// A word that differs between two encodings. Words past the end of one encoding are `None` in
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordDiff {
+    /// Byte offset of the word
+    pub offset: usize,
+    /// Name of the field at that offset, if within [ABI_FIELDS]
+    pub field: Option<&'static str>,
+    pub expected: Option<Vec<u8>>,
+    pub actual: Option<Vec<u8>>,
+}
+
+impl fmt::Display for WordDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |word: &Option<Vec<u8>>| word.as_ref().map_or("-".to_string(), hex::encode);
+        writeln!(
+            f,
+            "{:#06x} {}",
+            self.offset,
+            self.field.unwrap_or("(past the end of the transaction)")
+        )?;
+        writeln!(f, "  expected {}", show(&self.expected))?;
+        write!(f, "  actual   {}", show(&self.actual))
+    }
+}
+
+/// Compare two encodings word by word, for instance the output of [encode_certificate] and the
+/// bytes signed by the proxy. The result is empty when they are identical.
+pub fn diff_words(expected: &[u8], actual: &[u8]) -> Vec<WordDiff> {
+    let word =
+        |bytes: &[u8], index: usize| bytes.chunks(WORD).nth(index).map(|chunk| chunk.to_vec());
+    let words = expected.len().max(actual.len()).div_ceil(WORD);
+    (0..words)
+        .filter_map(|index| {
+            let (expected, actual) = (word(expected, index), word(actual, index));
+            (expected != actual).then(|| WordDiff {
+                offset: index * WORD,
+                field: ABI_FIELDS.get(index).copied(),
+                expected,
+                actual,
+            })
+        })
+        .collect()
+}
+
+/// A value of a function argument: a static type that fits in one word, or `bytes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Word([u8; WORD]),
+    Bytes(Vec<u8>),
+}
+
+impl AbiValue {
+    /// A `uintN` argument.
+    pub fn uint(value: u128) -> Self {
+        Self::Word(left_pad(&value.to_be_bytes()))
+    }
+}
+
+/// `abi.encode` of a list of arguments: their heads, with the offset of each `bytes` argument, then
+/// the length and right-padded contents of the `bytes` arguments.
+pub fn encode_values(values: &[AbiValue]) -> Vec<u8> {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    for value in values {
+        match value {
+            AbiValue::Word(word) => head.extend_from_slice(word),
+            AbiValue::Bytes(bytes) => {
+                let offset = values.len() * WORD + tail.len();
+                head.extend_from_slice(&left_pad(&(offset as u64).to_be_bytes()));
+                tail.extend_from_slice(&left_pad(&(bytes.len() as u64).to_be_bytes()));
+                tail.extend_from_slice(bytes);
+                tail.resize(tail.len().next_multiple_of(WORD), 0);
+            }
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+fn left_pad(bytes: &[u8]) -> [u8; WORD] {
+    let mut word = [0; WORD];
+    word[WORD - bytes.len()..].copy_from_slice(bytes);
+    word
+}
+
+/// The low `N` bytes of a big-endian word, whose other bytes must be zero.
+fn uint<const N: usize>(word: [u8; WORD], name: &'static str) -> Result<[u8; N], AbiError> {
+    let (padding, value) = word.split_at(WORD - N);
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(AbiError::OutOfRange(name));
+    }
+    Ok(value.try_into().expect("N bytes"))
+}
+
+impl CrossSignResponse {
+    pub fn decode_transaction(&self) -> Result<AbiTransaction, AbiError> {
+        AbiTransaction::decode(&self.transaction)
+    }
+
+    /// Check that the signed ABI payload describes the transaction of `certificate`, the one given
+    /// to `proxy_evmSignCertificate`, in the fields of the assumed layout.
+    pub fn check_certificate(
+        &self,
+        certificate: &TransactionCertificate,
+    ) -> Result<AbiCheck, AbiError> {
+        self.decode_transaction()?.check_certificate(certificate)
+    }
+
+    /// The words where [CrossSignResponse::transaction] differs from the encoding of the
+    /// transaction of `certificate` in the assumed layout.
+    pub fn diff_encoding(
+        &self,
+        certificate: &TransactionCertificate,
+    ) -> Result<Vec<WordDiff>, AbiError> {
+        Ok(diff_words(
+            &encode_certificate(certificate)?,
+            &self.transaction,
+        ))
+    }
+}
@@ -1,9 +1,9 @@
 use jsonrpsee::core::ClientError;
-use jsonrpsee::http_client::HttpClient;
+use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
 
 use crate::fastset_types::{
-    AccountInfoResponse, Amount, FastSetAddress, PublicKeyBytes,
-    SignatureOrMultiSig, TokenId, Transaction,
+    AccountInfoResponse, Amount, FastSetAddress, PublicKeyBytes, SignatureOrMultiSig, TokenId,
+    Transaction,
 };
 
 use crate::client::{ProxyRpcClient, ProxySubmitTransactionResult};
@@ -12,7 +12,13 @@ use crate::client::{ProxyRpcClient, ProxySubmitTransactionResult};
 // Proxy RPC Wrappers
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
-pub async fn get_account_info(client: &HttpClient, address: PublicKeyBytes) -> AccountInfoResponse {
+// The wrappers accept any transport implementing the generated `ProxyRpcClient` trait, i.e. both
+// `jsonrpsee::http_client::HttpClient` and the `WsClient` returned by `connect_ws`.
+
+pub async fn get_account_info(
+    client: &impl ProxyRpcClient,
+    address: PublicKeyBytes,
+) -> AccountInfoResponse {
     client
         .get_account_info(address, None, None, None)
         .await
@@ -20,7 +26,7 @@ pub async fn get_account_info(client: &HttpClient, address: PublicKeyBytes) -> A
 }
 
 pub async fn faucet_drip(
-    client: &HttpClient,
+    client: &impl ProxyRpcClient,
     recipient: FastSetAddress,
     amount: Amount,
 ) -> Result<(), ClientError> {
@@ -30,9 +36,15 @@ pub async fn faucet_drip(
 }
 
 pub async fn submit_transaction(
-    client: &HttpClient,
+    client: &impl ProxyRpcClient,
     transaction: Transaction,
     signature: SignatureOrMultiSig,
 ) -> Result<ProxySubmitTransactionResult, ClientError> {
     client.submit_transaction(transaction, signature).await
 }
+
+/// Open a WebSocket connection to the proxy (e.g. `wss://proxy.fastset.xyz`). Unlike the HTTP
+/// client, it can carry the `proxy_subscribe*` subscriptions.
+pub async fn connect_ws(url: &str) -> Result<WsClient, ClientError> {
+    WsClientBuilder::default().build(url).await
+}
@@ -0,0 +1,286 @@
+//! A local archive of settled certificates, kept as proof of payment after validators pruned them.
+//!
+//! The archive is a file of [JSON lines](crate::jsonl): a header with the format version, then one
+//! [TransactionCertificate] per line, in the order they were archived. Certificates are only ever
+//! appended, and each one is synced to disk before [CertificateArchive::insert] returns. Indexes by
+//! [signing_digest], sender and nonce, recipient and token are rebuilt in memory when the archive
+//! is opened.
+
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+use std::path::{Path, PathBuf};
+
+use futures::{Stream, StreamExt};
+use jsonrpsee::core::ClientError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::client::ProxySubmitTransactionResult;
+use crate::committee::{CertificateError, Committee};
+use crate::fastset_types::{
+    AccountInfoResponse, FastSetAddress, Nonce, TokenId, TransactionCertificate,
+};
+use crate::jsonl::{JsonLines, JsonLinesError};
+use crate::offline::signing_digest;
+
+/// Version of the archive file format
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Unsupported archive version {0}, expected {ARCHIVE_VERSION}.")]
+    UnsupportedVersion(u32),
+    #[error("Cannot access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Line {line} of {path} is malformed: {source}")]
+    Format {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("Another certificate for nonce {nonce} of {sender} is already archived.")]
+    Conflict {
+        sender: FastSetAddress,
+        nonce: Nonce,
+    },
+    #[error("Invalid certificate: {0}")]
+    Certificate(#[from] CertificateError),
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+impl From<JsonLinesError> for ArchiveError {
+    fn from(error: JsonLinesError) -> Self {
+        match error {
+            JsonLinesError::Io { path, source } => Self::Io { path, source },
+            JsonLinesError::Format { path, line, source } => Self::Format { path, line, source },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// Certificates stored in a file, with indexed lookups.
+///
+/// Lookups returning several certificates order them by the timestamp of their transaction, and
+/// take a range of timestamps in nanoseconds since the Unix epoch, such as `..` for all of them.
+pub struct CertificateArchive {
+    file: JsonLines,
+    committee: Option<Committee>,
+    certificates: Vec<TransactionCertificate>,
+    by_hash: HashMap<[u8; 32], usize>,
+    by_sender: HashMap<FastSetAddress, Vec<usize>>,
+    by_sender_nonce: HashMap<(FastSetAddress, Nonce), usize>,
+    by_recipient: HashMap<FastSetAddress, Vec<usize>>,
+    by_token: HashMap<TokenId, Vec<usize>>,
+}
+
+impl CertificateArchive {
+    /// Open the archive at `path`, creating it if it does not exist. With a `committee`, only
+    /// certificates signed by a quorum of it are archived.
+    pub fn open(path: &Path, committee: Option<Committee>) -> Result<Self, ArchiveError> {
+        let (file, lines) = JsonLines::open(path)?;
+        let mut archive = Self {
+            file,
+            committee,
+            certificates: Vec::new(),
+            by_hash: HashMap::new(),
+            by_sender: HashMap::new(),
+            by_sender_nonce: HashMap::new(),
+            by_recipient: HashMap::new(),
+            by_token: HashMap::new(),
+        };
+        let mut lines = lines.iter();
+        match lines.next() {
+            None => archive.file.append(&Header {
+                version: ARCHIVE_VERSION,
+            })?,
+            Some(header) => {
+                let header: Header = archive.file.parse(1, header)?;
+                if header.version != ARCHIVE_VERSION {
+                    return Err(ArchiveError::UnsupportedVersion(header.version));
+                }
+            }
+        }
+        for (index, line) in lines.enumerate() {
+            let certificate = archive.file.parse(index + 2, line)?;
+            archive.index(certificate)?;
+        }
+        Ok(archive)
+    }
+
+    /// Archive `certificate`, unless it already is. Returns whether it was added.
+    ///
+    /// Fails on a different certificate for the same sender and nonce, which is evidence of
+    /// equivocation rather than a payment to record.
+    pub fn insert(&mut self, certificate: TransactionCertificate) -> Result<bool, ArchiveError> {
+        if self
+            .by_hash
+            .contains_key(&signing_digest(&certificate.envelope.transaction))
+        {
+            return Ok(false);
+        }
+        if let Some(committee) = &self.committee {
+            committee.verify_certificate(&certificate)?;
+        }
+        self.check_conflict(&certificate)?;
+        self.file.append(&certificate)?;
+        self.index(certificate)
+    }
+
+    /// Archive the certificate of a successful `proxy_submitTransaction`.
+    pub fn insert_submit_result(
+        &mut self,
+        result: &ProxySubmitTransactionResult,
+    ) -> Result<bool, ArchiveError> {
+        match result {
+            ProxySubmitTransactionResult::Success(certificate) => self.insert(certificate.clone()),
+            _ => Ok(false),
+        }
+    }
+
+    /// Archive the certificates requested with `certificate_by_nonce`. Returns how many were added.
+    pub fn insert_account_info(
+        &mut self,
+        info: &AccountInfoResponse,
+    ) -> Result<usize, ArchiveError> {
+        let mut added = 0;
+        for certificate in info.requested_certificates.iter().flatten() {
+            added += usize::from(self.insert(certificate.clone())?);
+        }
+        Ok(added)
+    }
+
+    /// Archive the certificates of a history stream, such as
+    /// [settled_certificates](crate::subscription::settled_certificates), until it ends. Returns
+    /// how many were added.
+    pub async fn ingest<S>(&mut self, mut certificates: S) -> Result<usize, ArchiveError>
+    where
+        S: Stream<Item = Result<TransactionCertificate, ClientError>> + Unpin,
+    {
+        let mut added = 0;
+        while let Some(certificate) = certificates.next().await {
+            added += usize::from(self.insert(certificate?)?);
+        }
+        Ok(added)
+    }
+
+    pub fn len(&self) -> usize {
+        self.certificates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.certificates.is_empty()
+    }
+
+    /// The certificate of the transaction whose [signing_digest] is `hash`.
+    ///
+    /// That digest is computed by these examples, and is not a hash reported by the proxy, such as
+    /// [TransactionInfo::hash](crate::fastset_types::TransactionInfo::hash).
+    pub fn by_hash(&self, hash: &[u8; 32]) -> Option<&TransactionCertificate> {
+        self.by_hash
+            .get(hash)
+            .map(|&index| &self.certificates[index])
+    }
+
+    pub fn by_sender_nonce(
+        &self,
+        sender: FastSetAddress,
+        nonce: Nonce,
+    ) -> Option<&TransactionCertificate> {
+        self.by_sender_nonce
+            .get(&(sender, nonce))
+            .map(|&index| &self.certificates[index])
+    }
+
+    pub fn by_sender(
+        &self,
+        sender: FastSetAddress,
+        time: impl RangeBounds<u128>,
+    ) -> Vec<&TransactionCertificate> {
+        self.select(self.by_sender.get(&sender), time)
+    }
+
+    /// Certificates crediting `recipient`, including through the operations of a batch.
+    pub fn by_recipient(
+        &self,
+        recipient: FastSetAddress,
+        time: impl RangeBounds<u128>,
+    ) -> Vec<&TransactionCertificate> {
+        self.select(self.by_recipient.get(&recipient), time)
+    }
+
+    pub fn by_token(
+        &self,
+        token_id: TokenId,
+        time: impl RangeBounds<u128>,
+    ) -> Vec<&TransactionCertificate> {
+        self.select(self.by_token.get(&token_id), time)
+    }
+
+    /// All archived certificates in the time range.
+    pub fn all(&self, time: impl RangeBounds<u128>) -> Vec<&TransactionCertificate> {
+        let indexes: Vec<usize> = (0..self.certificates.len()).collect();
+        self.select(Some(&indexes), time)
+    }
+
+    fn select(
+        &self,
+        indexes: Option<&Vec<usize>>,
+        time: impl RangeBounds<u128>,
+    ) -> Vec<&TransactionCertificate> {
+        let mut selected: Vec<_> = indexes
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.certificates[index])
+            .filter(|certificate| time.contains(&certificate.envelope.transaction.timestamp_nanos))
+            .collect();
+        selected.sort_by_key(|certificate| certificate.envelope.transaction.timestamp_nanos);
+        selected
+    }
+
+    fn check_conflict(&self, certificate: &TransactionCertificate) -> Result<(), ArchiveError> {
+        let transaction = &certificate.envelope.transaction;
+        if self
+            .by_sender_nonce
+            .contains_key(&(transaction.sender, transaction.nonce))
+        {
+            return Err(ArchiveError::Conflict {
+                sender: transaction.sender,
+                nonce: transaction.nonce,
+            });
+        }
+        Ok(())
+    }
+
+    fn index(&mut self, certificate: TransactionCertificate) -> Result<bool, ArchiveError> {
+        let transaction = &certificate.envelope.transaction;
+        let hash = signing_digest(transaction);
+        if self.by_hash.contains_key(&hash) {
+            return Ok(false);
+        }
+        self.check_conflict(&certificate)?;
+        let index = self.certificates.len();
+        self.by_hash.insert(hash, index);
+        self.by_sender
+            .entry(transaction.sender)
+            .or_default()
+            .push(index);
+        self.by_sender_nonce
+            .insert((transaction.sender, transaction.nonce), index);
+        for recipient in transaction.recipients() {
+            self.by_recipient.entry(recipient).or_default().push(index);
+        }
+        for token_id in transaction.claim.token_ids() {
+            self.by_token.entry(token_id).or_default().push(index);
+        }
+        self.certificates.push(certificate);
+        Ok(true)
+    }
+}
@@ -0,0 +1,338 @@
+//! An independent check of the balances reported by a proxy, replaying the certificates of an
+//! account from a known opening state.
+//!
+//! Outgoing transactions are replayed in nonce order, and incoming transfers and mints are credited,
+//! so the certificates must include those received from other senders, such as those a
+//! [CertificateArchive](crate::archive::CertificateArchive) returns for the account as recipient.
+//!
+//! Transfers and mints are replayed, on their own or in a batch, and so are the claims that move no
+//! balance, like token management and state updates. A token creation credits a token whose id is
+//! not computed here, so it is reported as a [HistoryIssue::UnreplayableClaim] instead of being
+//! skipped silently.
+
+use std::collections::BTreeMap;
+
+use jsonrpsee::core::ClientError;
+
+use crate::client::ProxyRpcClient;
+use crate::fastset_types::{
+    AccountInfoResponse, Amount, Balance, ClaimType, FastSetAddress, Nonce, Operation, TokenId,
+    Transaction, TransactionCertificate,
+};
+
+/// The state of the account before the replayed certificates, for instance zero at its creation.
+/// Faucet drips and other credits without a certificate belong here.
+#[derive(Debug, Clone, Default)]
+pub struct Opening {
+    pub next_nonce: Nonce,
+    pub balances: BTreeMap<TokenId, Balance>,
+}
+
+/// A problem with the certificate history itself, independent of any proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryIssue {
+    /// No certificate for the outgoing transactions with nonces `from..to`; the replay stops there
+    MissingNonces { from: Nonce, to: Nonce },
+    /// Different certificates for the same outgoing nonce; the first one is replayed
+    ConflictingCertificates(Nonce),
+    /// A balance leaving the range of [Balance]; the certificate is not applied
+    Overflow { token_id: TokenId, nonce: Nonce },
+    /// A certificate with the sender's `nonce` whose claim is not replayed, so the balances it
+    /// changes are unchecked
+    UnreplayableClaim { nonce: Nonce, claim: &'static str },
+}
+
+/// A difference between the replayed state and the one reported by a proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    Balance {
+        token_id: TokenId,
+        expected: Balance,
+        reported: Balance,
+        /// The replayed certificates moving the token in or out of the account
+        certificates: Vec<TransactionCertificate>,
+    },
+    Nonce {
+        expected: Nonce,
+        reported: Nonce,
+        /// The replayed outgoing certificates the proxy does not account for, if it is behind
+        certificates: Vec<TransactionCertificate>,
+    },
+}
+
+/// The expected state of an account after replaying its certificates.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub address: FastSetAddress,
+    pub next_nonce: Nonce,
+    pub balances: BTreeMap<TokenId, Balance>,
+    pub issues: Vec<HistoryIssue>,
+    /// Applied certificates, by token moved
+    applied: BTreeMap<TokenId, Vec<TransactionCertificate>>,
+    /// Outgoing certificates, in nonce order
+    outgoing: Vec<TransactionCertificate>,
+}
+
+impl Replay {
+    /// Replay `certificates` on `opening`. Certificates neither sent nor received by `address`
+    /// are ignored, and so are the duplicates of a certificate and outgoing ones before
+    /// `opening.next_nonce`.
+    pub fn new<'a>(
+        address: FastSetAddress,
+        opening: &Opening,
+        certificates: impl IntoIterator<Item = &'a TransactionCertificate>,
+    ) -> Self {
+        let mut outgoing: BTreeMap<Nonce, &TransactionCertificate> = BTreeMap::new();
+        let mut incoming = Vec::new();
+        let mut issues = Vec::new();
+        for certificate in certificates {
+            let transaction = &certificate.envelope.transaction;
+            if transaction.sender == address {
+                if transaction.nonce < opening.next_nonce {
+                    continue;
+                }
+                match outgoing.get(&transaction.nonce) {
+                    None => {
+                        outgoing.insert(transaction.nonce, certificate);
+                    }
+                    Some(first) if first.envelope == certificate.envelope => {}
+                    Some(_) => {
+                        issues.push(HistoryIssue::ConflictingCertificates(transaction.nonce))
+                    }
+                }
+            } else if transaction.recipients().contains(&address)
+                && !incoming.contains(&certificate)
+            {
+                incoming.push(certificate);
+            }
+        }
+        let mut replay = Self {
+            address,
+            next_nonce: opening.next_nonce,
+            balances: opening.balances.clone(),
+            issues,
+            applied: BTreeMap::new(),
+            outgoing: Vec::new(),
+        };
+        for (nonce, certificate) in outgoing {
+            if nonce != replay.next_nonce {
+                replay.issues.push(HistoryIssue::MissingNonces {
+                    from: replay.next_nonce,
+                    to: nonce,
+                });
+                break;
+            }
+            replay.apply(certificate);
+            replay.outgoing.push(certificate.clone());
+            replay.next_nonce = Nonce(nonce.0 + 1);
+        }
+        for certificate in incoming {
+            replay.apply(certificate);
+        }
+        replay
+    }
+
+    /// The balance of `token_id`, zero for tokens never held.
+    pub fn balance(&self, token_id: TokenId) -> Balance {
+        self.balances.get(&token_id).copied().unwrap_or_default()
+    }
+
+    /// The custom tokens of the replay, to request with `token_balances_filter` so that
+    /// [Replay::compare] sees all of them.
+    pub fn token_ids(&self) -> Vec<TokenId> {
+        self.balances
+            .keys()
+            .filter(|token_id| **token_id != TokenId::native())
+            .copied()
+            .collect()
+    }
+
+    /// Compare the replay with account info reported for the same address. Only the tokens listed
+    /// in `info.token_balance` are compared, as the proxy may not report all tokens held.
+    pub fn compare(&self, info: &AccountInfoResponse) -> Vec<Discrepancy> {
+        let mut discrepancies = Vec::new();
+        if info.next_nonce != self.next_nonce {
+            let certificates = self
+                .outgoing
+                .iter()
+                .filter(|certificate| certificate.envelope.transaction.nonce >= info.next_nonce)
+                .cloned()
+                .collect();
+            discrepancies.push(Discrepancy::Nonce {
+                expected: self.next_nonce,
+                reported: info.next_nonce,
+                certificates,
+            });
+        }
+        let reported = std::iter::once((TokenId::native(), info.balance))
+            .chain(info.token_balance.iter().copied());
+        for (token_id, reported) in reported {
+            let expected = self.balance(token_id);
+            if expected != reported {
+                discrepancies.push(Discrepancy::Balance {
+                    token_id,
+                    expected,
+                    reported,
+                    certificates: self.applied.get(&token_id).cloned().unwrap_or_default(),
+                });
+            }
+        }
+        discrepancies
+    }
+
+    /// Apply the movements of `certificate` to the balances, all of them or, if one overflows,
+    /// none.
+    fn apply(&mut self, certificate: &TransactionCertificate) {
+        let transaction = &certificate.envelope.transaction;
+        let Some(movements) = movements(transaction) else {
+            return self.issues.push(HistoryIssue::UnreplayableClaim {
+                nonce: transaction.nonce,
+                claim: transaction.claim.name(),
+            });
+        };
+        let mut balances = BTreeMap::new();
+        for movement in movements {
+            if movement.from != Some(self.address) && movement.to != self.address {
+                continue;
+            }
+            let amount = Balance::from(movement.amount);
+            let mut balance = balances
+                .get(&movement.token_id)
+                .copied()
+                .unwrap_or_else(|| self.balance(movement.token_id));
+            if movement.from == Some(self.address) {
+                balance = match balance.checked_sub(amount) {
+                    Some(balance) => balance,
+                    None => return self.overflow(movement.token_id, transaction.nonce),
+                };
+            }
+            if movement.to == self.address {
+                balance = match balance.checked_add(amount) {
+                    Some(balance) => balance,
+                    None => return self.overflow(movement.token_id, transaction.nonce),
+                };
+            }
+            balances.insert(movement.token_id, balance);
+        }
+        for (token_id, balance) in balances {
+            self.balances.insert(token_id, balance);
+            self.applied
+                .entry(token_id)
+                .or_default()
+                .push(certificate.clone());
+        }
+    }
+
+    fn overflow(&mut self, token_id: TokenId, nonce: Nonce) {
+        self.issues.push(HistoryIssue::Overflow { token_id, nonce });
+    }
+}
+
+/// A balance change of a transaction.
+struct Movement {
+    token_id: TokenId,
+    /// The debited account, none for a mint
+    from: Option<FastSetAddress>,
+    to: FastSetAddress,
+    amount: Amount,
+}
+
+/// The balance changes of `transaction`, or `None` if its claim is not replayed.
+fn movements(transaction: &Transaction) -> Option<Vec<Movement>> {
+    let transfer = |token_id, to, amount| Movement {
+        token_id,
+        from: Some(transaction.sender),
+        to,
+        amount,
+    };
+    let mint = |token_id, to, amount| Movement {
+        token_id,
+        from: None,
+        to,
+        amount,
+    };
+    match &transaction.claim {
+        ClaimType::TokenTransfer(claim) => Some(vec![transfer(
+            claim.token_id,
+            transaction.recipient,
+            claim.amount,
+        )]),
+        ClaimType::Mint(claim) => Some(vec![mint(
+            claim.token_id,
+            transaction.recipient,
+            claim.amount,
+        )]),
+        ClaimType::Batch(bundle) => {
+            let mut movements = Vec::new();
+            for operation in &bundle.0 {
+                match operation {
+                    Operation::TokenTransfer(operation) => movements.push(transfer(
+                        operation.token_id,
+                        operation.recipient,
+                        operation.amount,
+                    )),
+                    Operation::Mint(operation) => movements.push(mint(
+                        operation.token_id,
+                        operation.recipient,
+                        operation.amount,
+                    )),
+                    Operation::TokenCreation(_) => return None,
+                    Operation::TokenManagement(_)
+                    | Operation::StateInitialization(_)
+                    | Operation::StateUpdate(_)
+                    | Operation::ExternalClaim(_)
+                    | Operation::StateReset(_)
+                    | Operation::JoinCommittee(_)
+                    | Operation::LeaveCommittee
+                    | Operation::ChangeCommittee(_) => {}
+                }
+            }
+            Some(movements)
+        }
+        ClaimType::TokenCreation(_) => None,
+        ClaimType::TokenManagement(_)
+        | ClaimType::StateInitialization(_)
+        | ClaimType::StateUpdate(_)
+        | ClaimType::ExternalClaim(_)
+        | ClaimType::StateReset(_)
+        | ClaimType::JoinCommittee(_)
+        | ClaimType::LeaveCommittee
+        | ClaimType::ChangeCommittee(_) => Some(Vec::new()),
+    }
+}
+
+/// The result of [audit_account].
+#[derive(Debug, Clone)]
+pub struct Audit {
+    pub replay: Replay,
+    pub info: AccountInfoResponse,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl Audit {
+    /// Whether the history is complete and matches the proxy.
+    pub fn is_clean(&self) -> bool {
+        self.replay.issues.is_empty() && self.discrepancies.is_empty()
+    }
+}
+
+/// Replay `certificates` of `address` and compare the result with the account info returned by
+/// `client`, requesting the balances of all the tokens of the replay, or of all tokens held if the
+/// replay has none.
+pub async fn audit_account<'a>(
+    client: &impl ProxyRpcClient,
+    address: FastSetAddress,
+    opening: &Opening,
+    certificates: impl IntoIterator<Item = &'a TransactionCertificate>,
+) -> Result<Audit, ClientError> {
+    let replay = Replay::new(address, opening, certificates);
+    let info = client
+        .get_account_info(address, Some(replay.token_ids()), None, None)
+        .await?;
+    let discrepancies = replay.compare(&info);
+    Ok(Audit {
+        replay,
+        info,
+        discrepancies,
+    })
+}
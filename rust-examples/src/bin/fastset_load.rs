@@ -0,0 +1,103 @@
+//! Fire transfers at a proxy and report latencies, errors and throughput as JSON.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use clap::Parser;
+use jsonrpsee::http_client::HttpClientBuilder;
+use rust_examples::fastset_types::Amount;
+use rust_examples::load::{LoadConfig, LoadError, run_load};
+use rust_examples::mock_proxy::MockProxy;
+
+#[derive(Parser)]
+#[command(about = "Measure how many FastSet transfers a proxy settles, and how fast")]
+struct Cli {
+    /// Proxy to load; an in-process mock proxy is started if omitted
+    #[arg(long, env = "FASTSET_PROXY_URL")]
+    proxy_url: Option<String>,
+    /// Number of accounts to fund with the faucet and send from
+    #[arg(long, default_value_t = 16)]
+    accounts: usize,
+    /// Number of transfers in flight at once
+    #[arg(long, default_value_t = 8)]
+    concurrency: usize,
+    /// Total number of transfers to submit
+    #[arg(long, default_value_t = 1_000)]
+    transfers: usize,
+    /// Cap on the rate of submissions, in transfers per second [default: no cap]
+    #[arg(long)]
+    rate: Option<f64>,
+    /// Native tokens requested from the faucet for each account, in least units
+    #[arg(long, default_value_t = 1_000_000)]
+    funding: u64,
+    /// Native tokens sent by each transfer, in least units
+    #[arg(long, default_value_t = 1)]
+    amount: u64,
+    /// File to write the JSON report to [default: standard output]
+    #[arg(long, short)]
+    out: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let config = LoadConfig {
+        accounts: cli.accounts,
+        concurrency: cli.concurrency,
+        transfers: cli.transfers,
+        rate: cli.rate,
+        funding: Amount::from(cli.funding),
+        amount: Amount::from(cli.amount),
+    };
+    if let Err(error) = config.period() {
+        eprintln!("error: {error}");
+        return ExitCode::FAILURE;
+    }
+    let (url, _mock) = match cli.proxy_url {
+        Some(url) => (url, None),
+        None => match MockProxy::new(4).start().await {
+            Ok((addr, handle)) => (format!("http://{addr}"), Some(handle)),
+            Err(error) => {
+                eprintln!("error: cannot start the mock proxy: {error}");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let client = match HttpClientBuilder::default().build(&url) {
+        Ok(client) => Arc::new(client),
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let report = match run_load(client, config).await {
+        Ok(report) => report,
+        Err(LoadError::Client(error)) => {
+            eprintln!("error: cannot fund the accounts on {url}: {error}");
+            return ExitCode::FAILURE;
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let json = serde_json::to_string_pretty(&report).expect("report serialization") + "\n";
+    match cli.out {
+        Some(path) => {
+            if let Err(error) = std::fs::write(&path, json) {
+                eprintln!("error: cannot write {}: {error}", path.display());
+                return ExitCode::FAILURE;
+            }
+            eprintln!(
+                "{} of {} transfers settled, {:.1} per second; report written to {}",
+                report.settled,
+                report.submitted,
+                report.throughput,
+                path.display()
+            );
+        }
+        None => print!("{json}"),
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,43 @@
+//! Write `docs/proxy/rpc.md` from the RPC trait and the wire type definitions.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use rust_examples::rpc_docs::render;
+
+#[derive(Parser)]
+#[command(about = "Generate the FastSet proxy RPC documentation")]
+struct Cli {
+    /// File to write the documentation to
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../docs/proxy/rpc.md"))]
+    out: PathBuf,
+    /// Only check that the file is up to date, exiting with an error otherwise
+    #[arg(long)]
+    check: bool,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let contents = match render() {
+        Ok(contents) => contents,
+        Err(error) => {
+            eprintln!("error: cannot parse the RPC trait: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if std::fs::read_to_string(&cli.out).is_ok_and(|existing| existing == contents) {
+        return ExitCode::SUCCESS;
+    }
+    if cli.check {
+        eprintln!("error: {} is out of date", cli.out.display());
+        eprintln!("Run `cargo run --features docs --bin fastset-rpc-docs` to regenerate it.");
+        return ExitCode::FAILURE;
+    }
+    if let Err(error) = std::fs::write(&cli.out, contents) {
+        eprintln!("error: cannot write {}: {error}", cli.out.display());
+        return ExitCode::FAILURE;
+    }
+    println!("wrote {}", cli.out.display());
+    ExitCode::SUCCESS
+}
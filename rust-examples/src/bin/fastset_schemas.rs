@@ -0,0 +1,47 @@
+//! Write the JSON Schema of every wire type to `<name>.json`, next to the RPC documentation by
+//! default.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use rust_examples::schema::{all_schemas, to_file_contents};
+
+#[derive(Parser)]
+#[command(about = "Write the JSON Schemas of the FastSet proxy wire types")]
+struct Cli {
+    /// Directory to write the schema files to
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../docs/proxy"))]
+    out_dir: PathBuf,
+    /// Only check that the files are up to date, exiting with an error otherwise
+    #[arg(long)]
+    check: bool,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut stale = Vec::new();
+    for (name, schema) in all_schemas() {
+        let path = cli.out_dir.join(format!("{name}.json"));
+        let contents = to_file_contents(&schema);
+        if std::fs::read_to_string(&path).is_ok_and(|existing| existing == contents) {
+            continue;
+        }
+        if cli.check {
+            stale.push(path);
+        } else if let Err(error) = std::fs::write(&path, contents) {
+            eprintln!("error: cannot write {}: {error}", path.display());
+            return ExitCode::FAILURE;
+        } else {
+            println!("wrote {}", path.display());
+        }
+    }
+    if stale.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+    for path in stale {
+        eprintln!("error: {} is out of date", path.display());
+    }
+    eprintln!("Run `cargo run --bin fastset-schemas` to regenerate the schemas.");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,37 @@
+//! Write the signing test vectors shared with the TypeScript example.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use rust_examples::test_vectors::{generate, to_file_contents};
+
+#[derive(Parser)]
+#[command(about = "Generate the FastSet signing test vectors")]
+struct Cli {
+    /// File to write the test vectors to
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../test-vectors/signing.json"))]
+    out: PathBuf,
+    /// Only check that the file is up to date, exiting with an error otherwise
+    #[arg(long)]
+    check: bool,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let contents = to_file_contents(&generate());
+    if std::fs::read_to_string(&cli.out).is_ok_and(|existing| existing == contents) {
+        return ExitCode::SUCCESS;
+    }
+    if cli.check {
+        eprintln!("error: {} is out of date", cli.out.display());
+        eprintln!("Run `cargo run --bin fastset-test-vectors` to regenerate it.");
+        return ExitCode::FAILURE;
+    }
+    if let Err(error) = std::fs::write(&cli.out, contents) {
+        eprintln!("error: cannot write {}: {error}", cli.out.display());
+        return ExitCode::FAILURE;
+    }
+    println!("wrote {}", cli.out.display());
+    ExitCode::SUCCESS
+}
@@ -1,16 +1,18 @@
 use crate::fastset_types::*;
+use jsonrpsee::core::{RpcResult, SubscriptionResult};
 use jsonrpsee::proc_macros::rpc;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum ProxySubmitTransactionResult {
     Success(TransactionCertificate),
     IncompleteVerifierSigs(),
     IncompleteMultiSig(),
 }
 
-#[rpc(client, namespace = "proxy")]
+#[rpc(client, server, namespace = "proxy")]
 pub trait ProxyRpc {
     /// Submit a signed transaction to the proxy to be submitted and settled on the network.
     ///
@@ -126,3 +128,52 @@ pub trait ProxyRpc {
         certificate: TransactionCertificate,
     ) -> RpcResult<CrossSignResponse>;
 }
+
+/// **Hypothetical** subscriptions, which are not part of the documented FastSet proxy API and are
+/// left out of the generated documentation. They sketch what these examples would use over a
+/// WebSocket connection, and are only served by the mock proxy of this crate. Against the real
+/// proxy, the `subscription` module falls back to polling `proxy_getAccountInfo`.
+#[rpc(client, server, namespace = "proxy")]
+pub trait ProxySubscriptionRpc {
+    /// Subscribe to changes of a specific account.
+    ///
+    /// Input:
+    /// - `address`: [FastSetAddress] of the designated account
+    /// - `token_balances_filter`: Same as for `proxy_getAccountInfo`
+    ///
+    /// Notifications:
+    /// - [AccountInfoResponse] for the account, sent on subscription and whenever it changes
+    #[subscription(
+        name = "subscribeAccountInfo" => "accountInfo",
+        unsubscribe = "unsubscribeAccountInfo",
+        item = AccountInfoResponse,
+        param_kind = map
+    )]
+    async fn subscribe_account_info(
+        &self,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+    ) -> SubscriptionResult;
+
+    /// Subscribe to the certificates of transactions sent by a specific account as they settle.
+    ///
+    /// Input:
+    /// - `address`: [FastSetAddress] of the transaction sender
+    /// - `start_nonce`: If passed, certificates are replayed from this nonce onwards (omitting
+    ///   those which have been pruned); otherwise only transactions settled after the subscription
+    ///   are sent.
+    ///
+    /// Notifications:
+    /// - [TransactionCertificate] for each settled transaction, in nonce order
+    #[subscription(
+        name = "subscribeCertificates" => "certificate",
+        unsubscribe = "unsubscribeCertificates",
+        item = TransactionCertificate,
+        param_kind = map
+    )]
+    async fn subscribe_certificates(
+        &self,
+        address: FastSetAddress,
+        start_nonce: Option<Nonce>,
+    ) -> SubscriptionResult;
+}
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::fastset_types::{
+    EnvelopeError, TransactionCertificate, TransactionEnvelope, ValidatedTransaction, ValidatorName,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CertificateError {
+    #[error("Signature from {0}, which is not a member of the committee.")]
+    UnknownValidator(ValidatorName),
+    #[error("Invalid signature from validator {0}.")]
+    InvalidSignature(ValidatorName),
+    #[error("Validator {0} signed more than once.")]
+    DuplicateSigner(ValidatorName),
+    #[error("Signed for a different transaction envelope.")]
+    EnvelopeMismatch,
+    #[error("Only {signers} signatures, a quorum is {quorum}.")]
+    NoQuorum { signers: usize, quorum: usize },
+    #[error("Invalid sender signature: {0}")]
+    Sender(EnvelopeError),
+}
+
+/// The set of validators whose signatures make up certificates.
+///
+/// Validators sign the [Transaction](crate::fastset_types::Transaction) of an envelope the same
+/// way its sender does.
+#[derive(Debug, Clone)]
+pub struct Committee {
+    pub members: Vec<ValidatorName>,
+    /// Minimum number of distinct members that must sign a certificate
+    pub quorum: usize,
+}
+
+impl Committee {
+    /// A committee of `3f + 1` members tolerating `f` faulty ones, with a quorum of `2f + 1`.
+    pub fn new(members: Vec<ValidatorName>) -> Self {
+        let quorum = members.len() - members.len().saturating_sub(1) / 3;
+        Self { members, quorum }
+    }
+
+    /// Check a single validator's vote for `envelope`.
+    pub fn verify_vote(
+        &self,
+        vote: &ValidatedTransaction,
+        envelope: &TransactionEnvelope,
+    ) -> Result<(), CertificateError> {
+        if vote.value != *envelope {
+            return Err(CertificateError::EnvelopeMismatch);
+        }
+        if !self.members.contains(&vote.validator) {
+            return Err(CertificateError::UnknownValidator(vote.validator));
+        }
+        vote.signature
+            .verify(&envelope.transaction, &vote.validator)
+            .map_err(|_| CertificateError::InvalidSignature(vote.validator))
+    }
+
+    /// Check that `certificate` carries a valid sender signature and valid signatures from a
+    /// quorum of distinct committee members.
+    pub fn verify_certificate(
+        &self,
+        certificate: &TransactionCertificate,
+    ) -> Result<(), CertificateError> {
+        let envelope = &certificate.envelope;
+        envelope.verify().map_err(CertificateError::Sender)?;
+        let mut signers = HashSet::new();
+        for (validator, signature) in &certificate.signatures {
+            if !self.members.contains(validator) {
+                return Err(CertificateError::UnknownValidator(*validator));
+            }
+            if !signers.insert(*validator) {
+                return Err(CertificateError::DuplicateSigner(*validator));
+            }
+            signature
+                .verify(&envelope.transaction, validator)
+                .map_err(|_| CertificateError::InvalidSignature(*validator))?;
+        }
+        if signers.len() < self.quorum {
+            return Err(CertificateError::NoQuorum {
+                signers: signers.len(),
+                quorum: self.quorum,
+            });
+        }
+        Ok(())
+    }
+}
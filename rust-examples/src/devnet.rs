@@ -0,0 +1,406 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use futures::future::join_all;
+use futures::stream::{FuturesUnordered, StreamExt};
+use jsonrpsee::core::{RpcResult, async_trait};
+use jsonrpsee::server::{Server, ServerHandle};
+use jsonrpsee::types::error::CALL_EXECUTION_FAILED_CODE;
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::{ProxyRpcServer, ProxySubmitTransactionResult};
+use crate::committee::Committee;
+use crate::fastset_types::{
+    AccountInfoResponse, Amount, CrossSignResponse, FastSetAddress, Nonce, NonceRange,
+    SignatureOrMultiSig, StateKey, TokenId, TokenInfoResponse, TokenMetadata, Transaction,
+    TransactionCertificate, TransactionEnvelope, ValidatedTransaction,
+};
+use crate::validator::{Faults, Validator, ValidatorError};
+
+/// The setup of a [Devnet] and the misbehaviour of its validators, typically loaded from a JSON
+/// test fixture:
+///
+/// ```json
+/// { "validators": 4, "faults": { "0": { "equivocate": true }, "3": { "delay_ms": 50 } } }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub validators: usize,
+    /// Faults of the validators with the given indices; the other validators are honest
+    #[serde(default)]
+    pub faults: BTreeMap<usize, Faults>,
+    /// How long the proxy waits for a validator before giving up on it, in milliseconds
+    #[serde(default = "Scenario::default_validator_timeout_ms")]
+    pub validator_timeout_ms: u64,
+    /// Whether the proxy checks validator votes before assembling them into a certificate.
+    /// Disabling this simulates a faulty proxy, whose certificates clients must reject.
+    #[serde(default = "Scenario::default_verify_votes")]
+    pub verify_votes: bool,
+}
+
+impl Scenario {
+    fn default_validator_timeout_ms() -> u64 {
+        1000
+    }
+
+    fn default_verify_votes() -> bool {
+        true
+    }
+
+    /// A network of honest validators.
+    pub fn honest(validators: usize) -> Self {
+        Self {
+            validators,
+            faults: BTreeMap::new(),
+            validator_timeout_ms: Self::default_validator_timeout_ms(),
+            verify_votes: Self::default_verify_votes(),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ScenarioError {
+    #[error("A devnet needs at least one validator.")]
+    NoValidators,
+    #[error("Faults are given for validator {index}, but there are only {validators} validators.")]
+    UnknownValidator { index: usize, validators: usize },
+}
+
+/// A local network of in-process validator stand-ins.
+pub struct Devnet {
+    validators: Vec<Arc<Validator>>,
+    committee: Committee,
+    validator_timeout: Duration,
+    verify_votes: bool,
+}
+
+impl Devnet {
+    /// A network of `validators` honest validators.
+    ///
+    /// Panics if `validators` is zero.
+    pub fn new(validators: usize) -> Self {
+        Self::from_scenario(&Scenario::honest(validators)).expect("a devnet needs a validator")
+    }
+
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        if scenario.validators == 0 {
+            return Err(ScenarioError::NoValidators);
+        }
+        if let Some(&index) = scenario.faults.keys().find(|&&i| i >= scenario.validators) {
+            return Err(ScenarioError::UnknownValidator {
+                index,
+                validators: scenario.validators,
+            });
+        }
+        let validators: Vec<_> = (0..scenario.validators)
+            .map(|_| Arc::new(Validator::new()))
+            .collect();
+        for (&index, faults) in &scenario.faults {
+            validators[index].set_faults(faults.clone());
+        }
+        let committee = Committee::new(validators.iter().map(|v| v.name()).collect());
+        Ok(Self {
+            validators,
+            committee,
+            validator_timeout: Duration::from_millis(scenario.validator_timeout_ms),
+            verify_votes: scenario.verify_votes,
+        })
+    }
+
+    pub fn committee(&self) -> &Committee {
+        &self.committee
+    }
+
+    pub fn validators(&self) -> &[Arc<Validator>] {
+        &self.validators
+    }
+
+    /// Register a custom token on every validator.
+    pub fn add_token(&self, token_id: TokenId, metadata: TokenMetadata) {
+        for validator in &self.validators {
+            validator.add_token(token_id, metadata.clone());
+        }
+    }
+
+    /// A proxy in front of all validators of this network.
+    pub fn proxy(&self) -> DevnetProxy {
+        DevnetProxy {
+            validators: self.validators.clone(),
+            committee: self.committee.clone(),
+            next_reader: Arc::default(),
+            validator_timeout: self.validator_timeout,
+            verify_votes: self.verify_votes,
+        }
+    }
+}
+
+/// A proxy implementation that settles transactions on a [Devnet] the way the FastSet proxy does:
+///
+/// - the envelope is sent to all validators, until a quorum of them has voted for it;
+/// - the votes are assembled into a [TransactionCertificate], which is sent to all validators for
+///   execution.
+///
+/// Validators that are behind on the sender's transactions are caught up with the certificates of
+/// an up-to-date validator, and validators that do not answer in time are given up on. Reads are
+/// served by the validators in turn, so they may reflect a lagging view just like on the real
+/// network.
+#[derive(Clone)]
+pub struct DevnetProxy {
+    validators: Vec<Arc<Validator>>,
+    committee: Committee,
+    next_reader: Arc<AtomicUsize>,
+    validator_timeout: Duration,
+    verify_votes: bool,
+}
+
+fn rejected(message: String) -> ErrorObjectOwned {
+    ErrorObject::owned(CALL_EXECUTION_FAILED_CODE, message, None::<()>)
+}
+
+impl DevnetProxy {
+    /// Serve the proxy API over HTTP and WebSocket on a free localhost port.
+    pub async fn start(self) -> std::io::Result<(SocketAddr, ServerHandle)> {
+        let server = Server::builder().build("127.0.0.1:0").await?;
+        let addr = server.local_addr()?;
+        Ok((addr, server.start(self.into_rpc())))
+    }
+
+    /// Wait for a validator's answer, up to the validator timeout.
+    async fn ask<T>(
+        &self,
+        request: impl Future<Output = Result<T, ValidatorError>>,
+    ) -> Result<T, ValidatorError> {
+        tokio::time::timeout(self.validator_timeout, request)
+            .await
+            .unwrap_or(Err(ValidatorError::Timeout))
+    }
+
+    /// Replay the certificates of `sender` that `validator` has missed, taking them from the most
+    /// advanced validator.
+    async fn catch_up(&self, validator: &Validator, sender: FastSetAddress, from: Nonce) {
+        let mut source = None;
+        for other in &self.validators {
+            if let Ok(info) = self.ask(other.account_info(sender, None, None, None)).await
+                && source
+                    .as_ref()
+                    .is_none_or(|(next, _)| info.next_nonce > *next)
+            {
+                source = Some((info.next_nonce, other));
+            }
+        }
+        let Some((next_nonce, source)) = source else {
+            return;
+        };
+        let range = NonceRange {
+            start: from,
+            limit: (next_nonce.0.saturating_sub(from.0)) as usize,
+        };
+        let Ok(info) = self
+            .ask(source.account_info(sender, None, None, Some(range)))
+            .await
+        else {
+            return;
+        };
+        for certificate in info.requested_certificates.unwrap_or_default() {
+            if self
+                .ask(validator.handle_certificate(&certificate, &self.committee))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    async fn request_vote(
+        &self,
+        validator: &Validator,
+        envelope: &TransactionEnvelope,
+    ) -> Result<ValidatedTransaction, ValidatorError> {
+        match validator.handle_transaction(envelope).await {
+            Err(ValidatorError::Lagging { next_nonce }) => {
+                self.catch_up(validator, envelope.transaction.sender, next_nonce)
+                    .await;
+                validator.handle_transaction(envelope).await
+            }
+            result => result,
+        }
+    }
+
+    async fn confirm(
+        &self,
+        validator: &Validator,
+        certificate: &TransactionCertificate,
+    ) -> Result<(), ValidatorError> {
+        match validator
+            .handle_certificate(certificate, &self.committee)
+            .await
+        {
+            Err(ValidatorError::Lagging { next_nonce }) => {
+                let sender = certificate.envelope.transaction.sender;
+                self.catch_up(validator, sender, next_nonce).await;
+                validator
+                    .handle_certificate(certificate, &self.committee)
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    /// Collect votes for `envelope` until a quorum is reached.
+    async fn certify(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<TransactionCertificate, ErrorObjectOwned> {
+        let mut votes: FuturesUnordered<_> = self
+            .validators
+            .iter()
+            .map(|validator| self.ask(self.request_vote(validator, envelope)))
+            .collect();
+        let mut signatures = Vec::new();
+        let mut errors = Vec::new();
+        while let Some(vote) = votes.next().await {
+            let vote = vote.and_then(|vote| {
+                if self.verify_votes {
+                    self.committee.verify_vote(&vote, envelope)?;
+                }
+                Ok(vote)
+            });
+            match vote {
+                Ok(vote) if signatures.iter().all(|(name, _)| *name != vote.validator) => {
+                    signatures.push((vote.validator, vote.signature));
+                    if signatures.len() >= self.committee.quorum {
+                        return Ok(TransactionCertificate {
+                            envelope: envelope.clone(),
+                            signatures,
+                        });
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => errors.push(err.to_string()),
+            }
+        }
+        Err(rejected(format!(
+            "Only {} of {} required validator signatures: {}",
+            signatures.len(),
+            self.committee.quorum,
+            errors.join("; ")
+        )))
+    }
+
+    /// Certify `envelope` and have all validators execute the certificate.
+    async fn settle(
+        &self,
+        envelope: TransactionEnvelope,
+    ) -> Result<TransactionCertificate, ErrorObjectOwned> {
+        let certificate = self.certify(&envelope).await?;
+        // Validators that fail to execute the certificate now will be caught up later.
+        join_all(
+            self.validators
+                .iter()
+                .map(|validator| self.ask(self.confirm(validator, &certificate))),
+        )
+        .await;
+        Ok(certificate)
+    }
+
+    /// The validators in the order in which they should serve the next read; the first one
+    /// changes with every read.
+    fn readers(&self) -> impl Iterator<Item = &Validator> {
+        let start = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.validators.len();
+        let (before, after) = self.validators.split_at(start);
+        after.iter().chain(before).map(Arc::as_ref)
+    }
+}
+
+impl From<ValidatorError> for ErrorObjectOwned {
+    fn from(err: ValidatorError) -> Self {
+        rejected(err.to_string())
+    }
+}
+
+#[async_trait]
+impl ProxyRpcServer for DevnetProxy {
+    async fn submit_transaction(
+        &self,
+        transaction: Transaction,
+        signature: SignatureOrMultiSig,
+    ) -> RpcResult<ProxySubmitTransactionResult> {
+        if let SignatureOrMultiSig::MultiSig(_) = signature {
+            return Ok(ProxySubmitTransactionResult::IncompleteMultiSig());
+        }
+        let envelope = TransactionEnvelope {
+            transaction,
+            signature,
+        };
+        // Like a real proxy, settle the transaction even if the client stops waiting for it.
+        let proxy = self.clone();
+        tokio::spawn(async move { proxy.settle(envelope).await })
+            .await
+            .map_err(|err| rejected(err.to_string()))?
+            .map(ProxySubmitTransactionResult::Success)
+    }
+
+    async fn faucet_drip(
+        &self,
+        recipient: FastSetAddress,
+        amount: Amount,
+        token_id: Option<TokenId>,
+    ) -> RpcResult<()> {
+        let token_id = token_id.unwrap_or_else(TokenId::native);
+        for validator in &self.validators {
+            validator.credit(recipient, token_id, amount)?;
+        }
+        Ok(())
+    }
+
+    async fn get_account_info(
+        &self,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+        state_key_filter: Option<Vec<StateKey>>,
+        certificate_by_nonce: Option<NonceRange>,
+    ) -> RpcResult<AccountInfoResponse> {
+        let mut last_error = None;
+        for validator in self.readers() {
+            match self
+                .ask(validator.account_info(
+                    address,
+                    token_balances_filter.clone(),
+                    state_key_filter.clone(),
+                    certificate_by_nonce.clone(),
+                ))
+                .await
+            {
+                Ok(info) => return Ok(info),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.expect("the devnet has validators").into())
+    }
+
+    async fn get_token_info(&self, token_ids: Vec<TokenId>) -> RpcResult<TokenInfoResponse> {
+        let mut last_error = None;
+        for validator in self.readers() {
+            match self.ask(validator.token_info(token_ids.clone())).await {
+                Ok(info) => return Ok(info),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.expect("the devnet has validators").into())
+    }
+
+    async fn evm_sign_certificate(
+        &self,
+        _certificate: TransactionCertificate,
+    ) -> RpcResult<CrossSignResponse> {
+        Err(rejected(
+            "the devnet proxy does not sign certificates for EVM".into(),
+        ))
+    }
+}
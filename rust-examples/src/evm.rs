@@ -0,0 +1,198 @@
+//! Checks of the [CrossSignResponse]s returned by `proxy_evmSignCertificate`, before they are
+//! relayed to an EVM chain.
+//!
+//! The proxy signs the ABI-encoded transaction with a secp256k1 key, as an EIP-191 version `0x45`
+//! ("Ethereum Signed Message") message. The signature is only worth relaying if it recovers to the
+//! address of that key, which settlement contracts are configured to trust.
+
+use std::fmt;
+use std::str::FromStr;
+
+use jsonrpsee::core::ClientError;
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+use crate::client::ProxyRpcClient;
+use crate::fastset_types::{CrossSignResponse, TransactionCertificate};
+
+/// The only [CrossSignResponse] format
+pub const EIP191_ABI_FORMAT: &str = "eip191-abi";
+
+#[derive(Debug, Error)]
+pub enum CrossSignError {
+    #[error("Unsupported cross-sign format {0:?}, expected {EIP191_ABI_FORMAT:?}.")]
+    UnsupportedFormat(String),
+    #[error("Malformed secp256k1 signature: {0}")]
+    MalformedSignature(String),
+    #[error("The signature does not recover to any address.")]
+    Unrecoverable,
+    #[error("The response is signed by {signer}, not by the trusted proxy signer {trusted}.")]
+    UntrustedSigner {
+        signer: EvmAddress,
+        trusted: EvmAddress,
+    },
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+/// A 20-byte EVM account address, written as `0x`-prefixed hex with the EIP-55 checksum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EvmAddress(pub [u8; 20]);
+
+impl EvmAddress {
+    /// The address of a secp256k1 public key: the last 20 bytes of the Keccak-256 hash of its
+    /// uncompressed encoding, without the `0x04` tag.
+    pub fn from_public_key(key: &VerifyingKey) -> Self {
+        let point = key.to_encoded_point(false);
+        let hash = keccak256(&point.as_bytes()[1..]);
+        let mut address = [0; 20];
+        address.copy_from_slice(&hash[12..]);
+        Self(address)
+    }
+}
+
+impl fmt::Display for EvmAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lower = hex::encode(self.0);
+        let hash = keccak256(lower.as_bytes());
+        let checksummed: String = lower
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0xf;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+        write!(f, "0x{checksummed}")
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EvmAddressError {
+    #[error("EVM addresses are 0x followed by 40 hex digits.")]
+    Malformed,
+    #[error("Wrong EIP-55 checksum; expected {0}.")]
+    Checksum(EvmAddress),
+}
+
+impl FromStr for EvmAddress {
+    type Err = EvmAddressError;
+
+    /// Parse a `0x`-prefixed address. Mixed-case addresses must have a valid EIP-55 checksum;
+    /// all-lowercase and all-uppercase ones have none.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").ok_or(EvmAddressError::Malformed)?;
+        let mut bytes = [0; 20];
+        hex::decode_to_slice(digits, &mut bytes).map_err(|_| EvmAddressError::Malformed)?;
+        let address = Self(bytes);
+        let has_lower = digits.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = digits.chars().any(|c| c.is_ascii_uppercase());
+        if has_lower && has_upper && address.to_string() != s {
+            return Err(EvmAddressError::Checksum(address));
+        }
+        Ok(address)
+    }
+}
+
+impl Serialize for EvmAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EvmAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// The EIP-191 version `0x45` digest of `message`, as computed by `eth_sign` and by
+/// `MessageHashUtils.toEthSignedMessageHash(bytes)` in OpenZeppelin.
+pub fn eip191_digest(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()));
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// Recover the address that produced a 65-byte `r || s || v` signature of `digest`, where `v` is
+/// 27 or 28 as for `ecrecover`, or 0 or 1.
+///
+/// Like OpenZeppelin's `ECDSA.recover`, signatures with a high `s` are rejected, since they are
+/// malleable copies of another valid signature.
+pub fn recover_signer(digest: &[u8; 32], signature: &[u8]) -> Result<EvmAddress, CrossSignError> {
+    let [rs @ .., v] = signature else {
+        return Err(CrossSignError::MalformedSignature("empty signature".into()));
+    };
+    if rs.len() != 64 {
+        return Err(CrossSignError::MalformedSignature(format!(
+            "{} bytes instead of 65",
+            signature.len()
+        )));
+    }
+    let signature = EcdsaSignature::from_slice(rs)
+        .map_err(|_| CrossSignError::MalformedSignature("r or s out of range".into()))?;
+    if signature.normalize_s().is_some() {
+        return Err(CrossSignError::MalformedSignature("high s value".into()));
+    }
+    let recovery_id = match v {
+        0 | 1 => *v,
+        27 | 28 => v - 27,
+        _ => return Err(CrossSignError::MalformedSignature(format!("invalid v {v}"))),
+    };
+    let recovery_id = RecoveryId::from_byte(recovery_id).expect("recovery IDs are 0 or 1");
+    let key = VerifyingKey::recover_from_prehash(digest, &signature, recovery_id)
+        .map_err(|_| CrossSignError::Unrecoverable)?;
+    Ok(EvmAddress::from_public_key(&key))
+}
+
+impl CrossSignResponse {
+    /// The address whose key signed the ABI-encoded transaction.
+    pub fn recover_signer(&self) -> Result<EvmAddress, CrossSignError> {
+        if self.format != EIP191_ABI_FORMAT {
+            return Err(CrossSignError::UnsupportedFormat(self.format.clone()));
+        }
+        recover_signer(&eip191_digest(&self.transaction), &self.signature_bytes()?)
+    }
+
+    /// The signature, with or without `0x` prefix, decoded from hex.
+    pub fn signature_bytes(&self) -> Result<Vec<u8>, CrossSignError> {
+        let digits = self.signature.strip_prefix("0x").unwrap_or(&self.signature);
+        hex::decode(digits).map_err(|err| CrossSignError::MalformedSignature(err.to_string()))
+    }
+
+    /// Check that the response is signed by `trusted`, the EVM address of the proxy's signing key.
+    pub fn verify(&self, trusted: &EvmAddress) -> Result<(), CrossSignError> {
+        let signer = self.recover_signer()?;
+        if signer != *trusted {
+            return Err(CrossSignError::UntrustedSigner {
+                signer,
+                trusted: *trusted,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Have the proxy sign `certificate` for EVM verification, and check that the response is signed
+/// by `trusted` before returning it.
+pub async fn evm_sign_certificate_verified(
+    client: &impl ProxyRpcClient,
+    certificate: TransactionCertificate,
+    trusted: &EvmAddress,
+) -> Result<CrossSignResponse, CrossSignError> {
+    let response = client.evm_sign_certificate(certificate).await?;
+    response.verify(trusted)?;
+    Ok(response)
+}
@@ -1,4 +1,5 @@
 use std::{
+    borrow::Cow,
     fmt::{Display, Formatter},
     str::FromStr,
 };
@@ -7,10 +8,17 @@ use bech32::{Bech32m, Hrp};
 use bnum::{BInt, BUint, cast::As as _, types::U256};
 use ed25519_dalek::{self as dalek, Signer};
 use rand::rngs::OsRng;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::{Deserialize, Serialize, de::Error as DesError};
 use thiserror::Error;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// A byte sequence that names an entity on or off the FastSet network,
+/// depending on the name of type which stores this value;
+/// typically encoded as an Ed25519 public key,
+/// but may have other formats including but not limited to:
+/// (a) Burn Address - a 32-byte string composed of all 0x00 bytes.
+/// Note: any funds sent to the burn address will be permenantly lost!
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct PublicKeyBytes(pub [u8; dalek::PUBLIC_KEY_LENGTH]);
 
 /// Convert address to string in default format (bech32m)
@@ -31,13 +39,68 @@ impl Display for PublicKeyBytes {
     }
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseAddressError {
+    #[error("Invalid bech32m address: {0}")]
+    Bech32(String),
+    #[error("Address prefix is {0}, expected {ADDRESS_HRP}.")]
+    WrongPrefix(String),
+    #[error("Address is {0} bytes long, expected {len}.", len = dalek::PUBLIC_KEY_LENGTH)]
+    WrongLength(usize),
+}
+
+/// Parse an address in the default format (bech32m)
+impl FromStr for PublicKeyBytes {
+    type Err = ParseAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, data) =
+            bech32::decode(s).map_err(|err| ParseAddressError::Bech32(err.to_string()))?;
+        if hrp != ADDRESS_HRP {
+            return Err(ParseAddressError::WrongPrefix(hrp.to_string()));
+        }
+        let length = data.len();
+        Ok(Self(
+            data.try_into()
+                .map_err(|_| ParseAddressError::WrongLength(length))?,
+        ))
+    }
+}
+
 pub type FastSetAddress = PublicKeyBytes;
 pub type ValidatorName = PublicKeyBytes;
 
 pub struct KeyPair(dalek::SigningKey);
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Nonce(u64);
+impl KeyPair {
+    pub fn public(&self) -> PublicKeyBytes {
+        PublicKeyBytes(self.0.verifying_key().to_bytes())
+    }
+
+    pub fn from_secret_bytes(secret: &[u8; dalek::SECRET_KEY_LENGTH]) -> Self {
+        KeyPair(dalek::SigningKey::from_bytes(secret))
+    }
+
+    pub fn secret_bytes(&self) -> [u8; dalek::SECRET_KEY_LENGTH] {
+        self.0.to_bytes()
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub struct Nonce(pub u64);
 
 impl Display for Nonce {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -45,10 +108,12 @@ impl Display for Nonce {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Quorum(u64);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Quorum(pub u64);
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 #[serde(transparent)]
 pub struct TokenId(pub [u8; 32]);
 
@@ -70,8 +135,19 @@ impl Display for TokenId {
     }
 }
 
+/// Parse a token ID from 64 hex digits, with or without a `0x` prefix
+impl FromStr for TokenId {
+    type Err = hex::FromHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(s.strip_prefix("0x").unwrap_or(s), &mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
 /// Encodes metadata about a custom token
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TokenMetadata {
     /// number of management operations applied to some token
     pub update_id: Nonce,
@@ -114,6 +190,21 @@ impl<'de> Deserialize<'de> for Amount {
     }
 }
 
+/// Amounts are hex strings in JSON, without a `0x` prefix.
+impl JsonSchema for Amount {
+    fn schema_name() -> Cow<'static, str> {
+        "Amount".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "An unsigned 256-bit number of least units, as a hex string without `0x` prefix",
+            "type": "string",
+            "pattern": "^[0-9a-fA-F]{1,64}$"
+        })
+    }
+}
+
 impl Display for Amount {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("Amount({})", self.0))
@@ -134,6 +225,10 @@ impl std::error::Error for ParseAmountError {}
 impl FromStr for Amount {
     type Err = ParseAmountError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The wire format has no sign, but `from_str_radix` would accept a leading `+`
+        if s.starts_with('+') {
+            return Err(ParseAmountError);
+        }
         Ok(Self(
             U256::from_str_radix(s, 16).map_err(|_| ParseAmountError)?,
         ))
@@ -146,6 +241,69 @@ impl From<u64> for Amount {
     }
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseDecimalError {
+    #[error("{0:?} is not a decimal number.")]
+    Invalid(String),
+    #[error("{0:?} has more than {1} decimals.")]
+    TooPrecise(String, u8),
+    #[error("{0:?} does not fit in an amount.")]
+    Overflow(String),
+}
+
+impl Amount {
+    /// Parse a decimal number of whole tokens, like `"12.5"`, into least units of a token with
+    /// `decimals` decimals.
+    pub fn from_decimal_str(s: &str, decimals: u8) -> Result<Self, ParseDecimalError> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() && fraction.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+            return Err(ParseDecimalError::Invalid(s.to_string()));
+        }
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > decimals as usize {
+            return Err(ParseDecimalError::TooPrecise(s.to_string(), decimals));
+        }
+        let digits = format!("0{whole}{fraction:0<width$}", width = decimals as usize);
+        U256::from_str_radix(&digits, 10)
+            .map(Self)
+            .map_err(|_| ParseDecimalError::Overflow(s.to_string()))
+    }
+
+    /// Format least units of a token with `decimals` decimals as a number of whole tokens,
+    /// without trailing zeros.
+    pub fn to_decimal_string(&self, decimals: u8) -> String {
+        format_decimal(self.0.to_str_radix(10), decimals)
+    }
+
+    /// The 32-byte big-endian encoding, as a Solidity `uint256`.
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, digit) in bytes.chunks_exact_mut(8).zip(self.0.digits().iter().rev()) {
+            chunk.copy_from_slice(&digit.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut digits = [0; 4];
+        for (digit, chunk) in digits.iter_mut().rev().zip(bytes.chunks_exact(8)) {
+            *digit = u64::from_be_bytes(chunk.try_into().expect("chunks of 8 bytes"));
+        }
+        Self(U256::from_digits(digits))
+    }
+}
+
+fn format_decimal(digits: String, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    match fraction.trim_end_matches('0') {
+        "" => whole.to_string(),
+        fraction => format!("{whole}.{fraction}"),
+    }
+}
+
 pub type I320 = BInt<5>;
 #[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Default, Debug)]
 pub struct Balance(I320);
@@ -186,12 +344,28 @@ impl<'de> Deserialize<'de> for Balance {
         if deserializer.is_human_readable() {
             Self::from_str(&String::deserialize(deserializer)?).map_err(DesError::custom)
         } else {
-            <[u64; 5]>::deserialize(deserializer)
-                .map(|digits| Self(I320::from_bits(BUint::<5>::from_digits(digits))))
+            let digits = <[u64; 5]>::deserialize(deserializer)?;
+            Self::try_from(I320::from_bits(BUint::<5>::from_digits(digits)))
+                .map_err(DesError::custom)
         }
     }
 }
 
+/// Balances are hex strings in JSON, without a `0x` prefix and with a `-` sign when negative.
+impl JsonSchema for Balance {
+    fn schema_name() -> Cow<'static, str> {
+        "Balance".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A signed number of least units between -(2^256 - 1) and 2^256 - 1, as a hex string without `0x` prefix",
+            "type": "string",
+            "pattern": "^-?[0-9a-fA-F]{1,64}$"
+        })
+    }
+}
+
 impl std::fmt::Display for Balance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -214,6 +388,9 @@ impl std::str::FromStr for Balance {
     type Err = BalanceFromStrError;
 
     fn from_str(src: &str) -> Result<Self, Self::Err> {
+        if src.starts_with('+') {
+            return Err(BalanceFromStrError::ParseIntError);
+        }
         Self::try_from(
             I320::from_str_radix(src, 16).map_err(|_| BalanceFromStrError::ParseIntError)?,
         )
@@ -232,16 +409,47 @@ impl TryFrom<I320> for Balance {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl From<Amount> for Balance {
+    fn from(value: Amount) -> Self {
+        Balance(value.0.as_())
+    }
+}
+
+impl Balance {
+    pub fn checked_add(self, other: Balance) -> Option<Balance> {
+        Balance::try_from(self.0.checked_add(other.0)?).ok()
+    }
+
+    pub fn checked_sub(self, other: Balance) -> Option<Balance> {
+        Balance::try_from(self.0.checked_sub(other.0)?).ok()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    /// Format least units of a token with `decimals` decimals as a number of whole tokens, like
+    /// [Amount::to_decimal_string].
+    pub fn to_decimal_string(&self, decimals: u8) -> String {
+        let magnitude = format_decimal(self.0.unsigned_abs().to_str_radix(10), decimals);
+        if self.is_negative() {
+            format!("-{magnitude}")
+        } else {
+            magnitude
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct UserData(pub Option<[u8; 32]>);
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct StateKey(pub [u8; 32]);
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct State(pub [u8; 32]);
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NonceRange {
     pub start: Nonce,
     pub limit: usize,
@@ -270,8 +478,23 @@ where
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Signature(#[serde(with = "serde_arrays")] pub ed25519::SignatureBytes);
+/// An Ed25519 signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Signature(
+    #[serde(with = "serde_arrays")]
+    #[schemars(schema_with = "signature_bytes_schema")]
+    pub ed25519::SignatureBytes,
+);
+
+/// `serde_arrays` writes the signature as a plain array of 64 bytes.
+fn signature_bytes_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "array",
+        "items": { "type": "integer", "format": "uint8", "minimum": 0, "maximum": 255 },
+        "minItems": 64,
+        "maxItems": 64
+    })
+}
 
 impl Signature {
     pub fn new<T>(value: &T, secret: &KeyPair) -> Self
@@ -283,9 +506,24 @@ impl Signature {
         let signature = secret.0.sign(&message);
         Signature(signature.to_bytes())
     }
+
+    /// Check that this is a signature of `value` by the owner of `author`.
+    pub fn verify<T>(&self, value: &T, author: &PublicKeyBytes) -> Result<(), FastSetError>
+    where
+        T: Signable<Vec<u8>>,
+    {
+        let mut message = Vec::new();
+        value.write(&mut message);
+        let public_key = dalek::VerifyingKey::from_bytes(&author.0)
+            .map_err(|_| FastSetError::InvalidSignature)?;
+        public_key
+            .verify_strict(&message, &dalek::Signature::from_bytes(&self.0))
+            .map_err(|_| FastSetError::InvalidSignature)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Together, determines the address of a multisig account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct MultiSigConfig {
     /// The accounts which may sign for a multisig transaction to be accepted
     pub authorized_signers: Vec<FastSetAddress>,
@@ -296,19 +534,19 @@ pub struct MultiSigConfig {
     pub nonce: Nonce,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct MultiSig {
     pub config: MultiSigConfig,
     pub signatures: Vec<(FastSetAddress, Signature)>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum SignatureOrMultiSig {
     Signature(Signature),
     MultiSig(MultiSig),
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct CrossSignResponse {
     /// The type of signature produced.
     /// - "eip191-abi"
@@ -325,7 +563,8 @@ pub struct CrossSignResponse {
 // We now define the set of basic claims and operations
 // ====================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Transfer tokens to another address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TokenTransfer {
     /// Token ID to transfer
     pub token_id: TokenId,
@@ -335,24 +574,298 @@ pub struct TokenTransfer {
     pub user_data: UserData,
 }
 
+/// Create a new token.
+/// The token id is derived from the [Transaction]
+/// so it depends also on the creator and the [Nonce].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TokenCreation {
+    /// Human-readable name
+    pub token_name: String,
+    /// Power of 10 that should be considered a full unit of this token.
+    /// An [Amount] is still always in least units.
+    pub decimals: u8,
+    /// Initial balance, which will be held by the creator of the token.
+    pub initial_amount: Amount,
+    /// Addresses which will be able to create more of this token
+    pub mints: Vec<FastSetAddress>,
+    /// Arbitrary userdata attached to this transaction
+    pub user_data: UserData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum AddressChange {
+    Add(),
+    Remove(),
+}
+
+/// Manage an existing token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TokenManagement {
+    /// The id of the token to be managed
+    pub token_id: TokenId,
+    /// The update id for this token (used for sequencing)
+    /// Each update id must be one greater than the last
+    pub update_id: Nonce,
+    /// The new admin address; preserve existing admin if None
+    pub new_admin: Option<FastSetAddress>,
+    /// The minter addresses to be added/removed
+    pub mints: Vec<(AddressChange, PublicKeyBytes)>,
+    /// Arbitrary userdata attached to this transaction
+    pub user_data: UserData,
+}
+
+/// Create more funds of a token.
+/// The sender of the [Transaction] must be a current mint of the token.
+/// Warning: This is not independent of a token management operation that
+/// removes the sender of this transaction from the list of mints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Mint {
+    /// Token ID. This is the hash of the TokenCreation transaction that created the token.
+    /// This is calculated using the keccak256 hash over the data encoded in the same way as for
+    /// signing.
+    pub token_id: TokenId,
+    /// Amount to mint
+    pub amount: Amount,
+}
+
+/// Initialize one state cell of a blockchain mirroring account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StateInitialization {
+    /// Key to initialize
+    pub key: StateKey,
+    /// Initial state
+    pub initial_state: State,
+}
+
+/// Update the state of a blockchain mirroring account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StateUpdate {
+    /// Key to update
+    pub key: StateKey,
+    /// Previous state
+    pub previous_state: State,
+    /// Next state
+    pub next_state: State,
+    pub compute_claim_tx_hash: [u8; 32],
+    pub compute_claim_tx_timestamp: u128,
+}
+
+/// Reset the state of a blockchain mirroring account
+/// This claim type is a temporary work-around that allows a left behind account to
+/// be easily caught up with the target blockchain's tip. In the future this claim type
+/// will be dropped and a left-behind mirroring account will need to settle all
+/// missed state updates in order to be caught up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StateReset {
+    /// Key to reset
+    pub key: StateKey,
+    /// Reset state
+    pub reset_state: State,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ClaimData(pub Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExternalClaimBody {
+    /// Set of verifiers (regular  FastSet addresses) that can sign for this ExternalClaim
+    pub verifier_committee: Vec<FastSetAddress>,
+    /// Minimum number of verifiers in `verifier_committee` for which fastset validators will sign
+    /// this transaction
+    pub verifier_quorum: Quorum,
+    /// Arbitrary data that the verifiers are signing.
+    pub claim_data: ClaimData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct VerifierSig {
+    pub verifier_addr: FastSetAddress,
+    pub sig: Signature,
+}
+
+/// Submit arbitrary data along with a quorum of signatures from external verifiers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExternalClaim {
+    /// The claim itself plus the required verifier quorum
+    pub claim: ExternalClaimBody,
+    /// At least `claim.verifier_quorum` signatures over the enclosing `Transaction` (with this
+    /// field set to the empty list) by members of `claim.verifier_committee`
+    pub signatures: Vec<VerifierSig>,
+}
+
+/// The configuration of a validator joining the committee.
+/// Its fields are not described by the proxy API documentation, so transactions with this claim
+/// cannot be decoded by these examples.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ValidatorConfig {}
+
+/// A change of the validator committee.
+/// Its fields are not described by the proxy API documentation, so transactions with this claim
+/// cannot be decoded by these examples.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum CommitteeChange {}
+
+/// Transfer tokens to another address.
+/// This is a variant of [TokenTransfer] that adds a recipient field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TokenTransferOperation {
+    /// Token ID to transfer
+    pub token_id: TokenId,
+    /// Recipient
+    pub recipient: FastSetAddress,
+    /// Amount to transfer
+    pub amount: Amount,
+    /// Extra data field to associate with this transfer
+    pub user_data: UserData,
+}
+
+/// Create more funds of a token.
+/// The sender of the [Transaction] must be a current mint of the token.
+/// Warning: This is not independent of a token management operation that
+/// removes the sender of this transaction from the list of mints.
+/// This is a variant of [Mint] that adds a recipient field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MintOperation {
+    /// Token ID. This is the hash of the TokenCreation transaction that created the token.
+    /// This is calculated using the keccak256 hash over the data encoded in the same way as for
+    /// signing.
+    pub token_id: TokenId,
+    /// Recipient of the new funds
+    pub recipient: FastSetAddress,
+    /// Amount to mint
+    pub amount: Amount,
+}
+
+/// One of various types of actions that be put in a multi-operation transaction
+/// Payload structs are different from the top-level [ClaimType] where we
+/// need to support multiple operations with different receivers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Operation {
+    /// Transfer or burn tokens (that is, transfer tokens to the burn address)
+    TokenTransfer(TokenTransferOperation),
+    /// Create custom token
+    TokenCreation(TokenCreation),
+    /// Modify custom token
+    TokenManagement(TokenManagement),
+    /// Mint funds in a custom token
+    Mint(MintOperation),
+    /// Initialize the state of an Ethereum blockchain mirroring account
+    StateInitialization(StateInitialization),
+    /// Update the state of an Ethereum blockchain mirroring account
+    StateUpdate(StateUpdate),
+    /// Submit arbitrary data to be settled on the network
+    ExternalClaim(ExternalClaim),
+    /// Reset the state of an Ethereum blockchain mirroring account
+    StateReset(StateReset),
+    /// Join Committee request
+    /// Since no escrow is currently involved with the requests we do not check the unicity of
+    /// the requests and just process them in the order of their timestamps
+    JoinCommittee(ValidatorConfig),
+    /// Leave Committee request
+    /// Since no escrow is currently involved with the requests we do not check the unicity of
+    /// the requests and just process them in the order of their timestamps
+    LeaveCommittee,
+    /// Change Committee request
+    ChangeCommittee(CommitteeChange),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct OperationBundle(pub Vec<Operation>);
+
 // ============================
 // We now define the claim type
 // ============================
 
 // A "claim" is a concept on FastSet that drives state changes on the FastSet network. It is akin to
-// the "calldata" of a transaction on Ethereum. There are many types of claims, but in this example,
-// others are omitted since we are interested in the Transfer claim.
-#[derive(Debug, Serialize, Deserialize)]
+// the "calldata" of a transaction on Ethereum. The variants are in the order of the proxy API, which
+// fixes their BCS encoding.
+/// One of various types of actions that can be packed into a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum ClaimType {
     /// Transfer or burn tokens (that is, transfer tokens to the burn address)
     TokenTransfer(TokenTransfer),
+    /// Create custom token
+    TokenCreation(TokenCreation),
+    /// Modify custom token
+    TokenManagement(TokenManagement),
+    /// Mint funds in a custom token
+    Mint(Mint),
+    /// Initialize the state of an Ethereum blockchain mirroring account
+    StateInitialization(StateInitialization),
+    /// Update the state of an Ethereum blockchain mirroring account
+    StateUpdate(StateUpdate),
+    /// Submit arbitrary data to be settled on the network
+    ExternalClaim(ExternalClaim),
+    /// Reset the state of an Ethereum blockchain mirroring account
+    StateReset(StateReset),
+    /// Join Committee request
+    /// Since no escrow is currently involved with the requests we do not check the unicity of
+    /// the requests and just process them in the order of their timestamps
+    JoinCommittee(ValidatorConfig),
+    /// Leave Committee request
+    /// Since no escrow is currently involved with the requests we do not check the unicity of
+    /// the requests and just process them in the order of their timestamps
+    LeaveCommittee,
+    /// Change Committee request
+    ChangeCommittee(CommitteeChange),
+    /// Perform several operations
+    Batch(OperationBundle),
+}
+
+impl ClaimType {
+    /// The name of the variant, as in JSON.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClaimType::TokenTransfer(_) => "TokenTransfer",
+            ClaimType::TokenCreation(_) => "TokenCreation",
+            ClaimType::TokenManagement(_) => "TokenManagement",
+            ClaimType::Mint(_) => "Mint",
+            ClaimType::StateInitialization(_) => "StateInitialization",
+            ClaimType::StateUpdate(_) => "StateUpdate",
+            ClaimType::ExternalClaim(_) => "ExternalClaim",
+            ClaimType::StateReset(_) => "StateReset",
+            ClaimType::JoinCommittee(_) => "JoinCommittee",
+            ClaimType::LeaveCommittee => "LeaveCommittee",
+            ClaimType::ChangeCommittee(_) => "ChangeCommittee",
+            ClaimType::Batch(_) => "Batch",
+        }
+    }
+
+    /// The existing tokens the claim moves, mints or manages. A token created by the claim is not
+    /// included, since its ID is only known once the transaction is hashed.
+    pub fn token_ids(&self) -> Vec<TokenId> {
+        let found = match self {
+            ClaimType::TokenTransfer(transfer) => vec![transfer.token_id],
+            ClaimType::TokenManagement(management) => vec![management.token_id],
+            ClaimType::Mint(mint) => vec![mint.token_id],
+            ClaimType::Batch(bundle) => bundle
+                .0
+                .iter()
+                .filter_map(|operation| match operation {
+                    Operation::TokenTransfer(transfer) => Some(transfer.token_id),
+                    Operation::TokenManagement(management) => Some(management.token_id),
+                    Operation::Mint(mint) => Some(mint.token_id),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut token_ids = Vec::new();
+        for token_id in found {
+            if !token_ids.contains(&token_id) {
+                token_ids.push(token_id);
+            }
+        }
+        token_ids
+    }
 }
 
 // =======================================================
 // We now define transactions, envelopes, and certificates
 // =======================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Action that can be submitted and confirmed on the network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Transaction {
     /// Address of sender, and intended signer of this transaction
     pub sender: FastSetAddress,
@@ -370,10 +883,32 @@ pub struct Transaction {
     pub archival: bool,
 }
 
-impl BcsSignable for Transaction {}
+impl Transaction {
+    /// The addresses the transaction may credit: its recipient, or those of the transfers and
+    /// mints of a batch, without duplicates.
+    pub fn recipients(&self) -> Vec<FastSetAddress> {
+        let ClaimType::Batch(bundle) = &self.claim else {
+            return vec![self.recipient];
+        };
+        let mut recipients = Vec::new();
+        for operation in &bundle.0 {
+            let recipient = match operation {
+                Operation::TokenTransfer(transfer) => transfer.recipient,
+                Operation::Mint(mint) => mint.recipient,
+                _ => continue,
+            };
+            if !recipients.contains(&recipient) {
+                recipients.push(recipient);
+            }
+        }
+        recipients
+    }
+}
 
+impl BcsSignable for Transaction {}
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A Transaction along with its sender's signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionEnvelope {
     pub transaction: Transaction,
     pub signature: SignatureOrMultiSig,
@@ -387,22 +922,36 @@ impl TransactionEnvelope {
             signature: SignatureOrMultiSig::Signature(signature),
         }
     }
+
+    /// Check the sender's signature over the transaction.
+    pub fn verify(&self) -> Result<(), EnvelopeError> {
+        match &self.signature {
+            SignatureOrMultiSig::Signature(signature) => {
+                Ok(signature.verify(&self.transaction, &self.transaction.sender)?)
+            }
+            SignatureOrMultiSig::MultiSig(_) => Err(EnvelopeError::MultiSigUnsupported),
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+/// A Transaction along with the signature from one validator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ValidatedTransaction {
     pub value: TransactionEnvelope,
     pub validator: ValidatorName,
     pub signature: Signature,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A Transaction along with a quorum of validator signatures
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionCertificate {
     pub envelope: TransactionEnvelope,
     pub signatures: Vec<(ValidatorName, Signature)>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Data associated to one account. Contains optional fields for returning account-related info from various
+/// types of queries. Reflects the view of a single validator, which may be lagging behind the rest of the network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct AccountInfoResponse {
     /// The address of the account
     pub sender: FastSetAddress,
@@ -423,16 +972,66 @@ pub struct AccountInfoResponse {
     pub token_balance: Vec<(TokenId, Balance)>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TokenInfoResponse {
     pub requested_token_metadata: Vec<(TokenId, Option<TokenMetadata>)>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TransactionInfo {
+    pub hash: [u8; 32],
+    pub sender: FastSetAddress,
+    pub recipient: FastSetAddress,
+    pub nonce: Nonce,
+    pub claim: ClaimType,
+    pub submission_timestamp_nanos: u128,
+}
+
+/// A client->server RPC message used to request a bounded number of
+/// records from the server starting from a given index or offset.
+///
+/// A client may safely issue multiple `PageRequest`s in parallel.
+///
+/// To avoid requesting redundant information, when issuing parallel
+/// requests, ensure that the ranges `[r.token, r.token+r.limit)` for
+/// each request `r` are non-overlapping, where `r.token` defaults
+/// to `0` if `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PageRequest {
+    /// The maximum number of records desired from the server. The server
+    /// may return less records, but it will not return more.
+    pub limit: usize,
+    /// The index or offset from which to begin querying records. If this
+    /// field is absent, it defaults to offset 0 (equivalently, the index of
+    /// the first record), i.e., the initial records will be returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Pagination {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
 /// FastSet core errors. Only protocol-related errors should be in here.
-#[derive(Debug, Serialize, Deserialize, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Error)]
 pub enum FastSetError {
     #[error("Account balance overflow.")]
     BalanceOverflow,
+    #[error("Invalid signature.")]
+    InvalidSignature,
+}
+
+/// Why [TransactionEnvelope::verify] does not accept an envelope.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EnvelopeError {
+    #[error(transparent)]
+    Protocol(#[from] FastSetError),
+    /// The envelope may be valid, but these examples cannot check multisig accounts.
+    #[error("Multisig accounts are not supported.")]
+    MultiSigUnsupported,
 }
 
 pub fn get_key_pair() -> (FastSetAddress, KeyPair) {
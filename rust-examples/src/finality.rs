@@ -0,0 +1,135 @@
+//! Waiting for a settled transaction to be visible, before reading the balances it changed.
+//!
+//! A proxy answers `proxy_submitTransaction` once a quorum of validators certified the
+//! transaction, but each `proxy_getAccountInfo` reflects a single validator, which may not have
+//! processed the certificate yet. [await_settled] polls until the change can be read back.
+
+use std::time::Duration;
+
+use futures::future::join_all;
+use jsonrpsee::core::ClientError;
+use thiserror::Error;
+use tokio::time::Instant;
+
+use crate::client::ProxyRpcClient;
+use crate::fastset_types::{FastSetAddress, Nonce, NonceRange, TransactionCertificate};
+
+#[derive(Debug, Clone)]
+pub struct SettleConfig {
+    /// Number of proxies that must show the transaction as settled
+    pub confirmations: usize,
+    /// How long to wait for the confirmations before giving up
+    pub timeout: Duration,
+    /// Delay between two rounds of queries
+    pub period: Duration,
+}
+
+impl Default for SettleConfig {
+    fn default() -> Self {
+        Self {
+            confirmations: 1,
+            timeout: Duration::from_secs(10),
+            period: Duration::from_millis(200),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SettleError {
+    #[error("{required} confirmations were requested, but only {available} proxies are available.")]
+    NotEnoughProxies { required: usize, available: usize },
+    #[error(
+        "Only {confirmations} of the {required} required proxies show nonce {nonce} as settled. \
+         Last error: {last_error:?}"
+    )]
+    Timeout {
+        nonce: Nonce,
+        confirmations: usize,
+        required: usize,
+        last_error: Option<ClientError>,
+    },
+}
+
+/// Wait until the transaction of `address` with `nonce` is settled in the view of
+/// [SettleConfig::confirmations] of `clients`, and return its certificate.
+///
+/// A proxy confirms the transaction once its account info has a `next_nonce` past `nonce` and
+/// returns the certificate for `nonce` through `certificate_by_nonce`. Proxies that confirmed are
+/// not queried again; the others are queried together every [SettleConfig::period], and their
+/// errors are retried until the timeout. Queries still pending at the timeout are abandoned, so
+/// that an unresponsive proxy cannot delay the result. A validator that already pruned the
+/// certificate never confirms.
+pub async fn await_settled<C: ProxyRpcClient>(
+    clients: &[C],
+    address: FastSetAddress,
+    nonce: Nonce,
+    config: &SettleConfig,
+) -> Result<TransactionCertificate, SettleError> {
+    let required = config.confirmations.max(1);
+    if clients.len() < required {
+        return Err(SettleError::NotEnoughProxies {
+            required,
+            available: clients.len(),
+        });
+    }
+    let deadline = Instant::now() + config.timeout;
+    let mut waiting: Vec<&C> = clients.iter().collect();
+    let mut certificate = None;
+    let mut last_error = None;
+    loop {
+        let results = join_all(waiting.iter().map(|client| {
+            tokio::time::timeout_at(deadline, settled_certificate(*client, address, nonce))
+        }))
+        .await;
+        let mut still_waiting = Vec::new();
+        for (client, result) in waiting.into_iter().zip(results) {
+            match result.unwrap_or(Err(ClientError::RequestTimeout)) {
+                Ok(Some(found)) => {
+                    certificate.get_or_insert(found);
+                }
+                Ok(None) => still_waiting.push(client),
+                Err(err) => {
+                    last_error = Some(err);
+                    still_waiting.push(client);
+                }
+            }
+        }
+        waiting = still_waiting;
+        let confirmations = clients.len() - waiting.len();
+        if confirmations >= required {
+            return Ok(certificate.expect("a proxy confirmed"));
+        }
+        if Instant::now() + config.period > deadline {
+            return Err(SettleError::Timeout {
+                nonce,
+                confirmations,
+                required,
+                last_error,
+            });
+        }
+        tokio::time::sleep(config.period).await;
+    }
+}
+
+/// The certificate for `nonce`, if `client` shows it as settled.
+async fn settled_certificate(
+    client: &impl ProxyRpcClient,
+    address: FastSetAddress,
+    nonce: Nonce,
+) -> Result<Option<TransactionCertificate>, ClientError> {
+    let range = NonceRange {
+        start: nonce,
+        limit: 1,
+    };
+    let info = client
+        .get_account_info(address, None, None, Some(range))
+        .await?;
+    if info.next_nonce <= nonce {
+        return Ok(None);
+    }
+    Ok(info
+        .requested_certificates
+        .into_iter()
+        .flatten()
+        .find(|certificate| certificate.envelope.transaction.nonce == nonce))
+}
@@ -0,0 +1,424 @@
+//! Decoding and pretty-printing of transactions, envelopes and certificates.
+//!
+//! Sender signatures follow the documented signing process. How validators sign certificates is
+//! not documented by the proxy API: validator signatures are checked against the convention of this
+//! crate's mock validators, which sign the transaction the way its sender does, and are reported as
+//! unverified since genuine certificates may use another one.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use jsonrpsee::core::ClientError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::client::ProxyRpcClient;
+use crate::fastset_types::{
+    Amount, ClaimType, FastSetError, PublicKeyBytes, SignatureOrMultiSig, TokenId, TokenMetadata,
+    Transaction, TransactionCertificate, TransactionEnvelope,
+};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Decoding
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadKind {
+    Transaction,
+    Envelope,
+    Certificate,
+}
+
+impl PayloadKind {
+    /// The kinds tried when the kind of a payload is not known, largest first: the BCS bytes of a
+    /// certificate start with those of its envelope, which start with those of its transaction.
+    const ALL: [PayloadKind; 3] = [
+        PayloadKind::Certificate,
+        PayloadKind::Envelope,
+        PayloadKind::Transaction,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            PayloadKind::Transaction => "transaction",
+            PayloadKind::Envelope => "envelope",
+            PayloadKind::Certificate => "certificate",
+        }
+    }
+}
+
+impl FromStr for PayloadKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| format!("expected transaction, envelope or certificate, got {s:?}"))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error("Input is neither JSON nor hex-encoded BCS.")]
+    UnknownFormat,
+    #[error("Input is not a {}: {reason}", kind.name())]
+    Invalid { kind: PayloadKind, reason: String },
+    #[error("Input is not a transaction, envelope or certificate.")]
+    Unrecognized,
+}
+
+/// A decoded transaction, transaction envelope or transaction certificate.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payload {
+    Transaction(Transaction),
+    Envelope(TransactionEnvelope),
+    Certificate(TransactionCertificate),
+}
+
+impl Payload {
+    /// Decode `input`, given either as hex-encoded BCS bytes (with or without a `0x` prefix) or as
+    /// JSON in the format used by the proxy.
+    ///
+    /// JSON may also be a whole `proxy_submitTransaction` request, whose parameters form an
+    /// envelope, or a JSON-RPC response carrying a certificate. If `kind` is not given, it is
+    /// inferred from the input.
+    pub fn decode(input: &str, kind: Option<PayloadKind>) -> Result<Self, DecodeError> {
+        let input = input.trim();
+        let kinds = kind.map_or(PayloadKind::ALL.to_vec(), |kind| vec![kind]);
+        if input.starts_with('{') {
+            let value: Value =
+                serde_json::from_str(input).map_err(|_| DecodeError::UnknownFormat)?;
+            let value = unwrap_json_rpc(value);
+            try_kinds(&kinds, |kind| match kind {
+                PayloadKind::Transaction => from_json(&value).map(Payload::Transaction),
+                PayloadKind::Envelope => from_json(&value).map(Payload::Envelope),
+                PayloadKind::Certificate => from_json(&value).map(Payload::Certificate),
+            })
+        } else {
+            let hex: String = input.split_whitespace().collect();
+            let bytes = hex::decode(hex.strip_prefix("0x").unwrap_or(&hex))
+                .map_err(|_| DecodeError::UnknownFormat)?;
+            try_kinds(&kinds, |kind| match kind {
+                PayloadKind::Transaction => from_bcs(&bytes).map(Payload::Transaction),
+                PayloadKind::Envelope => from_bcs(&bytes).map(Payload::Envelope),
+                PayloadKind::Certificate => from_bcs(&bytes).map(Payload::Certificate),
+            })
+        }
+    }
+
+    pub fn kind(&self) -> PayloadKind {
+        match self {
+            Payload::Transaction(_) => PayloadKind::Transaction,
+            Payload::Envelope(_) => PayloadKind::Envelope,
+            Payload::Certificate(_) => PayloadKind::Certificate,
+        }
+    }
+
+    pub fn transaction(&self) -> &Transaction {
+        match self {
+            Payload::Transaction(transaction) => transaction,
+            Payload::Envelope(envelope) => &envelope.transaction,
+            Payload::Certificate(certificate) => &certificate.envelope.transaction,
+        }
+    }
+
+    /// The signed envelope, unless the payload is a bare transaction.
+    pub fn envelope(&self) -> Option<&TransactionEnvelope> {
+        match self {
+            Payload::Transaction(_) => None,
+            Payload::Envelope(envelope) => Some(envelope),
+            Payload::Certificate(certificate) => Some(&certificate.envelope),
+        }
+    }
+
+    /// The tokens mentioned by the transaction.
+    pub fn token_ids(&self) -> Vec<TokenId> {
+        self.transaction().claim.token_ids()
+    }
+
+    /// Check every signature carried by the payload against the transaction.
+    ///
+    /// Validator signatures are checked against the assumed convention described in the module
+    /// documentation, so a failed check does not prove that a genuine certificate is invalid.
+    pub fn check_signatures(&self) -> Vec<SignatureCheck> {
+        let transaction = self.transaction();
+        let Some(envelope) = self.envelope() else {
+            return Vec::new();
+        };
+        let validators = match self {
+            Payload::Certificate(certificate) => &certificate.signatures[..],
+            _ => &[],
+        };
+        let mut checks = match &envelope.signature {
+            SignatureOrMultiSig::Signature(signature) => vec![SignatureCheck {
+                role: SignerRole::Sender,
+                signer: transaction.sender,
+                result: signature.verify(transaction, &transaction.sender),
+            }],
+            SignatureOrMultiSig::MultiSig(multisig) => multisig
+                .signatures
+                .iter()
+                .map(|(signer, signature)| SignatureCheck {
+                    role: SignerRole::MultiSigMember {
+                        authorized: multisig.config.authorized_signers.contains(signer),
+                    },
+                    signer: *signer,
+                    result: signature.verify(transaction, signer),
+                })
+                .collect(),
+        };
+        checks.extend(
+            validators
+                .iter()
+                .map(|(validator, signature)| SignatureCheck {
+                    role: SignerRole::Validator,
+                    signer: *validator,
+                    result: signature.verify(transaction, validator),
+                }),
+        );
+        checks
+    }
+}
+
+/// Strip the JSON-RPC framing from a request or response, and the variant tag from a
+/// `ProxySubmitTransactionResult`.
+fn unwrap_json_rpc(mut value: Value) -> Value {
+    for member in ["params", "result", "Success"] {
+        if let Some(inner) = value.get_mut(member) {
+            value = inner.take();
+        }
+    }
+    value
+}
+
+fn from_json<T: DeserializeOwned>(value: &Value) -> Result<T, String> {
+    T::deserialize(value).map_err(|err| err.to_string())
+}
+
+fn from_bcs<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    bcs::from_bytes(bytes).map_err(|err| err.to_string())
+}
+
+fn try_kinds(
+    kinds: &[PayloadKind],
+    decode: impl Fn(PayloadKind) -> Result<Payload, String>,
+) -> Result<Payload, DecodeError> {
+    let mut last_error = DecodeError::Unrecognized;
+    for &kind in kinds {
+        match decode(kind) {
+            Ok(payload) => return Ok(payload),
+            Err(reason) if kinds.len() == 1 => last_error = DecodeError::Invalid { kind, reason },
+            Err(_) => {}
+        }
+    }
+    Err(last_error)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Signatures
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerRole {
+    Sender,
+    /// A member of a multisig account; `authorized` tells whether it is listed in the account's
+    /// configuration.
+    MultiSigMember {
+        authorized: bool,
+    },
+    Validator,
+}
+
+/// The outcome of verifying one signature of a payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureCheck {
+    pub role: SignerRole,
+    pub signer: PublicKeyBytes,
+    pub result: Result<(), FastSetError>,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Pretty-printing
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// How amounts of a token are displayed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenLabel {
+    pub name: String,
+    pub decimals: u8,
+}
+
+impl TokenLabel {
+    /// The label of a token with `metadata`. The native token has no metadata, and is labelled SET
+    /// with `native_decimals` decimals.
+    pub fn new(
+        token_id: TokenId,
+        metadata: Option<TokenMetadata>,
+        native_decimals: u8,
+    ) -> Option<Self> {
+        match metadata {
+            Some(metadata) => Some(Self {
+                name: metadata.token_name,
+                decimals: metadata.decimals,
+            }),
+            None if token_id == TokenId::native() => Some(Self {
+                name: "SET".to_string(),
+                decimals: native_decimals,
+            }),
+            None => None,
+        }
+    }
+}
+
+/// Labels for `token_ids`, from the token metadata known to the proxy. Tokens without metadata are
+/// left out, except for the native token, which is labelled SET with `native_decimals` decimals.
+pub async fn token_labels(
+    client: &impl ProxyRpcClient,
+    token_ids: Vec<TokenId>,
+    native_decimals: u8,
+) -> Result<BTreeMap<TokenId, TokenLabel>, ClientError> {
+    let info = client.get_token_info(token_ids).await?;
+    Ok(info
+        .requested_token_metadata
+        .into_iter()
+        .filter_map(|(token_id, metadata)| {
+            TokenLabel::new(token_id, metadata, native_decimals).map(|label| (token_id, label))
+        })
+        .collect())
+}
+
+/// Format nanoseconds since the Unix epoch as an RFC 3339 timestamp in UTC, if it is within the
+/// range that RFC 3339 can represent.
+pub fn format_timestamp(timestamp_nanos: u128) -> Option<String> {
+    const MAX_SECONDS: u64 = 253_402_300_799; // 9999-12-31T23:59:59Z
+    let seconds = u64::try_from(timestamp_nanos / 1_000_000_000)
+        .ok()
+        .filter(|seconds| *seconds <= MAX_SECONDS)?;
+    let time =
+        SystemTime::UNIX_EPOCH + Duration::new(seconds, (timestamp_nanos % 1_000_000_000) as u32);
+    Some(humantime::format_rfc3339_nanos(time).to_string())
+}
+
+fn format_amount(amount: &Amount, token: Option<&TokenLabel>) -> String {
+    let least_units = amount.to_decimal_string(0);
+    match token {
+        Some(token) => format!(
+            "{} {} ({least_units} least units)",
+            amount.to_decimal_string(token.decimals),
+            token.name
+        ),
+        None => format!("{least_units} least units of an unknown token"),
+    }
+}
+
+/// A human-readable rendering of a [Payload], see [describe].
+pub struct Description<'a> {
+    payload: &'a Payload,
+    tokens: &'a BTreeMap<TokenId, TokenLabel>,
+}
+
+/// Render `payload` for humans: addresses in bech32m, amounts in whole tokens where `tokens` has a
+/// label for them, timestamps in RFC 3339, and the outcome of every signature check.
+pub fn describe<'a>(
+    payload: &'a Payload,
+    tokens: &'a BTreeMap<TokenId, TokenLabel>,
+) -> Description<'a> {
+    Description { payload, tokens }
+}
+
+impl Display for Description<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let transaction = self.payload.transaction();
+        writeln!(f, "{}", self.payload.kind().name())?;
+        writeln!(f, "  sender:    {}", transaction.sender)?;
+        writeln!(f, "  recipient: {}", transaction.recipient)?;
+        writeln!(f, "  nonce:     {}", transaction.nonce)?;
+        writeln!(
+            f,
+            "  timestamp: {} ({} ns)",
+            format_timestamp(transaction.timestamp_nanos)
+                .as_deref()
+                .unwrap_or("out of range"),
+            transaction.timestamp_nanos
+        )?;
+        writeln!(f, "  archival:  {}", transaction.archival)?;
+        match &transaction.claim {
+            ClaimType::TokenTransfer(transfer) => {
+                writeln!(f, "  claim:     TokenTransfer")?;
+                writeln!(f, "    token:     {}", transfer.token_id)?;
+                writeln!(
+                    f,
+                    "    amount:    {}",
+                    format_amount(&transfer.amount, self.tokens.get(&transfer.token_id))
+                )?;
+                if let Some(user_data) = transfer.user_data.0 {
+                    writeln!(f, "    user data: 0x{}", hex::encode(user_data))?;
+                }
+            }
+            ClaimType::Mint(mint) => {
+                writeln!(f, "  claim:     Mint")?;
+                writeln!(f, "    token:     {}", mint.token_id)?;
+                writeln!(
+                    f,
+                    "    amount:    {}",
+                    format_amount(&mint.amount, self.tokens.get(&mint.token_id))
+                )?;
+            }
+            claim => writeln!(f, "  claim:     {}", claim.name())?,
+        }
+        if let Some(SignatureOrMultiSig::MultiSig(multisig)) =
+            self.payload.envelope().map(|envelope| &envelope.signature)
+        {
+            writeln!(
+                f,
+                "  multisig:  {} of {} signers, config nonce {}",
+                multisig.config.quorum.0,
+                multisig.config.authorized_signers.len(),
+                multisig.config.nonce
+            )?;
+        }
+
+        let checks = self.payload.check_signatures();
+        if !checks.is_empty() {
+            writeln!(f, "  signatures:")?;
+        }
+        for check in checks {
+            let role = match check.role {
+                SignerRole::Sender => "sender",
+                SignerRole::MultiSigMember { authorized: true } => "multisig signer",
+                SignerRole::MultiSigMember { authorized: false } => "unauthorized multisig signer",
+                SignerRole::Validator => "validator",
+            };
+            match (check.role, check.result) {
+                (SignerRole::Validator, Ok(())) => writeln!(
+                    f,
+                    "    {role} {}: unverified (matches the assumed convention)",
+                    check.signer
+                )?,
+                (SignerRole::Validator, Err(err)) => writeln!(
+                    f,
+                    "    {role} {}: unverified (does not match the assumed convention: {err})",
+                    check.signer
+                )?,
+                (_, Ok(())) => writeln!(f, "    {role} {}: valid", check.signer)?,
+                (_, Err(err)) => writeln!(f, "    {role} {}: INVALID ({err})", check.signer)?,
+            }
+        }
+        if let Payload::Certificate(certificate) = self.payload
+            && !certificate.signatures.is_empty()
+        {
+            writeln!(
+                f,
+                "  note: the proxy API does not document how validators sign; their signatures were \
+                 checked as signatures of the transaction, like the sender's."
+            )?;
+        }
+        Ok(())
+    }
+}
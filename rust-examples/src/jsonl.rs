@@ -0,0 +1,107 @@
+//! Append-only files of JSON lines, such as the
+//! [CertificateArchive](crate::archive::CertificateArchive) and the journal of
+//! [run_payouts](crate::payouts::run_payouts): a header line, then one record per line, each synced
+//! to disk before it counts.
+//!
+//! A crash while appending can leave a final line without its newline. Such a torn line was never
+//! acknowledged, so it is cut off when the file is opened. A failed append is undone the same way,
+//! by truncating the file back to its last complete line.
+
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JsonLinesError {
+    #[error("Cannot access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Line {line} of {path} is malformed: {source}")]
+    Format {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+}
+
+pub struct JsonLines {
+    path: PathBuf,
+    file: File,
+    /// Length of the complete lines of the file
+    len: u64,
+}
+
+impl JsonLines {
+    /// Open the file at `path`, creating it if it does not exist, and read its complete lines.
+    pub fn open(path: &Path) -> Result<(Self, Vec<String>), JsonLinesError> {
+        let io_error = |source| JsonLinesError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(io_error)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(io_error)?;
+        let len = contents
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |newline| newline + 1);
+        if len < contents.len() {
+            file.set_len(len as u64)
+                .and_then(|()| file.sync_data())
+                .map_err(io_error)?;
+            contents.truncate(len);
+        }
+        let contents = String::from_utf8(contents)
+            .map_err(|err| io_error(std::io::Error::new(ErrorKind::InvalidData, err)))?;
+        let lines = contents.lines().map(str::to_string).collect();
+        let file = Self {
+            path: path.to_path_buf(),
+            file,
+            len: len as u64,
+        };
+        Ok((file, lines))
+    }
+
+    /// Parse `json`, the 1-based `line` of the file.
+    pub fn parse<T: for<'de> Deserialize<'de>>(
+        &self,
+        line: usize,
+        json: &str,
+    ) -> Result<T, JsonLinesError> {
+        serde_json::from_str(json).map_err(|source| JsonLinesError::Format {
+            path: self.path.clone(),
+            line,
+            source,
+        })
+    }
+
+    /// Append `value` as a line and sync it to disk.
+    pub fn append(&mut self, value: &impl Serialize) -> Result<(), JsonLinesError> {
+        let mut line = serde_json::to_vec(value).expect("JSON lines serialization");
+        line.push(b'\n');
+        if let Err(source) = self
+            .file
+            .write_all(&line)
+            .and_then(|()| self.file.sync_data())
+        {
+            // Should this fail too, the torn line is cut off on the next open.
+            let _ = self.file.set_len(self.len);
+            return Err(JsonLinesError::Io {
+                path: self.path.clone(),
+                source,
+            });
+        }
+        self.len += line.len() as u64;
+        Ok(())
+    }
+}
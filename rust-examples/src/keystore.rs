@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::fastset_types::KeyPair;
+
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("Cannot access keystore {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Keystore {path} is malformed: {source}")]
+    Format {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("No key named {0:?} in the keystore.")]
+    UnknownKey(String),
+    #[error("A key named {0:?} already exists.")]
+    KeyExists(String),
+    #[error("Key {0:?} is not a hex-encoded 32-byte ed25519 secret key.")]
+    InvalidSecret(String),
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct KeystoreFile {
+    /// Hex-encoded ed25519 secret keys by name
+    keys: BTreeMap<String, String>,
+}
+
+/// Named signing keys, stored in a JSON file.
+///
+/// Secret keys are stored unencrypted. On Unix the file is only readable by its owner; keep it on
+/// an encrypted disk and out of backups that others can read.
+pub struct Keystore {
+    path: PathBuf,
+    file: KeystoreFile,
+}
+
+impl Keystore {
+    /// Open the keystore at `path`. A missing file is an empty keystore, created on [Keystore::save].
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, KeystoreError> {
+        let path = path.into();
+        let file = match std::fs::read(&path) {
+            Ok(bytes) => {
+                serde_json::from_slice(&bytes).map_err(|source| KeystoreError::Format {
+                    path: path.clone(),
+                    source,
+                })?
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => KeystoreFile::default(),
+            Err(source) => return Err(KeystoreError::Io { path, source }),
+        };
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.file.keys.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, name: &str) -> Result<KeyPair, KeystoreError> {
+        let secret = self
+            .file
+            .keys
+            .get(name)
+            .ok_or_else(|| KeystoreError::UnknownKey(name.to_string()))?;
+        parse_secret(secret).ok_or_else(|| KeystoreError::InvalidSecret(name.to_string()))
+    }
+
+    /// Add `key` under `name`. An existing key with that name is only replaced if `overwrite` is
+    /// set.
+    pub fn insert(
+        &mut self,
+        name: &str,
+        key: &KeyPair,
+        overwrite: bool,
+    ) -> Result<(), KeystoreError> {
+        if !overwrite && self.file.keys.contains_key(name) {
+            return Err(KeystoreError::KeyExists(name.to_string()));
+        }
+        self.file
+            .keys
+            .insert(name.to_string(), hex::encode(key.secret_bytes()));
+        Ok(())
+    }
+
+    /// Write the keystore back to its file, creating missing parent directories.
+    ///
+    /// The keys are written to a temporary file next to the keystore, which then replaces it, so
+    /// that a crash or a full disk cannot leave a truncated keystore behind.
+    pub fn save(&self) -> Result<(), KeystoreError> {
+        let io_error = |source| KeystoreError::Io {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let json = serde_json::to_vec_pretty(&self.file).expect("keystore serialization");
+        let mut temp_name = self.path.file_name().unwrap_or_default().to_owned();
+        temp_name.push(".tmp");
+        let temp_path = self.path.with_file_name(temp_name);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut temp = options.open(&temp_path).map_err(io_error)?;
+        // The mode only applies to new files, and a previous attempt may have left one behind.
+        #[cfg(unix)]
+        temp.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))
+            .map_err(io_error)?;
+        io::Write::write_all(&mut temp, &json).map_err(io_error)?;
+        temp.sync_all().map_err(io_error)?;
+        std::fs::rename(&temp_path, &self.path).map_err(io_error)?;
+        // Make the rename itself durable.
+        #[cfg(unix)]
+        if let Some(parent) = self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::File::open(parent)
+                .and_then(|dir| dir.sync_all())
+                .map_err(io_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a hex-encoded ed25519 secret key, with or without a `0x` prefix.
+pub fn parse_secret(secret: &str) -> Option<KeyPair> {
+    let secret = secret.trim();
+    let mut bytes = [0; 32];
+    hex::decode_to_slice(secret.strip_prefix("0x").unwrap_or(secret), &mut bytes).ok()?;
+    Some(KeyPair::from_secret_bytes(&bytes))
+}
@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, HashMap};
+
+use thiserror::Error;
+
+use crate::fastset_types::{
+    AccountInfoResponse, Amount, Balance, ClaimType, FastSetAddress, FastSetError, Nonce,
+    NonceRange, StateKey, TokenId, TokenInfoResponse, TokenMetadata, Transaction,
+    TransactionCertificate,
+};
+
+// An in-memory model of the state kept by a validator. It backs the local stand-ins for the proxy
+// and the validators, and only implements the claims defined in `fastset_types`.
+
+#[derive(Debug, Clone, Default)]
+pub struct AccountState {
+    pub balance: Balance,
+    pub next_nonce: Nonce,
+    pub token_balance: BTreeMap<TokenId, Balance>,
+    /// Certificates of the transactions sent by this account, by nonce
+    pub certificates: BTreeMap<Nonce, TransactionCertificate>,
+}
+
+#[derive(Debug, Error)]
+pub enum LedgerError {
+    #[error("Expected nonce {expected}, got {got}.")]
+    UnexpectedNonce { expected: Nonce, got: Nonce },
+    #[error("Insufficient balance of token {0}.")]
+    InsufficientBalance(TokenId),
+    #[error("Unknown token {0}.")]
+    UnknownToken(TokenId),
+    #[error("{0} claims are not supported.")]
+    UnsupportedClaim(&'static str),
+    #[error(transparent)]
+    FastSet(#[from] FastSetError),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    accounts: HashMap<FastSetAddress, AccountState>,
+    tokens: HashMap<TokenId, TokenMetadata>,
+}
+
+impl Ledger {
+    pub fn account(&self, address: &FastSetAddress) -> AccountState {
+        self.accounts.get(address).cloned().unwrap_or_default()
+    }
+
+    pub fn add_token(&mut self, token_id: TokenId, metadata: TokenMetadata) {
+        self.tokens.insert(token_id, metadata);
+    }
+
+    fn balance_mut(
+        &mut self,
+        address: FastSetAddress,
+        token_id: TokenId,
+    ) -> Result<&mut Balance, LedgerError> {
+        if token_id != TokenId::native() && !self.tokens.contains_key(&token_id) {
+            return Err(LedgerError::UnknownToken(token_id));
+        }
+        let account = self.accounts.entry(address).or_default();
+        Ok(if token_id == TokenId::native() {
+            &mut account.balance
+        } else {
+            account.token_balance.entry(token_id).or_default()
+        })
+    }
+
+    /// Create `amount` of `token_id` out of thin air for `recipient`, as the faucet does.
+    pub fn credit(
+        &mut self,
+        recipient: FastSetAddress,
+        token_id: TokenId,
+        amount: Amount,
+    ) -> Result<(), LedgerError> {
+        let balance = self.balance_mut(recipient, token_id)?;
+        *balance = balance
+            .checked_add(amount.into())
+            .ok_or(FastSetError::BalanceOverflow)?;
+        Ok(())
+    }
+
+    /// Check that `transaction` can be executed next: its nonce is the sender's next nonce and the
+    /// sender can afford it.
+    pub fn check(&self, transaction: &Transaction) -> Result<(), LedgerError> {
+        let sender = self.account(&transaction.sender);
+        if transaction.nonce != sender.next_nonce {
+            return Err(LedgerError::UnexpectedNonce {
+                expected: sender.next_nonce,
+                got: transaction.nonce,
+            });
+        }
+        match &transaction.claim {
+            ClaimType::TokenTransfer(transfer) => {
+                let available = if transfer.token_id == TokenId::native() {
+                    sender.balance
+                } else if self.tokens.contains_key(&transfer.token_id) {
+                    sender
+                        .token_balance
+                        .get(&transfer.token_id)
+                        .copied()
+                        .unwrap_or_default()
+                } else {
+                    return Err(LedgerError::UnknownToken(transfer.token_id));
+                };
+                if available < Balance::from(transfer.amount) {
+                    return Err(LedgerError::InsufficientBalance(transfer.token_id));
+                }
+            }
+            claim => return Err(LedgerError::UnsupportedClaim(claim.name())),
+        }
+        Ok(())
+    }
+
+    /// Execute a certified transaction and record its certificate.
+    pub fn execute(&mut self, certificate: TransactionCertificate) -> Result<(), LedgerError> {
+        let transaction = &certificate.envelope.transaction;
+        self.check(transaction)?;
+        match &transaction.claim {
+            ClaimType::TokenTransfer(transfer) => {
+                let amount = Balance::from(transfer.amount);
+                let sender = self.balance_mut(transaction.sender, transfer.token_id)?;
+                *sender = sender
+                    .checked_sub(amount)
+                    .ok_or(FastSetError::BalanceOverflow)?;
+                let recipient = self.balance_mut(transaction.recipient, transfer.token_id)?;
+                *recipient = recipient
+                    .checked_add(amount)
+                    .ok_or(FastSetError::BalanceOverflow)?;
+            }
+            claim => return Err(LedgerError::UnsupportedClaim(claim.name())),
+        }
+        let sender = self.accounts.entry(transaction.sender).or_default();
+        sender.next_nonce = Nonce(transaction.nonce.0 + 1);
+        sender.certificates.insert(transaction.nonce, certificate);
+        Ok(())
+    }
+
+    /// Answer a `proxy_getAccountInfo` request. Account state fields are not modelled, and
+    /// `pending_confirmation` is left to the caller.
+    pub fn account_info(
+        &self,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+        _state_key_filter: Option<Vec<StateKey>>,
+        certificate_by_nonce: Option<NonceRange>,
+    ) -> AccountInfoResponse {
+        let account = self.account(&address);
+        let token_balance = match token_balances_filter {
+            None => Vec::new(),
+            Some(filter) if filter.is_empty() => account.token_balance.into_iter().collect(),
+            Some(filter) => filter
+                .into_iter()
+                .map(|token_id| {
+                    let balance = account.token_balance.get(&token_id).copied();
+                    (token_id, balance.unwrap_or_default())
+                })
+                .collect(),
+        };
+        let requested_certificates = certificate_by_nonce.map(|range| {
+            let end = Nonce(range.start.0.saturating_add(range.limit as u64));
+            account
+                .certificates
+                .range(range.start..end)
+                .map(|(_, cert)| cert.clone())
+                .collect()
+        });
+        AccountInfoResponse {
+            sender: address,
+            balance: account.balance,
+            next_nonce: account.next_nonce,
+            pending_confirmation: None,
+            requested_state: Vec::new(),
+            requested_certificates,
+            requested_validated_transaction: None,
+            token_balance,
+        }
+    }
+
+    /// Answer a `proxy_getTokenInfo` request.
+    pub fn token_info(&self, token_ids: Vec<TokenId>) -> TokenInfoResponse {
+        TokenInfoResponse {
+            requested_token_metadata: token_ids
+                .into_iter()
+                .map(|token_id| (token_id, self.tokens.get(&token_id).cloned()))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+pub mod abi;
+pub mod api;
+pub mod archive;
+pub mod audit;
+pub mod client;
+pub mod committee;
+pub mod devnet;
+pub mod evm;
+pub mod fastset_types;
+pub mod finality;
+pub mod inspect;
+pub mod jsonl;
+pub mod keystore;
+pub mod ledger;
+pub mod load;
+pub mod mock_proxy;
+pub mod multi_proxy;
+pub mod offline;
+#[cfg(feature = "docs")]
+pub mod openrpc;
+pub mod payouts;
+pub mod profile;
+pub mod retry;
+#[cfg(feature = "docs")]
+pub mod rpc_docs;
+pub mod schema;
+pub mod settlement;
+pub mod subscription;
+pub mod test_vectors;
+pub mod token_registry;
+pub mod validator;
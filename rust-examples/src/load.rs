@@ -0,0 +1,351 @@
+//! Load generation against a proxy, to measure how many transfers it settles and how fast.
+//!
+//! [run_load] funds fresh accounts with `proxy_faucetDrip`, then has concurrent workers fire native
+//! transfers between them, optionally capped at a total rate. Each worker owns a disjoint set of
+//! accounts, so that it can track their nonces without asking the proxy.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use futures::future::{join_all, try_join_all};
+use jsonrpsee::core::ClientError;
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::api::faucet_drip;
+use crate::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use crate::fastset_types::{
+    Amount, ClaimType, FastSetAddress, KeyPair, Nonce, TokenId, TokenTransfer, Transaction,
+    TransactionEnvelope, UserData, get_key_pair,
+};
+
+/// Upper bounds of the latency histogram buckets, in milliseconds
+pub const LATENCY_BUCKETS_MS: [u64; 12] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1_000, 2_000, 5_000];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadConfig {
+    /// Number of accounts to fund and send from
+    pub accounts: usize,
+    /// Number of transfers in flight at once, at most one per account
+    pub concurrency: usize,
+    /// Total number of transfers to submit
+    pub transfers: usize,
+    /// Cap on the total rate of submissions, in transfers per second, see [LoadConfig::period]
+    pub rate: Option<f64>,
+    /// Native tokens dripped to each account, in least units
+    pub funding: Amount,
+    /// Native tokens sent by each transfer, in least units
+    pub amount: Amount,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            accounts: 16,
+            concurrency: 8,
+            transfers: 1_000,
+            rate: None,
+            funding: Amount::from(1_000_000),
+            amount: Amount::from(1),
+        }
+    }
+}
+
+impl LoadConfig {
+    /// The time between two submissions when the rate is capped. The rate must be a positive,
+    /// finite number of transfers per second, with a period of at least a nanosecond.
+    pub fn period(&self) -> Result<Option<Duration>, LoadError> {
+        let Some(rate) = self.rate else {
+            return Ok(None);
+        };
+        if !(rate.is_finite() && rate > 0.0) {
+            return Err(LoadError::InvalidRate(rate));
+        }
+        match Duration::try_from_secs_f64(1.0 / rate) {
+            Ok(period) if !period.is_zero() => Ok(Some(period)),
+            _ => Err(LoadError::InvalidRate(rate)),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("Invalid rate {0}, expected a positive number of transfers per second, at most 1e9.")]
+    InvalidRate(f64),
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+/// Latencies of `proxy_submitTransaction` calls, in milliseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+    pub histogram: Vec<LatencyBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LatencyBucket {
+    /// Upper bound of the bucket, from [LATENCY_BUCKETS_MS]; `None` for slower calls
+    pub le_ms: Option<u64>,
+    pub count: usize,
+}
+
+impl LatencySummary {
+    /// Summarize `latencies`. A latency falls in the first bucket whose bound it does not exceed,
+    /// fractions of a millisecond included.
+    pub fn new(mut latencies: Vec<Duration>) -> Self {
+        latencies.sort();
+        let ms = |latency: &Duration| latency.as_secs_f64() * 1_000.0;
+        let percentile = |p: usize| {
+            latencies
+                .get((latencies.len() * p).div_ceil(100).saturating_sub(1))
+                .map_or(0.0, ms)
+        };
+        let mut histogram: Vec<_> = LATENCY_BUCKETS_MS
+            .iter()
+            .map(|bound| Some(*bound))
+            .chain([None])
+            .map(|le_ms| LatencyBucket { le_ms, count: 0 })
+            .collect();
+        for latency in &latencies {
+            let bucket = LATENCY_BUCKETS_MS
+                .iter()
+                .position(|bound| ms(latency) <= *bound as f64)
+                .unwrap_or(LATENCY_BUCKETS_MS.len());
+            histogram[bucket].count += 1;
+        }
+        Self {
+            count: latencies.len(),
+            min: latencies.first().map_or(0.0, ms),
+            mean: match latencies.len() {
+                0 => 0.0,
+                count => latencies.iter().map(ms).sum::<f64>() / count as f64,
+            },
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: latencies.last().map_or(0.0, ms),
+            histogram,
+        }
+    }
+}
+
+/// The outcome of [run_load], serialized as the JSON report of the `fastset-load` binary.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub config: LoadConfig,
+    /// Time spent submitting transfers, funding excluded
+    pub elapsed_secs: f64,
+    pub submitted: usize,
+    pub settled: usize,
+    /// Settled transfers per second
+    pub throughput: f64,
+    /// Latencies of all calls, whether they settled or failed
+    pub latency: LatencySummary,
+    /// Latencies of the calls that settled
+    pub settled_latency: LatencySummary,
+    /// Number of failed calls, by [error_class]
+    pub errors: BTreeMap<String, usize>,
+}
+
+/// A short class for an error, such as `timeout` or `call:-32000`, to count errors by.
+pub fn error_class(err: &ClientError) -> String {
+    match err {
+        ClientError::Call(err) => format!("call:{}", err.code()),
+        ClientError::Transport(_) => "transport".to_string(),
+        ClientError::RequestTimeout => "timeout".to_string(),
+        ClientError::ParseError(_) => "parse".to_string(),
+        ClientError::RestartNeeded(_) | ClientError::ServiceDisconnect => {
+            "disconnected".to_string()
+        }
+        _ => "other".to_string(),
+    }
+}
+
+struct Sender {
+    key: KeyPair,
+    nonce: Nonce,
+    recipient: FastSetAddress,
+    /// The envelope of a failed call whose nonce is still unused, submitted again as is
+    unsettled: Option<TransactionEnvelope>,
+}
+
+#[derive(Default)]
+struct WorkerStats {
+    latencies: Vec<Duration>,
+    settled_latencies: Vec<Duration>,
+    errors: BTreeMap<String, usize>,
+}
+
+/// Fund `config.accounts` new accounts, submit `config.transfers` transfers between them, and
+/// report what happened.
+///
+/// After a failed call, the nonce of the account is read again from the proxy, as the transfer
+/// may have settled anyway. While the nonce is unused, the account submits the same envelope again
+/// rather than a new transfer, as
+/// [submit_transaction_with_retry](crate::retry::submit_transaction_with_retry) does. Fails only if
+/// the rate is invalid or funding the accounts fails.
+pub async fn run_load<C>(client: Arc<C>, config: LoadConfig) -> Result<LoadReport, LoadError>
+where
+    C: ProxyRpcClient + Send + Sync + 'static,
+{
+    let period = config.period()?;
+    let accounts: Vec<KeyPair> = (0..config.accounts.max(2))
+        .map(|_| get_key_pair().1)
+        .collect();
+    let addresses: Vec<_> = accounts.iter().map(KeyPair::public).collect();
+    try_join_all(
+        addresses
+            .iter()
+            .map(|address| faucet_drip(&*client, *address, config.funding)),
+    )
+    .await?;
+
+    let concurrency = config.concurrency.clamp(1, accounts.len());
+    let mut owned: Vec<Vec<Sender>> = (0..concurrency).map(|_| Vec::new()).collect();
+    for (index, key) in accounts.into_iter().enumerate() {
+        // Each account pays the next one, so that all of them keep funds.
+        owned[index % concurrency].push(Sender {
+            key,
+            nonce: Nonce(0),
+            recipient: addresses[(index + 1) % addresses.len()],
+            unsettled: None,
+        });
+    }
+    let pacing = period.map(|period| {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Arc::new(Mutex::new(interval))
+    });
+    let remaining = Arc::new(AtomicUsize::new(config.transfers));
+
+    let start = Instant::now();
+    let workers = owned.into_iter().map(|senders| {
+        tokio::spawn(worker(
+            client.clone(),
+            senders,
+            config.amount,
+            pacing.clone(),
+            remaining.clone(),
+        ))
+    });
+    let mut stats = WorkerStats::default();
+    for worker in join_all(workers).await {
+        let worker = worker.expect("load worker panicked");
+        stats.latencies.extend(worker.latencies);
+        stats.settled_latencies.extend(worker.settled_latencies);
+        for (class, count) in worker.errors {
+            *stats.errors.entry(class).or_default() += count;
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let settled = stats.settled_latencies.len();
+    Ok(LoadReport {
+        config,
+        elapsed_secs: elapsed,
+        submitted: stats.latencies.len(),
+        settled,
+        throughput: if elapsed > 0.0 {
+            settled as f64 / elapsed
+        } else {
+            0.0
+        },
+        latency: LatencySummary::new(stats.latencies),
+        settled_latency: LatencySummary::new(stats.settled_latencies),
+        errors: stats.errors,
+    })
+}
+
+async fn worker<C: ProxyRpcClient>(
+    client: Arc<C>,
+    mut senders: Vec<Sender>,
+    amount: Amount,
+    pacing: Option<Arc<Mutex<Interval>>>,
+    remaining: Arc<AtomicUsize>,
+) -> WorkerStats {
+    let mut stats = WorkerStats::default();
+    for turn in 0.. {
+        let claimed = remaining.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+            remaining.checked_sub(1)
+        });
+        if claimed.is_err() {
+            break;
+        }
+        if let Some(pacing) = &pacing {
+            pacing.lock().await.tick().await;
+        }
+        let count = senders.len();
+        let sender = &mut senders[turn % count];
+        let envelope = match sender.unsettled.take() {
+            Some(envelope) => envelope,
+            None => transfer(sender, amount),
+        };
+
+        let start = Instant::now();
+        let result = client
+            .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+            .await;
+        let latency = start.elapsed();
+        stats.latencies.push(latency);
+        let class = match result {
+            Ok(ProxySubmitTransactionResult::Success(_)) => {
+                stats.settled_latencies.push(latency);
+                sender.nonce = Nonce(sender.nonce.0 + 1);
+                continue;
+            }
+            Ok(ProxySubmitTransactionResult::IncompleteVerifierSigs()) => {
+                "incomplete_verifier_sigs".to_string()
+            }
+            Ok(ProxySubmitTransactionResult::IncompleteMultiSig()) => {
+                "incomplete_multisig".to_string()
+            }
+            Err(err) => error_class(&err),
+        };
+        *stats.errors.entry(class).or_default() += 1;
+        match client
+            .get_account_info(sender.key.public(), None, None, None)
+            .await
+        {
+            Ok(info) => {
+                if info.next_nonce == envelope.transaction.nonce {
+                    sender.unsettled = Some(envelope);
+                }
+                sender.nonce = info.next_nonce;
+            }
+            // Without the nonce, resending the same envelope cannot pay twice.
+            Err(_) => sender.unsettled = Some(envelope),
+        }
+    }
+    stats
+}
+
+/// A new transfer from `sender` at its current nonce.
+fn transfer(sender: &Sender, amount: Amount) -> TransactionEnvelope {
+    let transaction = Transaction {
+        sender: sender.key.public(),
+        recipient: sender.recipient,
+        nonce: sender.nonce,
+        timestamp_nanos: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("system clock is after the Unix epoch")
+            .as_nanos(),
+        claim: ClaimType::TokenTransfer(TokenTransfer {
+            token_id: TokenId::native(),
+            amount,
+            user_data: UserData(None),
+        }),
+        archival: false,
+    };
+    TransactionEnvelope::new(transaction, &sender.key)
+}
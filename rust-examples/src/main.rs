@@ -1,112 +1,500 @@
-use std::{str::FromStr, time::SystemTime};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::SystemTime;
 
-use jsonrpsee::http_client::HttpClient;
-
-use crate::{
-    api::{faucet_drip, get_account_info, submit_transaction}, client::ProxySubmitTransactionResult, fastset_types::{Amount, Balance, ClaimType, FastSetAddress, Nonce, TokenId, TokenTransfer, Transaction, TransactionEnvelope, UserData, get_key_pair}
+use clap::{Args, Parser, Subcommand};
+use rust_examples::{
+    client::{ProxyRpcClient, ProxySubmitTransactionResult},
+    fastset_types::{
+        AccountInfoResponse, Amount, ClaimType, FastSetAddress, KeyPair, TokenId, TokenTransfer,
+        Transaction, TransactionEnvelope, UserData, get_key_pair,
+    },
+    finality::SettleConfig,
+    inspect::{Payload, PayloadKind, TokenLabel, describe, token_labels},
+    keystore::{Keystore, parse_secret},
+    multi_proxy::{FailoverConfig, MultiProxyClient},
+    offline::{SignedTransaction, UnsignedTransaction, prepare_transfer, submit_signed},
+    payouts::{PayoutConfig, parse_payouts, run_payouts},
+    profile::{
+        Config, DEFAULT_KEY, DEFAULT_NATIVE_DECIMALS, DEFAULT_PROFILE, DEFAULT_PROXY_URL,
+        default_config_path, default_keystore_path, expand_home,
+    },
+    retry::{RetryConfig, SubmitOutcome, submit_transaction_with_retry},
 };
 
-mod api;
-mod client;
-mod fastset_types;
+type CliResult<T> = Result<T, Box<dyn Error>>;
 
-const PROXY_URL: &str = "https://proxy.fastset.xyz";
+/// A command-line wallet for the FastSet network.
+///
+/// Settings are taken from the command line, then from the environment, then from the selected
+/// profile of the configuration file.
+#[derive(Parser)]
+#[command(name = "fastset", version)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[command(subcommand)]
+    command: Command,
+}
 
-pub async fn get_next_nonce(client: &HttpClient, address: FastSetAddress) -> Nonce {
-    // Fetching the next nonce to use in the transaction.
-    // If the account is fresh, the next nonce will be 0
-    get_account_info(client, address).await.next_nonce
+#[derive(Args)]
+struct GlobalArgs {
+    /// Configuration file holding the profiles [default: ~/.fastset/config.toml]
+    #[arg(long, global = true, env = "FASTSET_CONFIG")]
+    config: Option<PathBuf>,
+    /// Profile to take the settings from
+    #[arg(long, global = true, env = "FASTSET_PROFILE", default_value = DEFAULT_PROFILE)]
+    profile: String,
+    /// Proxy URL; repeat the flag to fail over between several proxies
+    #[arg(
+        long = "proxy-url",
+        global = true,
+        env = "FASTSET_PROXY_URL",
+        value_delimiter = ','
+    )]
+    proxy_urls: Vec<String>,
+    /// Keystore file [default: ~/.fastset/keystore.json]
+    #[arg(long, global = true, env = "FASTSET_KEYSTORE")]
+    keystore: Option<PathBuf>,
+    /// Name of the key in the keystore [default: default]
+    #[arg(long, global = true, env = "FASTSET_KEY")]
+    key: Option<String>,
 }
 
-pub async fn get_balance(client: &HttpClient, address: FastSetAddress) -> Balance {
-    get_account_info(client, address).await.balance
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new key and add it to the keystore
+    Keygen {
+        /// Replace an existing key with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Add a hex-encoded secret key, read from standard input, to the keystore
+    Import {
+        /// Replace an existing key with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// List the keys in the keystore
+    Keys,
+    /// Print the address of the key
+    Address,
+    /// Show the native and token balances of an account
+    Balance {
+        /// The account [default: the address of the key]
+        address: Option<FastSetAddress>,
+        /// Only show the balance of this token (hex token ID); can be repeated
+        #[arg(long = "token")]
+        tokens: Vec<TokenId>,
+    },
+    /// Request funds from the faucet
+    Faucet {
+        /// Amount in whole tokens, e.g. 12.5
+        amount: String,
+        /// Token to request [default: the native token]
+        #[arg(long)]
+        token: Option<TokenId>,
+        /// Recipient [default: the address of the key]
+        #[arg(long)]
+        to: Option<FastSetAddress>,
+    },
+    /// Decode a transaction, envelope or certificate and check its signatures
+    Decode {
+        /// Hex-encoded BCS or proxy JSON; read from standard input if omitted or `-`
+        input: Option<String>,
+        /// What the input is: transaction, envelope or certificate [default: inferred]
+        #[arg(long)]
+        kind: Option<PayloadKind>,
+        /// Do not look up token names on the proxy
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Prepare, sign and submit transactions of keys kept on an offline machine
+    #[command(subcommand)]
+    Offline(OfflineCommand),
+    /// Transfer funds from the key's account
+    Transfer {
+        recipient: FastSetAddress,
+        /// Amount in whole tokens, e.g. 12.5
+        amount: String,
+        /// Token to transfer [default: the native token]
+        #[arg(long)]
+        token: Option<TokenId>,
+    },
+    /// Pay every row of a CSV file with columns recipient, amount, and optionally token and memo
+    Payouts {
+        file: PathBuf,
+        /// Journal recording progress, to resume an interrupted run [default: FILE.journal]
+        #[arg(long)]
+        journal: Option<PathBuf>,
+        /// File to write the per-row report to [default: FILE.report.csv]
+        #[arg(long)]
+        report: Option<PathBuf>,
+        /// Rows paid by each transaction, as a batch of transfers if more than one
+        #[arg(long, default_value_t = 1)]
+        batch_size: usize,
+    },
 }
 
-// This example shows how to sign and submit a transaction to the FastSet network via the proxy.
-// More information can be found in the docs folder.
-//
-// NOTE: when talking to validators, balances are encoded as hexadecimal numbers in JSON strings
-#[tokio::main]
-async fn main() {
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    // Generating keys for testing
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    let (sender_pub_key, sender_priv_key) = get_key_pair();
-    let (recipient_pub_key, _recipient_priv_key) = get_key_pair();
-
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    // Setting up the client to talk to the proxy
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    let client = HttpClient::builder().build(PROXY_URL).unwrap();
-
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    // Funding sender account from the faucet
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-
-    let requested_amt = Amount::from(1_000_000_000_000_000_000u64); // 1 million native tokens
-    faucet_drip(&client, sender_pub_key, requested_amt)
-        .await
-        .unwrap_or_else(|err|
-            panic!("Error while dripping amount {requested_amt} tokens to account {sender_pub_key}. Error: {err}")
-        );
-
-    println!("Dripped amount {requested_amt} tokens to account {sender_pub_key}.");
-
-    let acct_balance = get_balance(&client, sender_pub_key).await;
-    println!("Account {sender_pub_key} balance before transfer: {acct_balance}");
-
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    // Build and sign a transaction from sender
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    
-    println!("Transferring 65535 tokens from {sender_pub_key} to {recipient_pub_key}.");
-
-    let nonce = get_next_nonce(&client, sender_pub_key).await;
-
-    let transaction = Transaction {
-        sender: sender_pub_key,
-        recipient: recipient_pub_key,
-        nonce, // an incorrect nonce leads to an invalid txn
-        timestamp_nanos: SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos(),
-        claim: ClaimType::TokenTransfer(TokenTransfer {
-            token_id: TokenId::native(),
-            amount: Amount::from_str("ffff").unwrap(), // 65535 native tokens
-            user_data: UserData(None),
-        }),
-        archival: false,
-    };
-
-    let envelope = TransactionEnvelope::new(
-        transaction,
-        &sender_priv_key,
-    );
-
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    // Submit the signed transaction to the proxy
-    ////////////////////////////////////////////////////////////////////////////////////////////////////
-    
-    let submit_res = submit_transaction(&client, envelope.transaction, envelope.signature)
-        .await
-        .unwrap_or_else(|err| {
-            panic!("Error while submitting transaction: {err}");
-        });
-    match submit_res {
-        ProxySubmitTransactionResult::Success(_) => {
-            println!("Transaction submitted successfully.");
-        },
-        ProxySubmitTransactionResult::IncompleteVerifierSigs() => {
-            panic!("Transaction submission resulted in incomplete verifier signatures. This should not happen in normal circumstances.");
-        },
-        ProxySubmitTransactionResult::IncompleteMultiSig() => unimplemented!(),
+#[derive(Subcommand)]
+enum OfflineCommand {
+    /// Prepare an unsigned transfer with the sender's next nonce (online)
+    Prepare {
+        recipient: FastSetAddress,
+        /// Amount in whole tokens, e.g. 12.5
+        amount: String,
+        /// Token to transfer [default: the native token]
+        #[arg(long)]
+        token: Option<TokenId>,
+        /// Sender [default: the address of the key]
+        #[arg(long)]
+        from: Option<FastSetAddress>,
+        /// File to write the unsigned transaction to
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+    /// Show an unsigned transaction and sign it with the key (offline)
+    Sign {
+        file: PathBuf,
+        /// File to write the signed transaction to
+        #[arg(long, short)]
+        out: PathBuf,
+        /// Sign without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Submit a signed transaction (online)
+    Submit { file: PathBuf },
+}
+
+/// The settings in effect after combining the command line with the profile.
+struct Settings {
+    proxy_urls: Vec<String>,
+    keystore: PathBuf,
+    key: String,
+    native_decimals: u8,
+}
+
+impl Settings {
+    fn resolve(args: GlobalArgs) -> CliResult<Self> {
+        let config_path = args.config.unwrap_or_else(default_config_path);
+        let profile = Config::load(&config_path)?.profile(&args.profile)?;
+        let proxy_urls = [args.proxy_urls, profile.proxy_urls]
+            .into_iter()
+            .find(|urls| !urls.is_empty())
+            .unwrap_or_else(|| vec![DEFAULT_PROXY_URL.to_string()]);
+        Ok(Self {
+            proxy_urls,
+            keystore: args
+                .keystore
+                .or(profile.keystore)
+                .map_or_else(default_keystore_path, |path| expand_home(&path)),
+            key: args
+                .key
+                .or(profile.key)
+                .unwrap_or_else(|| DEFAULT_KEY.to_string()),
+            native_decimals: profile.native_decimals.unwrap_or(DEFAULT_NATIVE_DECIMALS),
+        })
+    }
+
+    fn client(&self) -> CliResult<MultiProxyClient> {
+        Ok(MultiProxyClient::new(
+            &self.proxy_urls,
+            FailoverConfig::default(),
+        )?)
+    }
+
+    fn signing_key(&self) -> CliResult<KeyPair> {
+        Ok(Keystore::open(&self.keystore)?.get(&self.key)?)
+    }
+}
+
+/// Convert a decimal amount of whole `token_id` tokens to least units.
+async fn parse_amount(
+    client: &MultiProxyClient,
+    settings: &Settings,
+    amount: &str,
+    token_id: TokenId,
+) -> CliResult<(Amount, TokenLabel)> {
+    let label = token_labels(client, vec![token_id], settings.native_decimals)
+        .await?
+        .remove(&token_id)
+        .ok_or_else(|| format!("Token {token_id} is unknown to the proxy."))?;
+    Ok((Amount::from_decimal_str(amount, label.decimals)?, label))
+}
+
+/// The freshest view of an account among the configured proxies.
+async fn account_info(
+    client: &MultiProxyClient,
+    address: FastSetAddress,
+    token_balances_filter: Option<Vec<TokenId>>,
+) -> CliResult<AccountInfoResponse> {
+    let endpoints = client.healthy_endpoints().len().max(1);
+    Ok(client
+        .consistent_account_info(address, token_balances_filter, None, None, endpoints, 1)
+        .await?
+        .info)
+}
+
+async fn run(cli: Cli) -> CliResult<()> {
+    let settings = Settings::resolve(cli.global)?;
+    match cli.command {
+        Command::Keygen { force } => {
+            let mut keystore = Keystore::open(&settings.keystore)?;
+            let (address, key) = get_key_pair();
+            keystore.insert(&settings.key, &key, force)?;
+            keystore.save()?;
+            println!("{address}");
+        }
+        Command::Import { force } => {
+            let secret = std::io::read_to_string(std::io::stdin())?;
+            let key = parse_secret(&secret).ok_or("Expected a hex-encoded 32-byte secret key.")?;
+            let mut keystore = Keystore::open(&settings.keystore)?;
+            keystore.insert(&settings.key, &key, force)?;
+            keystore.save()?;
+            println!("{}", key.public());
+        }
+        Command::Keys => {
+            let keystore = Keystore::open(&settings.keystore)?;
+            for name in keystore.names() {
+                println!("{name}\t{}", keystore.get(name)?.public());
+            }
+        }
+        Command::Address => println!("{}", settings.signing_key()?.public()),
+        Command::Balance { address, tokens } => {
+            let address = match address {
+                Some(address) => address,
+                None => settings.signing_key()?.public(),
+            };
+            let client = settings.client()?;
+            // An empty filter asks for the balances of all tokens held by the account.
+            let show_native = tokens.is_empty() || tokens.contains(&TokenId::native());
+            let info = account_info(&client, address, Some(tokens)).await?;
+            let token_balance: Vec<_> = info
+                .token_balance
+                .iter()
+                .filter(|(token_id, _)| *token_id != TokenId::native())
+                .collect();
+
+            let mut token_ids: Vec<_> = token_balance.iter().map(|(id, _)| *id).collect();
+            token_ids.push(TokenId::native());
+            let labels = token_labels(&client, token_ids, settings.native_decimals).await?;
+
+            println!("{address} (next nonce {})", info.next_nonce);
+            if show_native {
+                let native = &labels[&TokenId::native()];
+                println!(
+                    "  {} {}",
+                    info.balance.to_decimal_string(native.decimals),
+                    native.name
+                );
+            }
+            for (token_id, balance) in token_balance {
+                match labels.get(token_id) {
+                    Some(token) => println!(
+                        "  {} {} ({token_id})",
+                        balance.to_decimal_string(token.decimals),
+                        token.name
+                    ),
+                    None => println!("  {balance} least units of unknown token {token_id}"),
+                }
+            }
+        }
+        Command::Faucet { amount, token, to } => {
+            let recipient = match to {
+                Some(recipient) => recipient,
+                None => settings.signing_key()?.public(),
+            };
+            let token_id = token.unwrap_or_else(TokenId::native);
+            let client = settings.client()?;
+            let (amount, token) = parse_amount(&client, &settings, &amount, token_id).await?;
+            client
+                .faucet_drip(recipient, amount, Some(token_id))
+                .await?;
+            println!(
+                "Dripped {} {} to {recipient}.",
+                amount.to_decimal_string(token.decimals),
+                token.name
+            );
+        }
+        Command::Decode {
+            input,
+            kind,
+            offline,
+        } => {
+            let input = match input.filter(|input| input != "-") {
+                Some(input) => input,
+                None => std::io::read_to_string(std::io::stdin())?,
+            };
+            let payload = Payload::decode(&input, kind)?;
+            let native = TokenLabel {
+                name: "SET".to_string(),
+                decimals: settings.native_decimals,
+            };
+            let mut labels = BTreeMap::from([(TokenId::native(), native)]);
+            if !offline {
+                let client = settings.client()?;
+                match token_labels(&client, payload.token_ids(), settings.native_decimals).await {
+                    Ok(found) => labels.extend(found),
+                    Err(err) => eprintln!("warning: cannot look up token names: {err}"),
+                }
+            }
+            print!("{}", describe(&payload, &labels));
+        }
+        Command::Offline(command) => run_offline(&settings, command).await?,
+        Command::Transfer {
+            recipient,
+            amount,
+            token,
+        } => {
+            let key = settings.signing_key()?;
+            let sender = key.public();
+            let token_id = token.unwrap_or_else(TokenId::native);
+            let client = settings.client()?;
+            let (amount, token) = parse_amount(&client, &settings, &amount, token_id).await?;
+            let nonce = account_info(&client, sender, None).await?.next_nonce;
+
+            let transaction = Transaction {
+                sender,
+                recipient,
+                nonce,
+                timestamp_nanos: SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)?
+                    .as_nanos(),
+                claim: ClaimType::TokenTransfer(TokenTransfer {
+                    token_id,
+                    amount,
+                    user_data: UserData(None),
+                }),
+                archival: false,
+            };
+            let envelope = TransactionEnvelope::new(transaction, &key);
+            // The same signed envelope is resent on ambiguous failures, so a timeout can never
+            // turn into a second payment under a fresh nonce.
+            let outcome =
+                submit_transaction_with_retry(&client, &envelope, &RetryConfig::default()).await?;
+            match outcome {
+                SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(_))
+                | SubmitOutcome::AlreadySettled(_) => {}
+                SubmitOutcome::Submitted(result) => {
+                    return Err(format!("Transaction was not settled: {result:?}").into());
+                }
+            }
+            // Balances read right away may come from a validator that has not processed the
+            // certificate yet.
+            if let Err(err) = client
+                .await_settled(sender, nonce, &SettleConfig::default())
+                .await
+            {
+                eprintln!("Warning: balances may not reflect the transfer yet. {err}");
+            }
+            println!(
+                "Transferred {} {} from {sender} to {recipient} (nonce {nonce}).",
+                amount.to_decimal_string(token.decimals),
+                token.name
+            );
+        }
+        Command::Payouts {
+            file,
+            journal,
+            report,
+            batch_size,
+        } => {
+            let key = settings.signing_key()?;
+            let client = settings.client()?;
+            let text = std::fs::read_to_string(&file)
+                .map_err(|err| format!("Cannot read {}: {err}", file.display()))?;
+            let payouts = parse_payouts(&client, &text, settings.native_decimals).await?;
+            let journal = journal.unwrap_or_else(|| file.with_extension("journal"));
+            let config = PayoutConfig {
+                batch_size,
+                retry: RetryConfig::default(),
+            };
+            let outcome = run_payouts(&client, &key, &payouts, &journal, &config).await?;
+            let report = report.unwrap_or_else(|| file.with_extension("report.csv"));
+            std::fs::write(&report, outcome.to_csv())
+                .map_err(|err| format!("Cannot write {}: {err}", report.display()))?;
+            println!("{outcome} Report written to {}.", report.display());
+            if !outcome.is_complete() {
+                return Err(
+                    "Not all rows were paid; run again with the same journal to resume.".into(),
+                );
+            }
+        }
     }
+    Ok(())
+}
 
-    let sender_balance = get_balance(&client, sender_pub_key).await;
-    println!("Account {sender_pub_key} balance after transfer: {sender_balance}");
+async fn run_offline(settings: &Settings, command: OfflineCommand) -> CliResult<()> {
+    match command {
+        OfflineCommand::Prepare {
+            recipient,
+            amount,
+            token,
+            from,
+            out,
+        } => {
+            let sender = match from {
+                Some(sender) => sender,
+                None => settings.signing_key()?.public(),
+            };
+            let token_id = token.unwrap_or_else(TokenId::native);
+            let client = settings.client()?;
+            let (amount, _) = parse_amount(&client, settings, &amount, token_id).await?;
+            let unsigned = prepare_transfer(
+                &client,
+                sender,
+                recipient,
+                token_id,
+                amount,
+                settings.native_decimals,
+            )
+            .await?;
+            unsigned.save(&out)?;
+            println!("{}\ndigest {}", unsigned.summary, unsigned.digest);
+        }
+        OfflineCommand::Sign { file, out, yes } => {
+            let unsigned = UnsignedTransaction::load(&file)?;
+            unsigned.verify()?;
+            println!("{}\ndigest {}", unsigned.summary, unsigned.digest);
+            if !yes {
+                print!("Sign this transaction? [y/N] ");
+                std::io::Write::flush(&mut std::io::stdout())?;
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                    return Err("Not signed.".into());
+                }
+            }
+            unsigned.sign(&settings.signing_key()?)?.save(&out)?;
+            println!("Signed transaction written to {}.", out.display());
+        }
+        OfflineCommand::Submit { file } => {
+            let signed = SignedTransaction::load(&file)?;
+            let client = settings.client()?;
+            match submit_signed(&client, &signed, &RetryConfig::default()).await? {
+                SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(_))
+                | SubmitOutcome::AlreadySettled(_) => {
+                    println!("{}\ndigest {}\nSettled.", signed.summary, signed.digest)
+                }
+                SubmitOutcome::Submitted(result) => {
+                    return Err(format!("Transaction was not settled: {result:?}").into());
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
-    let recipient_balance = get_balance(&client, recipient_pub_key).await;
-    println!("Account {recipient_pub_key} balance after transfer: {recipient_balance}");
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,249 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use jsonrpsee::core::{RpcResult, SubscriptionResult, async_trait};
+use jsonrpsee::server::{PendingSubscriptionSink, Server, ServerHandle, SubscriptionSink};
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::types::error::{CALL_EXECUTION_FAILED_CODE, INVALID_PARAMS_CODE};
+use serde::Serialize;
+use serde_json::value::to_raw_value;
+use tokio::sync::broadcast;
+
+use crate::client::{ProxyRpcServer, ProxySubmitTransactionResult, ProxySubscriptionRpcServer};
+use crate::fastset_types::{
+    AccountInfoResponse, Amount, CrossSignResponse, FastSetAddress, KeyPair, Nonce, NonceRange,
+    Signature, SignatureOrMultiSig, StateKey, TokenId, TokenInfoResponse, TokenMetadata,
+    Transaction, TransactionCertificate, TransactionEnvelope, ValidatorName, get_key_pair,
+};
+use crate::ledger::{Ledger, LedgerError};
+
+/// A proxy that settles transactions in memory instead of talking to validators, for running
+/// client code without a network.
+///
+/// Every transaction that passes the sender signature, nonce and balance checks is settled at
+/// once, with a certificate signed by all members of a generated committee. Validators sign the
+/// [Transaction] the same way senders do. `proxy_evmSignCertificate` is not supported.
+#[derive(Clone)]
+pub struct MockProxy {
+    ledger: Arc<Mutex<Ledger>>,
+    committee: Arc<Vec<KeyPair>>,
+    /// Notifies subscriptions of the accounts touched by each change
+    updates: broadcast::Sender<FastSetAddress>,
+}
+
+impl MockProxy {
+    pub fn new(validators: usize) -> Self {
+        Self {
+            ledger: Arc::default(),
+            committee: Arc::new((0..validators).map(|_| get_key_pair().1).collect()),
+            updates: broadcast::channel(1024).0,
+        }
+    }
+
+    /// The names of the validators signing the certificates.
+    pub fn committee(&self) -> Vec<ValidatorName> {
+        self.committee.iter().map(KeyPair::public).collect()
+    }
+
+    /// Register a custom token, so that it can be dripped and transferred.
+    pub fn add_token(&self, token_id: TokenId, metadata: TokenMetadata) {
+        self.ledger.lock().unwrap().add_token(token_id, metadata);
+    }
+
+    /// Serve the proxy API over HTTP and WebSocket on a free localhost port.
+    pub async fn start(self) -> std::io::Result<(SocketAddr, ServerHandle)> {
+        let server = Server::builder().build("127.0.0.1:0").await?;
+        let addr = server.local_addr()?;
+        let mut module = ProxyRpcServer::into_rpc(self.clone());
+        module
+            .merge(ProxySubscriptionRpcServer::into_rpc(self))
+            .expect("the subscriptions do not clash with the proxy methods");
+        Ok((addr, server.start(module)))
+    }
+
+    fn certify(&self, envelope: TransactionEnvelope) -> TransactionCertificate {
+        let signatures = self
+            .committee
+            .iter()
+            .map(|key| (key.public(), Signature::new(&envelope.transaction, key)))
+            .collect();
+        TransactionCertificate {
+            envelope,
+            signatures,
+        }
+    }
+
+    fn account_info(
+        &self,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+        state_key_filter: Option<Vec<StateKey>>,
+        certificate_by_nonce: Option<NonceRange>,
+    ) -> AccountInfoResponse {
+        self.ledger.lock().unwrap().account_info(
+            address,
+            token_balances_filter,
+            state_key_filter,
+            certificate_by_nonce,
+        )
+    }
+}
+
+fn rejected(err: impl ToString) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(CALL_EXECUTION_FAILED_CODE, err.to_string(), None::<()>)
+}
+
+impl From<LedgerError> for ErrorObjectOwned {
+    fn from(err: LedgerError) -> Self {
+        rejected(err)
+    }
+}
+
+async fn send(sink: &SubscriptionSink, item: &impl Serialize) -> SubscriptionResult {
+    sink.send(to_raw_value(item)?).await?;
+    Ok(())
+}
+
+/// Wait until `address` may have changed (lagging behind on updates counts as a change). Returns
+/// `false` once the subscription is closed.
+async fn wait_for_update(
+    sink: &SubscriptionSink,
+    updates: &mut broadcast::Receiver<FastSetAddress>,
+    address: FastSetAddress,
+) -> bool {
+    loop {
+        tokio::select! {
+            _ = sink.closed() => return false,
+            update = updates.recv() => match update {
+                Ok(touched) if touched != address => continue,
+                Err(broadcast::error::RecvError::Closed) => return false,
+                _ => return true,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl ProxyRpcServer for MockProxy {
+    async fn submit_transaction(
+        &self,
+        transaction: Transaction,
+        signature: SignatureOrMultiSig,
+    ) -> RpcResult<ProxySubmitTransactionResult> {
+        if let SignatureOrMultiSig::MultiSig(_) = signature {
+            return Ok(ProxySubmitTransactionResult::IncompleteMultiSig());
+        }
+        let envelope = TransactionEnvelope {
+            transaction,
+            signature,
+        };
+        envelope.verify().map_err(|err| {
+            ErrorObjectOwned::owned(INVALID_PARAMS_CODE, err.to_string(), None::<()>)
+        })?;
+        let certificate = self.certify(envelope);
+        self.ledger.lock().unwrap().execute(certificate.clone())?;
+
+        let transaction = &certificate.envelope.transaction;
+        let _ = self.updates.send(transaction.sender);
+        let _ = self.updates.send(transaction.recipient);
+        Ok(ProxySubmitTransactionResult::Success(certificate))
+    }
+
+    async fn faucet_drip(
+        &self,
+        recipient: FastSetAddress,
+        amount: Amount,
+        token_id: Option<TokenId>,
+    ) -> RpcResult<()> {
+        self.ledger.lock().unwrap().credit(
+            recipient,
+            token_id.unwrap_or_else(TokenId::native),
+            amount,
+        )?;
+        let _ = self.updates.send(recipient);
+        Ok(())
+    }
+
+    async fn get_account_info(
+        &self,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+        state_key_filter: Option<Vec<StateKey>>,
+        certificate_by_nonce: Option<NonceRange>,
+    ) -> RpcResult<AccountInfoResponse> {
+        Ok(self.account_info(
+            address,
+            token_balances_filter,
+            state_key_filter,
+            certificate_by_nonce,
+        ))
+    }
+
+    async fn get_token_info(&self, token_ids: Vec<TokenId>) -> RpcResult<TokenInfoResponse> {
+        Ok(self.ledger.lock().unwrap().token_info(token_ids))
+    }
+
+    async fn evm_sign_certificate(
+        &self,
+        _certificate: TransactionCertificate,
+    ) -> RpcResult<CrossSignResponse> {
+        Err(rejected(
+            "the mock proxy does not sign certificates for EVM",
+        ))
+    }
+}
+
+#[async_trait]
+impl ProxySubscriptionRpcServer for MockProxy {
+    async fn subscribe_account_info(
+        &self,
+        pending: PendingSubscriptionSink,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+    ) -> SubscriptionResult {
+        let mut updates = self.updates.subscribe();
+        let sink = pending.accept().await?;
+        let mut last = None;
+        loop {
+            let info = self.account_info(address, token_balances_filter.clone(), None, None);
+            if last.as_ref() != Some(&info) {
+                send(&sink, &info).await?;
+                last = Some(info);
+            }
+            if !wait_for_update(&sink, &mut updates, address).await {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn subscribe_certificates(
+        &self,
+        pending: PendingSubscriptionSink,
+        address: FastSetAddress,
+        start_nonce: Option<Nonce>,
+    ) -> SubscriptionResult {
+        let mut updates = self.updates.subscribe();
+        let sink = pending.accept().await?;
+        let mut cursor = match start_nonce {
+            Some(nonce) => nonce,
+            None => self.account_info(address, None, None, None).next_nonce,
+        };
+        loop {
+            let range = NonceRange {
+                start: cursor,
+                limit: usize::MAX,
+            };
+            let certificates = self
+                .account_info(address, None, None, Some(range))
+                .requested_certificates
+                .unwrap_or_default();
+            for certificate in certificates {
+                cursor = Nonce(certificate.envelope.transaction.nonce.0 + 1);
+                send(&sink, &certificate).await?;
+            }
+            if !wait_for_update(&sink, &mut updates, address).await {
+                return Ok(());
+            }
+        }
+    }
+}
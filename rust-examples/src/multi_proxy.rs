@@ -0,0 +1,377 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use jsonrpsee::core::ClientError;
+use jsonrpsee::core::client::{BatchResponse, ClientT, Subscription, SubscriptionClientT};
+use jsonrpsee::core::params::BatchRequestBuilder;
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use serde::de::DeserializeOwned;
+use serde_json::value::RawValue;
+use thiserror::Error;
+
+use crate::client::ProxyRpcClient;
+use crate::fastset_types::{
+    AccountInfoResponse, FastSetAddress, Nonce, NonceRange, StateKey, TokenId,
+    TransactionCertificate,
+};
+use crate::finality::{SettleConfig, SettleError, await_settled};
+use crate::retry::is_ambiguous;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Endpoint health
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone)]
+pub struct FailoverConfig {
+    /// Number of consecutive transport failures after which an endpoint is considered unhealthy
+    pub failure_threshold: u32,
+    /// How long an unhealthy endpoint is only used as a last resort
+    pub cooldown: Duration,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+struct Endpoint {
+    url: String,
+    client: HttpClient,
+    health: Mutex<Health>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self, now: Instant) -> bool {
+        let health = self.health.lock().unwrap();
+        health.unhealthy_until.is_none_or(|until| until <= now)
+    }
+
+    /// Update the health of the endpoint after a request returned `result`. Errors returned by the
+    /// proxy itself (as opposed to connection failures) do not count against it.
+    fn record<T>(&self, result: &Result<T, ClientError>, config: &FailoverConfig) {
+        let mut health = self.health.lock().unwrap();
+        match result {
+            Err(err) if is_ambiguous(err) => {
+                health.consecutive_failures += 1;
+                if health.consecutive_failures >= config.failure_threshold {
+                    health.unhealthy_until = Some(Instant::now() + config.cooldown);
+                }
+            }
+            _ => *health = Health::default(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Multi-proxy client
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A client spreading requests over several proxies.
+///
+/// Requests go to the first healthy endpoint in the configured order. When the connection fails,
+/// reads and submissions are retried on the next endpoint, and endpoints that keep failing are
+/// skipped for a cooldown period (they are still tried last if every endpoint is unhealthy). Other
+/// requests, such as `proxy_faucetDrip`, may have taken effect before the failure, so their error
+/// is returned instead of sending them again; so is the error of a batch.
+///
+/// `MultiProxyClient` implements the jsonrpsee client traits, so all `ProxyRpcClient` methods and
+/// the helpers of this crate can be used on it directly. Subscriptions are not supported over HTTP,
+/// so the `subscription` module falls back to polling.
+pub struct MultiProxyClient {
+    endpoints: Vec<Endpoint>,
+    config: FailoverConfig,
+}
+
+impl MultiProxyClient {
+    pub fn new<S: AsRef<str>>(urls: &[S], config: FailoverConfig) -> Result<Self, ClientError> {
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                Ok(Endpoint {
+                    url: url.as_ref().to_string(),
+                    client: HttpClientBuilder::default().build(url)?,
+                    health: Mutex::default(),
+                })
+            })
+            .collect::<Result<Vec<_>, ClientError>>()?;
+        if endpoints.is_empty() {
+            return Err(ClientError::Custom("no proxy endpoint configured".into()));
+        }
+        Ok(Self { endpoints, config })
+    }
+
+    /// URLs of the endpoints that are currently considered healthy.
+    pub fn healthy_endpoints(&self) -> Vec<&str> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_healthy(now))
+            .map(|endpoint| endpoint.url.as_str())
+            .collect()
+    }
+
+    /// Endpoints in the order they should be tried: healthy ones first.
+    fn by_preference(&self) -> impl Iterator<Item = &Endpoint> {
+        let now = Instant::now();
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = self
+            .endpoints
+            .iter()
+            .partition(|endpoint| endpoint.is_healthy(now));
+        healthy.into_iter().chain(unhealthy)
+    }
+
+    /// Make `call` on the preferred endpoint and, if `failover` and the outcome is unknown, on the
+    /// next ones.
+    async fn with_failover<T, F, Fut>(&self, failover: bool, mut call: F) -> Result<T, ClientError>
+    where
+        F: FnMut(HttpClient) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut last_error = None;
+        for endpoint in self.by_preference() {
+            // `HttpClient` is a cheap handle, cloning lets the call own it.
+            let result = call(endpoint.client.clone()).await;
+            endpoint.record(&result, &self.config);
+            match result {
+                Err(err) if failover && is_ambiguous(&err) => last_error = Some(err),
+                result => return result,
+            }
+        }
+        Err(last_error.expect("at least one endpoint is configured"))
+    }
+}
+
+/// Methods that can be sent to another endpoint when the outcome of a call is unknown: reads, and
+/// submissions, which send the same signed envelope again and so settle at most once.
+const FAILOVER_METHODS: [&str; 4] = [
+    "proxy_getAccountInfo",
+    "proxy_getTokenInfo",
+    "proxy_evmSignCertificate",
+    "proxy_submitTransaction",
+];
+
+/// Parameters serialized once and replayed to every endpoint tried.
+struct RawParams(Option<Box<RawValue>>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, serde_json::Error> {
+        Ok(self.0)
+    }
+}
+
+impl ClientT for MultiProxyClient {
+    fn notification<Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> impl Future<Output = Result<(), ClientError>> + Send
+    where
+        Params: ToRpcParams + Send,
+    {
+        let params = params.to_rpc_params();
+        async move {
+            let params = params?;
+            self.with_failover(FAILOVER_METHODS.contains(&method), |client| {
+                let params = RawParams(params.clone());
+                async move { client.notification(method, params).await }
+            })
+            .await
+        }
+    }
+
+    fn request<R, Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> impl Future<Output = Result<R, ClientError>> + Send
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        let params = params.to_rpc_params();
+        async move {
+            let params = params?;
+            self.with_failover(FAILOVER_METHODS.contains(&method), |client| {
+                let params = RawParams(params.clone());
+                async move { client.request(method, params).await }
+            })
+            .await
+        }
+    }
+
+    fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> impl Future<Output = Result<BatchResponse<'a, R>, ClientError>> + Send
+    where
+        R: DeserializeOwned + std::fmt::Debug + 'a,
+    {
+        self.with_failover(false, move |client| {
+            let batch = batch.clone();
+            async move { client.batch_request(batch).await }
+        })
+    }
+}
+
+impl SubscriptionClientT for MultiProxyClient {
+    async fn subscribe<'a, Notif, Params>(
+        &self,
+        _subscribe_method: &'a str,
+        _params: Params,
+        _unsubscribe_method: &'a str,
+    ) -> Result<Subscription<Notif>, ClientError>
+    where
+        Params: ToRpcParams + Send,
+        Notif: DeserializeOwned,
+    {
+        Err(ClientError::HttpNotImplemented)
+    }
+
+    async fn subscribe_to_method<Notif>(
+        &self,
+        _method: &str,
+    ) -> Result<Subscription<Notif>, ClientError>
+    where
+        Notif: DeserializeOwned,
+    {
+        Err(ClientError::HttpNotImplemented)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Consistency-checked reads
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The freshest account view found among several proxies.
+#[derive(Debug)]
+pub struct ConsistentRead {
+    pub info: AccountInfoResponse,
+    /// Number of endpoints which answered
+    pub responses: usize,
+    /// Endpoints whose view had a lower `next_nonce` than `info`
+    pub lagging: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ConsistencyError {
+    #[error(
+        "Only {responses} of the {required} required proxies answered. Last error: {last_error:?}"
+    )]
+    Unavailable {
+        responses: usize,
+        required: usize,
+        last_error: Option<ClientError>,
+    },
+    /// Endpoints report the same `next_nonce` but different balances or state, so there is no way
+    /// to tell which view is the freshest.
+    #[error("Proxies {endpoints:?} disagree on the account state at the same nonce.")]
+    Disagreement { endpoints: Vec<String> },
+}
+
+/// Whether two views of an account at the same nonce describe the same state, whatever the order
+/// in which they list token balances and state.
+fn same_state(a: &AccountInfoResponse, b: &AccountInfoResponse) -> bool {
+    let tokens = |info: &AccountInfoResponse| -> BTreeMap<_, _> {
+        info.token_balance.iter().copied().collect()
+    };
+    let state = |info: &AccountInfoResponse| -> BTreeMap<_, _> {
+        info.requested_state
+            .iter()
+            .map(|(key, state)| (key.0, state.0))
+            .collect()
+    };
+    a.balance == b.balance && tokens(a) == tokens(b) && state(a) == state(b)
+}
+
+impl MultiProxyClient {
+    /// Query the account on the first `endpoints` endpoints (by health) and return the view with
+    /// the highest `next_nonce`.
+    ///
+    /// Each response reflects a single validator, which may be lagging behind the network. Fails
+    /// if fewer than `required` endpoints answer, or if the freshest views disagree on balances or
+    /// state (incoming transfers do not advance the nonce, so such views cannot be ordered).
+    pub async fn consistent_account_info(
+        &self,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+        state_key_filter: Option<Vec<StateKey>>,
+        certificate_by_nonce: Option<NonceRange>,
+        endpoints: usize,
+        required: usize,
+    ) -> Result<ConsistentRead, ConsistencyError> {
+        let queried: Vec<_> = self.by_preference().take(endpoints).collect();
+        let results = join_all(queried.iter().map(|endpoint| {
+            endpoint.client.get_account_info(
+                address,
+                token_balances_filter.clone(),
+                state_key_filter.clone(),
+                certificate_by_nonce.clone(),
+            )
+        }))
+        .await;
+
+        let mut views = Vec::new();
+        let mut last_error = None;
+        for (endpoint, result) in queried.into_iter().zip(results) {
+            endpoint.record(&result, &self.config);
+            match result {
+                Ok(info) => views.push((endpoint.url.clone(), info)),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        if views.len() < required.max(1) {
+            return Err(ConsistencyError::Unavailable {
+                responses: views.len(),
+                required,
+                last_error,
+            });
+        }
+
+        let freshest = views.iter().map(|(_, info)| info.next_nonce).max().unwrap();
+        let (current, lagging): (Vec<_>, Vec<_>) = views
+            .into_iter()
+            .partition(|(_, info)| info.next_nonce == freshest);
+        let responses = current.len() + lagging.len();
+        let (_, reference) = &current[0];
+        if current.iter().any(|(_, info)| !same_state(reference, info)) {
+            return Err(ConsistencyError::Disagreement {
+                endpoints: current.into_iter().map(|(url, _)| url).collect(),
+            });
+        }
+        Ok(ConsistentRead {
+            info: current.into_iter().next().unwrap().1,
+            responses,
+            lagging: lagging.into_iter().map(|(url, _)| url).collect(),
+        })
+    }
+
+    /// Wait until the transaction of `address` with `nonce` is settled on
+    /// [SettleConfig::confirmations] of the endpoints, healthy ones being queried first. See
+    /// [await_settled].
+    pub async fn await_settled(
+        &self,
+        address: FastSetAddress,
+        nonce: Nonce,
+        config: &SettleConfig,
+    ) -> Result<TransactionCertificate, SettleError> {
+        let clients: Vec<HttpClient> = self
+            .by_preference()
+            .map(|endpoint| endpoint.client.clone())
+            .collect();
+        await_settled(&clients, address, nonce, config).await
+    }
+}
@@ -0,0 +1,275 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use jsonrpsee::core::ClientError;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::client::ProxyRpcClient;
+use crate::fastset_types::{
+    Amount, ClaimType, EnvelopeError, FastSetAddress, KeyPair, Signable, TokenId, TokenTransfer,
+    Transaction, TransactionEnvelope, UserData,
+};
+use crate::inspect::{TokenLabel, format_timestamp, token_labels};
+use crate::retry::{RetryConfig, SubmitError, SubmitOutcome, submit_transaction_with_retry};
+
+/// Version of the unsigned and signed transaction file formats
+pub const FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum OfflineError {
+    #[error("Unsupported file version {0}, expected {FILE_VERSION}.")]
+    UnsupportedVersion(u32),
+    #[error("The summary in the file does not match its transaction.")]
+    SummaryMismatch,
+    #[error("The digest in the file does not match its transaction.")]
+    DigestMismatch,
+    #[error(
+        "The transaction is sent by {sender}, which is not the address of the signing key {key}."
+    )]
+    WrongKey {
+        sender: FastSetAddress,
+        key: FastSetAddress,
+    },
+    #[error("Invalid sender signature: {0}")]
+    Signature(EnvelopeError),
+    #[error("Cannot access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{path} is malformed: {source}")]
+    Format {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+/// The SHA-256 hash of the exact message signed for `transaction`: the `Transaction::` seed
+/// followed by its BCS bytes.
+pub fn signing_digest(transaction: &Transaction) -> [u8; 32] {
+    let mut message = Vec::new();
+    transaction.write(&mut message);
+    Sha256::digest(&message).into()
+}
+
+/// A few lines describing what signing `transaction` authorizes. Amounts are always shown in least
+/// units next to the token ID, so a wrong `token` label cannot hide what is transferred.
+pub fn summarize(transaction: &Transaction, token: Option<&TokenLabel>) -> String {
+    let (action, user_data) = match &transaction.claim {
+        ClaimType::TokenTransfer(transfer) => {
+            let least_units = transfer.amount.to_decimal_string(0);
+            let amount = match token {
+                Some(token) => format!(
+                    "{} {} ({least_units} least units of token {})",
+                    transfer.amount.to_decimal_string(token.decimals),
+                    token.name,
+                    transfer.token_id
+                ),
+                None => format!("{least_units} least units of token {}", transfer.token_id),
+            };
+            (format!("Transfer {amount}"), transfer.user_data.0)
+        }
+        // Other claims are shown whole rather than summarized.
+        claim => (
+            format!(
+                "{} claim {}",
+                claim.name(),
+                serde_json::to_string_pretty(claim).expect("claims serialize to JSON")
+            ),
+            None,
+        ),
+    };
+    let mut summary = format!(
+        "{action}\nfrom {}\nto {}\nnonce {}, created {}",
+        transaction.sender,
+        transaction.recipient,
+        transaction.nonce,
+        format_timestamp(transaction.timestamp_nanos).unwrap_or_else(|| "out of range".into()),
+    );
+    if let Some(user_data) = user_data {
+        summary.push_str(&format!("\nuser data 0x{}", hex::encode(user_data)));
+    }
+    if transaction.archival {
+        summary.push_str("\narchival");
+    }
+    summary
+}
+
+/// A transaction prepared on a networked machine, to be signed on an offline one.
+///
+/// The summary and digest are recomputed from the transaction before signing, so a tampered
+/// file is refused rather than signed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub version: u32,
+    /// What the transaction does, see [summarize]
+    pub summary: String,
+    /// Hex-encoded [signing_digest] of the transaction
+    pub digest: String,
+    /// How to display the transferred token, as known to the proxy when the file was prepared
+    pub token: Option<TokenLabel>,
+    pub transaction: Transaction,
+}
+
+/// A transaction signed offline, to be submitted from a networked machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub version: u32,
+    pub summary: String,
+    pub digest: String,
+    pub token: Option<TokenLabel>,
+    pub envelope: TransactionEnvelope,
+}
+
+impl UnsignedTransaction {
+    pub fn new(transaction: Transaction, token: Option<TokenLabel>) -> Self {
+        Self {
+            version: FILE_VERSION,
+            summary: summarize(&transaction, token.as_ref()),
+            digest: hex::encode(signing_digest(&transaction)),
+            token,
+            transaction,
+        }
+    }
+
+    /// Check that the summary and digest describe the transaction.
+    pub fn verify(&self) -> Result<(), OfflineError> {
+        if self.version != FILE_VERSION {
+            return Err(OfflineError::UnsupportedVersion(self.version));
+        }
+        if self.summary != summarize(&self.transaction, self.token.as_ref()) {
+            return Err(OfflineError::SummaryMismatch);
+        }
+        if self.digest != hex::encode(signing_digest(&self.transaction)) {
+            return Err(OfflineError::DigestMismatch);
+        }
+        Ok(())
+    }
+
+    /// Sign the transaction with `key`, which must belong to the sender. Needs no network access.
+    pub fn sign(&self, key: &KeyPair) -> Result<SignedTransaction, OfflineError> {
+        self.verify()?;
+        if key.public() != self.transaction.sender {
+            return Err(OfflineError::WrongKey {
+                sender: self.transaction.sender,
+                key: key.public(),
+            });
+        }
+        Ok(SignedTransaction {
+            version: FILE_VERSION,
+            summary: self.summary.clone(),
+            digest: self.digest.clone(),
+            token: self.token.clone(),
+            envelope: TransactionEnvelope::new(self.transaction.clone(), key),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, OfflineError> {
+        read_json(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), OfflineError> {
+        write_json(path, self)
+    }
+}
+
+impl SignedTransaction {
+    /// Check the summary, the digest and the sender signature.
+    pub fn verify(&self) -> Result<(), OfflineError> {
+        if self.version != FILE_VERSION {
+            return Err(OfflineError::UnsupportedVersion(self.version));
+        }
+        if self.summary != summarize(&self.envelope.transaction, self.token.as_ref()) {
+            return Err(OfflineError::SummaryMismatch);
+        }
+        if self.digest != hex::encode(signing_digest(&self.envelope.transaction)) {
+            return Err(OfflineError::DigestMismatch);
+        }
+        self.envelope.verify().map_err(OfflineError::Signature)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, OfflineError> {
+        read_json(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), OfflineError> {
+        write_json(path, self)
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, OfflineError> {
+    let bytes = std::fs::read(path).map_err(|source| OfflineError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_slice(&bytes).map_err(|source| OfflineError::Format {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), OfflineError> {
+    let json = serde_json::to_string_pretty(value).expect("transaction file serialization");
+    std::fs::write(path, json + "\n").map_err(|source| OfflineError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Prepare a transfer from `sender` with its next nonce, for signing offline.
+///
+/// The nonce is only valid until the sender's next transaction settles, so the signed file must be
+/// submitted before any other transaction from the same account.
+pub async fn prepare_transfer(
+    client: &impl ProxyRpcClient,
+    sender: FastSetAddress,
+    recipient: FastSetAddress,
+    token_id: TokenId,
+    amount: Amount,
+    native_decimals: u8,
+) -> Result<UnsignedTransaction, ClientError> {
+    let nonce = client
+        .get_account_info(sender, None, None, None)
+        .await?
+        .next_nonce;
+    let token = token_labels(client, vec![token_id], native_decimals)
+        .await?
+        .remove(&token_id);
+    let transaction = Transaction {
+        sender,
+        recipient,
+        nonce,
+        timestamp_nanos: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("system clock is after the Unix epoch")
+            .as_nanos(),
+        claim: ClaimType::TokenTransfer(TokenTransfer {
+            token_id,
+            amount,
+            user_data: UserData(None),
+        }),
+        archival: false,
+    };
+    Ok(UnsignedTransaction::new(transaction, token))
+}
+
+#[derive(Debug, Error)]
+pub enum SubmitSignedError {
+    #[error(transparent)]
+    Offline(#[from] OfflineError),
+    #[error(transparent)]
+    Submit(#[from] SubmitError),
+}
+
+/// Verify a signed transaction file and submit its envelope, retrying the same envelope on
+/// ambiguous failures.
+pub async fn submit_signed(
+    client: &impl ProxyRpcClient,
+    signed: &SignedTransaction,
+    config: &RetryConfig,
+) -> Result<SubmitOutcome, SubmitSignedError> {
+    signed.verify()?;
+    Ok(submit_transaction_with_retry(client, &signed.envelope, config).await?)
+}
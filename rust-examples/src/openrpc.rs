@@ -0,0 +1,191 @@
+//! An [OpenRPC](https://spec.open-rpc.org/) document for the proxy API, built from the same
+//! sources as `rpc.md`: the [ProxyRpc](crate::client::ProxyRpc) trait and the JSON Schemas of the
+//! wire types.
+//!
+//! OpenRPC has no notion of subscriptions. Subscribe methods are described as methods returning
+//! the subscription ID, with an `x-notification` extension naming the notification method and the
+//! schema of its items, and each is followed by its unsubscribe method.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value, json};
+use syn::{GenericArgument, PathArguments, Type};
+
+use crate::rpc_docs::{Endpoint, EndpointKind, endpoints};
+use crate::schema::definitions;
+
+pub const OPENRPC_VERSION: &str = "1.2.6";
+const COMPONENTS_PREFIX: &str = "#/components/schemas/";
+
+/// Build the OpenRPC document.
+pub fn document() -> syn::Result<Value> {
+    let types = definitions();
+    let mut methods = Vec::new();
+    for endpoint in endpoints()? {
+        methods.push(method(&endpoint, &types)?);
+        if let EndpointKind::Subscription { unsubscribe, .. } = &endpoint.kind {
+            methods.push(json!({
+                "name": unsubscribe,
+                "summary": format!("Cancel a `{}` subscription.", endpoint.name),
+                "paramStructure": "by-position",
+                "params": [{
+                    "name": "subscription",
+                    "required": true,
+                    "schema": subscription_id_schema(),
+                }],
+                "result": {
+                    "name": "unsubscribed",
+                    "schema": { "type": "boolean" },
+                },
+            }));
+        }
+    }
+    let schemas: Map<String, Value> = types
+        .into_iter()
+        .map(|(name, mut schema)| {
+            rewrite_refs(&mut schema);
+            (name, schema)
+        })
+        .collect();
+    Ok(json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "FastSet Proxy JSON-RPC API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods,
+        "components": { "schemas": schemas },
+    }))
+}
+
+/// Render the document the way `openrpc.json` is written.
+pub fn to_file_contents(document: &Value) -> String {
+    serde_json::to_string_pretty(document).expect("OpenRPC serialization") + "\n"
+}
+
+fn method(endpoint: &Endpoint, types: &BTreeMap<String, Value>) -> syn::Result<Value> {
+    let mut params = Vec::new();
+    for (name, ty) in &endpoint.params {
+        let (inner, required) = match option_argument(ty) {
+            Some(inner) => (inner, false),
+            None => (ty, true),
+        };
+        params.push(json!({
+            "name": name,
+            "required": required,
+            "schema": type_schema(inner, types)?,
+        }));
+    }
+    let mut method = json!({
+        "name": endpoint.name,
+        "summary": endpoint.docs.lines().next().unwrap_or_default(),
+        "description": endpoint.docs,
+        "paramStructure": "by-name",
+        "params": params,
+    });
+    match &endpoint.kind {
+        EndpointKind::Method { returns } => {
+            method["result"] = json!({
+                "name": result_name(returns),
+                "schema": type_schema(returns, types)?,
+            });
+        }
+        EndpointKind::Subscription {
+            notification, item, ..
+        } => {
+            method["result"] = json!({
+                "name": "subscription",
+                "schema": subscription_id_schema(),
+            });
+            method["x-notification"] = json!({
+                "name": notification,
+                "schema": type_schema(item, types)?,
+            });
+        }
+    }
+    Ok(method)
+}
+
+/// Subscription IDs are numbers or strings depending on the server.
+fn subscription_id_schema() -> Value {
+    json!({ "type": ["integer", "string"] })
+}
+
+fn result_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or_else(String::new, |segment| segment.ident.to_string()),
+        _ => "null".to_string(),
+    }
+}
+
+/// The `T` of an `Option<T>` parameter, which may be omitted.
+fn option_argument(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    generic_argument(&segment.arguments)
+}
+
+fn generic_argument(arguments: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(args) = arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+/// The schema of a Rust parameter or result type, referring to the components.
+fn type_schema(ty: &Type, types: &BTreeMap<String, Value>) -> syn::Result<Value> {
+    let unsupported = || syn::Error::new_spanned(ty, "unsupported type in RPC trait");
+    match ty {
+        Type::Tuple(tuple) if tuple.elems.is_empty() => Ok(json!({ "type": "null" })),
+        Type::Path(path) => {
+            let segment = path.path.segments.last().ok_or_else(unsupported)?;
+            let ident = segment.ident.to_string();
+            match ident.as_str() {
+                "Option" => {
+                    let inner = generic_argument(&segment.arguments).ok_or_else(unsupported)?;
+                    Ok(json!({ "oneOf": [type_schema(inner, types)?, { "type": "null" }] }))
+                }
+                "Vec" => {
+                    let inner = generic_argument(&segment.arguments).ok_or_else(unsupported)?;
+                    Ok(json!({ "type": "array", "items": type_schema(inner, types)? }))
+                }
+                name if types.contains_key(name) => {
+                    Ok(json!({ "$ref": format!("{COMPONENTS_PREFIX}{name}") }))
+                }
+                _ => Err(unsupported()),
+            }
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+fn rewrite_refs(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => {
+                        if let Some(name) = reference.strip_prefix("#/$defs/") {
+                            *reference = format!("{COMPONENTS_PREFIX}{name}");
+                        }
+                    }
+                    (_, value) => rewrite_refs(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
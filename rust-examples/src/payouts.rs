@@ -0,0 +1,637 @@
+//! Bulk payouts from a CSV file, resumable after an interruption without paying anyone twice.
+//!
+//! The file has a header row naming its columns: `recipient` (bech32m address) and `amount` (in
+//! whole tokens, e.g. `12.5`) are required; `token` (hex token ID, the native token if empty) and
+//! `memo` (sent as user data) are optional. Every row is validated before anything is paid.
+//!
+//! Rows are paid in file order, in chunks of [PayoutConfig::batch_size] consecutive rows, each
+//! chunk with a single [ClaimType::Batch] transaction of one transfer per row. A chunk of a single
+//! row is paid with a plain transfer, so a batch size of 1 falls back to sequential transfers, for
+//! proxies that do not execute batches. A rejected batch is not split up automatically, since the
+//! rejection does not rule out that it settled.
+//!
+//! Each signed envelope is recorded in a journal before it is submitted, so that a resumed run
+//! resends the very same envelope instead of signing a new one under a fresh nonce. The rows of a
+//! chunk are journaled together, and a journal only resumes runs with the batch size it was
+//! created for.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use jsonrpsee::core::ClientError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use crate::fastset_types::{
+    Amount, ClaimType, FastSetAddress, KeyPair, Nonce, Operation, OperationBundle,
+    ParseAddressError, ParseDecimalError, TokenId, TokenTransfer, TokenTransferOperation,
+    Transaction, TransactionEnvelope, UserData,
+};
+use crate::inspect::token_labels;
+use crate::jsonl::{JsonLines, JsonLinesError};
+use crate::offline::signing_digest;
+use crate::retry::{RetryConfig, SubmitError, SubmitOutcome, submit_transaction_with_retry};
+
+/// Version of the journal file format
+pub const JOURNAL_VERSION: u32 = 2;
+
+#[derive(Debug, Error)]
+pub enum PayoutError {
+    #[error("Malformed header row, expected e.g. \"recipient,amount,token,memo\": {0}")]
+    MalformedHeader(String),
+    #[error("{} invalid rows:\n{}", .0.len(), list_rows(.0))]
+    InvalidRows(Vec<RowError>),
+    #[error("The journal was written for other payouts, another sender or another batch size.")]
+    JournalMismatch,
+    #[error("Unsupported journal version {0}, expected {JOURNAL_VERSION}.")]
+    UnsupportedVersion(u32),
+    #[error("Cannot access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Line {line} of {path} is malformed: {source}")]
+    Format {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+impl From<JsonLinesError> for PayoutError {
+    fn from(error: JsonLinesError) -> Self {
+        match error {
+            JsonLinesError::Io { path, source } => Self::Io { path, source },
+            JsonLinesError::Format { path, line, source } => Self::Format { path, line, source },
+        }
+    }
+}
+
+fn list_rows(errors: &[RowError]) -> String {
+    let lines: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    lines.join("\n")
+}
+
+/// A row of the CSV file that cannot be paid.
+#[derive(Debug, Error)]
+#[error("Line {line}: {problem}")]
+pub struct RowError {
+    pub line: usize,
+    pub problem: RowProblem,
+}
+
+#[derive(Debug, Error)]
+pub enum RowProblem {
+    #[error("Expected {expected} fields, found {found}.")]
+    FieldCount { expected: usize, found: usize },
+    #[error("Unterminated quoted field.")]
+    UnterminatedQuote,
+    #[error("Invalid recipient: {0}")]
+    Recipient(ParseAddressError),
+    #[error("Invalid token ID: {0}")]
+    Token(hex::FromHexError),
+    #[error("Token {0} is unknown to the proxy.")]
+    UnknownToken(TokenId),
+    #[error("Invalid amount: {0}")]
+    Amount(ParseDecimalError),
+    #[error("The amount is zero.")]
+    ZeroAmount,
+    #[error("The memo is longer than 32 bytes.")]
+    MemoTooLong,
+}
+
+/// A validated row of the CSV file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Payout {
+    /// Line of the row in the CSV file, starting at 1 with the header
+    pub line: usize,
+    pub recipient: FastSetAddress,
+    pub token_id: TokenId,
+    /// Amount in least units
+    pub amount: Amount,
+    /// Decimals of the token, for the report
+    pub decimals: u8,
+    pub memo: UserData,
+}
+
+/// A row as written in the file, before token decimals are known.
+struct Row {
+    line: usize,
+    recipient: FastSetAddress,
+    token_id: TokenId,
+    amount: String,
+    memo: UserData,
+}
+
+/// Parse and validate the CSV file `text`, looking up the decimals of its tokens on the proxy.
+/// All invalid rows are reported at once.
+pub async fn parse_payouts(
+    client: &impl ProxyRpcClient,
+    text: &str,
+    native_decimals: u8,
+) -> Result<Vec<Payout>, PayoutError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| PayoutError::MalformedHeader("the file is empty".to_string()))?;
+    let header = split_record(header)
+        .map_err(|problem| PayoutError::MalformedHeader(problem.to_string()))?;
+    let column = |name: &str| header.iter().position(|field| field.trim() == name);
+    let (Some(recipient), Some(amount)) = (column("recipient"), column("amount")) else {
+        return Err(PayoutError::MalformedHeader(
+            "the recipient and amount columns are required".to_string(),
+        ));
+    };
+    let (token, memo) = (column("token"), column("memo"));
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (line, record) in lines {
+        let parsed = split_record(record).and_then(|fields| {
+            if fields.len() != header.len() {
+                return Err(RowProblem::FieldCount {
+                    expected: header.len(),
+                    found: fields.len(),
+                });
+            }
+            let field = |index: Option<usize>| index.map_or("", |index| fields[index].trim());
+            Ok(Row {
+                line,
+                recipient: field(Some(recipient))
+                    .parse()
+                    .map_err(RowProblem::Recipient)?,
+                token_id: match field(token) {
+                    "" => TokenId::native(),
+                    token_id => token_id.parse().map_err(RowProblem::Token)?,
+                },
+                amount: field(Some(amount)).to_string(),
+                memo: parse_memo(field(memo))?,
+            })
+        });
+        match parsed {
+            Ok(row) => rows.push(row),
+            Err(problem) => errors.push(RowError { line, problem }),
+        }
+    }
+
+    let mut token_ids: Vec<TokenId> = rows.iter().map(|row| row.token_id).collect();
+    token_ids.sort();
+    token_ids.dedup();
+    let labels = token_labels(client, token_ids, native_decimals).await?;
+    let mut payouts = Vec::new();
+    for row in rows {
+        let validated = labels
+            .get(&row.token_id)
+            .ok_or(RowProblem::UnknownToken(row.token_id))
+            .and_then(|label| {
+                let amount = Amount::from_decimal_str(&row.amount, label.decimals)
+                    .map_err(RowProblem::Amount)?;
+                if amount == Amount::from(0) {
+                    return Err(RowProblem::ZeroAmount);
+                }
+                Ok(Payout {
+                    line: row.line,
+                    recipient: row.recipient,
+                    token_id: row.token_id,
+                    amount,
+                    decimals: label.decimals,
+                    memo: row.memo,
+                })
+            });
+        match validated {
+            Ok(payout) => payouts.push(payout),
+            Err(problem) => errors.push(RowError {
+                line: row.line,
+                problem,
+            }),
+        }
+    }
+    if !errors.is_empty() {
+        errors.sort_by_key(|error| error.line);
+        return Err(PayoutError::InvalidRows(errors));
+    }
+    Ok(payouts)
+}
+
+/// Split a CSV record into fields. Fields may be quoted, with `""` standing for a quote.
+fn split_record(record: &str) -> Result<Vec<String>, RowProblem> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = record.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            (false, ',') => fields.push(std::mem::take(&mut field)),
+            (_, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(RowProblem::UnterminatedQuote);
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// A memo is either `0x` followed by 32 hex-encoded bytes, or text of at most 32 bytes, padded
+/// with zeros.
+fn parse_memo(memo: &str) -> Result<UserData, RowProblem> {
+    if memo.is_empty() {
+        return Ok(UserData(None));
+    }
+    let mut bytes = [0; 32];
+    if let Some(hex) = memo.strip_prefix("0x")
+        && hex::decode_to_slice(hex, &mut bytes).is_ok()
+    {
+        return Ok(UserData(Some(bytes)));
+    }
+    if memo.len() > bytes.len() {
+        return Err(RowProblem::MemoTooLong);
+    }
+    bytes[..memo.len()].copy_from_slice(memo.as_bytes());
+    Ok(UserData(Some(bytes)))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Journal
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Serialize, Deserialize)]
+struct JournalHeader {
+    version: u32,
+    sender: FastSetAddress,
+    batch_size: usize,
+    /// SHA-256 of the JSON encoding of the payouts
+    payouts: String,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JournalEntry {
+    /// The envelope paying the chunk of rows at `lines`, recorded before it is first submitted
+    Signed {
+        lines: Vec<usize>,
+        envelope: TransactionEnvelope,
+    },
+    /// The envelope of the chunk settled
+    Settled { lines: Vec<usize> },
+    /// The nonce of the envelope was used by another transaction, so it can never settle
+    Abandoned { lines: Vec<usize> },
+}
+
+/// What the journal says about a chunk of rows.
+#[derive(Default)]
+struct ChunkJournal {
+    envelope: Option<TransactionEnvelope>,
+    settled: bool,
+}
+
+struct Journal {
+    file: JsonLines,
+    /// By the lines of the rows of the chunk
+    chunks: BTreeMap<Vec<usize>, ChunkJournal>,
+}
+
+impl Journal {
+    fn open(
+        path: &Path,
+        sender: FastSetAddress,
+        batch_size: usize,
+        payouts: &[Payout],
+    ) -> Result<Self, PayoutError> {
+        let expected = JournalHeader {
+            version: JOURNAL_VERSION,
+            sender,
+            batch_size,
+            payouts: hex::encode(Sha256::digest(
+                serde_json::to_vec(payouts).expect("payouts serialization"),
+            )),
+        };
+        let (file, lines) = JsonLines::open(path)?;
+        let mut journal = Self {
+            file,
+            chunks: BTreeMap::new(),
+        };
+        let mut lines = lines.iter();
+        match lines.next() {
+            None => journal.file.append(&expected)?,
+            Some(header) => {
+                let header: JournalHeader = journal.file.parse(1, header)?;
+                if header.version != JOURNAL_VERSION {
+                    return Err(PayoutError::UnsupportedVersion(header.version));
+                }
+                if header.sender != expected.sender
+                    || header.batch_size != expected.batch_size
+                    || header.payouts != expected.payouts
+                {
+                    return Err(PayoutError::JournalMismatch);
+                }
+            }
+        }
+        for (index, line) in lines.enumerate() {
+            let entry = journal.file.parse(index + 2, line)?;
+            journal.apply(entry);
+        }
+        Ok(journal)
+    }
+
+    fn record(&mut self, entry: JournalEntry) -> Result<(), PayoutError> {
+        self.file.append(&entry)?;
+        self.apply(entry);
+        Ok(())
+    }
+
+    fn apply(&mut self, entry: JournalEntry) {
+        match entry {
+            JournalEntry::Signed { lines, envelope } => {
+                self.chunks.entry(lines).or_default().envelope = Some(envelope);
+            }
+            JournalEntry::Settled { lines } => self.chunks.entry(lines).or_default().settled = true,
+            JournalEntry::Abandoned { lines } => {
+                self.chunks.entry(lines).or_default().envelope = None;
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Payout run
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Controls how rows are grouped into transactions and how their submissions are retried.
+#[derive(Debug, Clone)]
+pub struct PayoutConfig {
+    /// Number of rows paid by each transaction, as a batch of transfers if more than one
+    pub batch_size: usize,
+    pub retry: RetryConfig,
+}
+
+impl Default for PayoutConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1,
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowStatus {
+    /// The row is paid, alone or with the other rows of its batch, by the transaction with this
+    /// nonce and
+    /// [signing_digest](crate::offline::signing_digest), the hash a
+    /// [CertificateArchive](crate::archive::CertificateArchive) indexes certificates by
+    Paid { nonce: Nonce, hash: [u8; 32] },
+    /// Paying the row or its batch failed, which stopped the run
+    Failed(String),
+    /// The run stopped before the row
+    Pending,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowReport {
+    pub payout: Payout,
+    pub status: RowStatus,
+}
+
+/// The outcome of [run_payouts], row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutReport {
+    pub rows: Vec<RowReport>,
+}
+
+impl PayoutReport {
+    pub fn is_complete(&self) -> bool {
+        self.rows
+            .iter()
+            .all(|row| matches!(row.status, RowStatus::Paid { .. }))
+    }
+
+    /// The report as CSV, with a row per payout. The `signing_digest` column of a paid row holds
+    /// the [signing_digest](crate::offline::signing_digest) of its transaction, not a hash reported
+    /// by the proxy.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "line,recipient,amount,token,memo,status,nonce,signing_digest\n".to_string();
+        for row in &self.rows {
+            let payout = &row.payout;
+            let memo = payout.memo.0.map(hex::encode).unwrap_or_default();
+            let (status, nonce, hash) = match &row.status {
+                RowStatus::Paid { nonce, hash } => {
+                    ("paid".to_string(), nonce.to_string(), hex::encode(hash))
+                }
+                RowStatus::Failed(reason) => {
+                    (format!("failed: {reason}"), String::new(), String::new())
+                }
+                RowStatus::Pending => ("pending".to_string(), String::new(), String::new()),
+            };
+            csv.push_str(&format!(
+                "{},{},{},{},{memo},{},{nonce},{hash}\n",
+                payout.line,
+                payout.recipient,
+                payout.amount.to_decimal_string(payout.decimals),
+                payout.token_id,
+                quote(&status),
+            ));
+        }
+        csv
+    }
+}
+
+impl Display for PayoutReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let count = |matches: fn(&RowStatus) -> bool| {
+            self.rows.iter().filter(|row| matches(&row.status)).count()
+        };
+        write!(
+            f,
+            "{} paid, {} failed, {} pending.",
+            count(|status| matches!(status, RowStatus::Paid { .. })),
+            count(|status| matches!(status, RowStatus::Failed(_))),
+            count(|status| matches!(status, RowStatus::Pending)),
+        )
+    }
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Pay `payouts` from the account of `key`, recording progress in the journal at
+/// `journal_path`.
+///
+/// Chunks of rows the journal marks as settled are skipped, and chunks signed by an interrupted run
+/// are resubmitted with the same envelope. Other chunks are signed with the sender's next nonce,
+/// read from the proxy. The first chunk that fails stops the run; running again with the same
+/// journal resumes from there. The journal only accepts the payouts, sender and batch size it was
+/// created for. A batch size of 0 is taken as 1.
+pub async fn run_payouts(
+    client: &impl ProxyRpcClient,
+    key: &KeyPair,
+    payouts: &[Payout],
+    journal_path: &Path,
+    config: &PayoutConfig,
+) -> Result<PayoutReport, PayoutError> {
+    let sender = key.public();
+    let batch_size = config.batch_size.max(1);
+    let mut journal = Journal::open(journal_path, sender, batch_size, payouts)?;
+    let mut rows = Vec::new();
+    let mut stopped = false;
+    for chunk in payouts.chunks(batch_size) {
+        let status = if stopped {
+            RowStatus::Pending
+        } else {
+            match pay(client, key, chunk, &mut journal, &config.retry).await? {
+                Ok(status) => status,
+                Err(reason) => {
+                    stopped = true;
+                    RowStatus::Failed(reason)
+                }
+            }
+        };
+        rows.extend(chunk.iter().map(|payout| RowReport {
+            payout: payout.clone(),
+            status: status.clone(),
+        }));
+    }
+    Ok(PayoutReport { rows })
+}
+
+/// Pay a chunk of rows with a single transaction. Journal errors are returned as errors, and
+/// failures of the chunk as `Ok(Err)`.
+async fn pay(
+    client: &impl ProxyRpcClient,
+    key: &KeyPair,
+    chunk: &[Payout],
+    journal: &mut Journal,
+    config: &RetryConfig,
+) -> Result<Result<RowStatus, String>, PayoutError> {
+    let lines: Vec<_> = chunk.iter().map(|payout| payout.line).collect();
+    loop {
+        let journaled = journal.chunks.get(&lines);
+        let envelope = match journaled.and_then(|journaled| journaled.envelope.clone()) {
+            Some(envelope) if journaled.is_some_and(|journaled| journaled.settled) => {
+                return Ok(Ok(paid(&envelope)));
+            }
+            Some(envelope) => envelope,
+            None => {
+                let nonce = match client
+                    .get_account_info(key.public(), None, None, None)
+                    .await
+                {
+                    Ok(info) => info.next_nonce,
+                    Err(err) => return Ok(Err(err.to_string())),
+                };
+                let envelope = TransactionEnvelope::new(payment(key, chunk, nonce), key);
+                journal.record(JournalEntry::Signed {
+                    lines: lines.clone(),
+                    envelope: envelope.clone(),
+                })?;
+                envelope
+            }
+        };
+        match submit_transaction_with_retry(client, &envelope, config).await {
+            Ok(
+                SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(certificate))
+                | SubmitOutcome::AlreadySettled(certificate),
+            ) => {
+                // Only a certificate of this very transaction pays the rows.
+                if certificate.envelope.transaction != envelope.transaction {
+                    return Ok(Err(format!(
+                        "the certificate returned for nonce {} is for another transaction",
+                        envelope.transaction.nonce
+                    )));
+                }
+                journal.record(JournalEntry::Settled {
+                    lines: lines.clone(),
+                })?;
+                return Ok(Ok(paid(&envelope)));
+            }
+            Ok(SubmitOutcome::Submitted(result)) => {
+                return Ok(Err(format!("the transaction was not settled: {result:?}")));
+            }
+            // Another transaction took the nonce, so the envelope can no longer settle and the
+            // chunk is signed again with the next nonce.
+            Err(SubmitError::NonceConflict(_)) => {
+                journal.record(JournalEntry::Abandoned {
+                    lines: lines.clone(),
+                })?;
+            }
+            // The envelope may have settled, so it is neither settled nor abandoned: the chunk
+            // stays unresolved until a proxy shows the certificate of the nonce.
+            Err(err @ SubmitError::NonceConsumed(_)) => return Ok(Err(err.to_string())),
+            Err(err) => return Ok(Err(err.to_string())),
+        }
+    }
+}
+
+fn paid(envelope: &TransactionEnvelope) -> RowStatus {
+    RowStatus::Paid {
+        nonce: envelope.transaction.nonce,
+        hash: signing_digest(&envelope.transaction),
+    }
+}
+
+/// The transaction paying `chunk`: a transfer for a single row, or a batch of transfers, addressed
+/// to the sender, whose operations name the recipients.
+fn payment(key: &KeyPair, chunk: &[Payout], nonce: Nonce) -> Transaction {
+    let (recipient, claim) = match chunk {
+        [payout] => (
+            payout.recipient,
+            ClaimType::TokenTransfer(TokenTransfer {
+                token_id: payout.token_id,
+                amount: payout.amount,
+                user_data: payout.memo,
+            }),
+        ),
+        chunk => (
+            key.public(),
+            ClaimType::Batch(OperationBundle(
+                chunk
+                    .iter()
+                    .map(|payout| {
+                        Operation::TokenTransfer(TokenTransferOperation {
+                            token_id: payout.token_id,
+                            recipient: payout.recipient,
+                            amount: payout.amount,
+                            user_data: payout.memo,
+                        })
+                    })
+                    .collect(),
+            )),
+        ),
+    };
+    Transaction {
+        sender: key.public(),
+        recipient,
+        nonce,
+        timestamp_nanos: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("system clock is after the Unix epoch")
+            .as_nanos(),
+        claim,
+        archival: false,
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+pub const DEFAULT_PROXY_URL: &str = "https://proxy.fastset.xyz";
+pub const DEFAULT_PROFILE: &str = "default";
+pub const DEFAULT_KEY: &str = "default";
+/// Decimals assumed for the native token when the proxy has no metadata for it
+pub const DEFAULT_NATIVE_DECIMALS: u8 = 18;
+
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    #[error("Cannot read configuration {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Configuration {path} is malformed: {source}")]
+    Format {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("No profile named {0:?} in the configuration.")]
+    UnknownProfile(String),
+}
+
+/// Settings shared by the commands run with a profile. Every setting can be overridden on the
+/// command line.
+///
+/// ```toml
+/// [profiles.ops]
+/// proxy_urls = ["https://proxy.fastset.xyz"]
+/// keystore = "~/.fastset/ops-keys.json"
+/// key = "treasury"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Proxies to talk to, in order of preference
+    pub proxy_urls: Vec<String>,
+    /// Path of the [Keystore](crate::keystore::Keystore) file
+    pub keystore: Option<PathBuf>,
+    /// Name of the key used to sign transactions
+    pub key: Option<String>,
+    /// Decimals of the native token when amounts are shown, [DEFAULT_NATIVE_DECIMALS] if unset
+    pub native_decimals: Option<u8>,
+}
+
+/// The wallet configuration file: a set of named profiles.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Load the configuration at `path`. A missing file is an empty configuration.
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|source| ProfileError::Format {
+                path: path.to_path_buf(),
+                source,
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(ProfileError::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    /// The profile called `name`. The default profile may be left out of the configuration.
+    pub fn profile(&self, name: &str) -> Result<Profile, ProfileError> {
+        match self.profiles.get(name) {
+            Some(profile) => Ok(profile.clone()),
+            None if name == DEFAULT_PROFILE => Ok(Profile::default()),
+            None => Err(ProfileError::UnknownProfile(name.to_string())),
+        }
+    }
+}
+
+/// The directory holding the wallet configuration and default keystore, `~/.fastset`.
+pub fn fastset_dir() -> PathBuf {
+    std::env::home_dir().unwrap_or_default().join(".fastset")
+}
+
+pub fn default_config_path() -> PathBuf {
+    fastset_dir().join("config.toml")
+}
+
+pub fn default_keystore_path() -> PathBuf {
+    fastset_dir().join("keystore.json")
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => std::env::home_dir().unwrap_or_default().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use jsonrpsee::core::ClientError;
+use rand::Rng;
+use thiserror::Error;
+
+use crate::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use crate::fastset_types::{Nonce, NonceRange, TransactionCertificate, TransactionEnvelope};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Retry configuration
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Controls how often and how patiently a submission is retried.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Maximum number of calls to `proxy_submitTransaction` (including the first one)
+    pub max_attempts: u32,
+    /// Upper bound of the delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between any two attempts
+    pub max_backoff: Duration,
+    /// Factor applied to the backoff after every attempt
+    pub multiplier: u32,
+    /// Maximum number of account lookups spent waiting on a transaction that is still pending,
+    /// before the same envelope is submitted again to drive it to completion
+    pub max_status_checks: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2,
+            max_status_checks: 20,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Exponential backoff with full jitter: a uniformly random delay between zero and
+    /// `initial_backoff * multiplier^retries`, capped at `max_backoff`, where `retries` is the
+    /// number of delays already waited.
+    pub fn backoff(&self, retries: u32) -> Duration {
+        let ceiling = self
+            .multiplier
+            .checked_pow(retries)
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff));
+        let millis = u64::try_from(ceiling.as_millis()).unwrap_or(u64::MAX);
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Submission status
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// What the network knows about a previously submitted envelope.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum SubmissionStatus {
+    /// The network settled the transaction, as shown by its certificate.
+    Settled(TransactionCertificate),
+    /// The sender's nonce has moved past the transaction, but no certificate tells which
+    /// transaction used it: this one, whose certificate was pruned, or another one.
+    NonceConsumed,
+    /// A validator has signed the transaction, but it has not been confirmed yet.
+    Pending,
+    /// No validator has seen the transaction: resending the same envelope is safe.
+    Unseen,
+}
+
+/// Result of a retrying submission.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum SubmitOutcome {
+    /// The proxy answered the submission itself.
+    Submitted(ProxySubmitTransactionResult),
+    /// An earlier attempt whose response was lost had already settled the transaction.
+    AlreadySettled(TransactionCertificate),
+}
+
+#[derive(Debug, Error)]
+pub enum SubmitError {
+    /// The proxy answered with an error and the transaction did not settle.
+    #[error("Transaction rejected by the proxy: {0}")]
+    Rejected(ClientError),
+    /// The nonce of the transaction was used by a different transaction from the same sender.
+    #[error("Nonce {0} was consumed by a different transaction.")]
+    NonceConflict(Nonce),
+    /// The nonce of the transaction was used, but it is unknown whether by this transaction.
+    #[error("Nonce {0} was consumed, but no certificate shows by which transaction.")]
+    NonceConsumed(Nonce),
+    /// The retry budget ran out without a definite outcome.
+    #[error("Gave up after {attempts} attempts. Last error: {last_error}")]
+    Exhausted {
+        attempts: u32,
+        last_error: ClientError,
+    },
+}
+
+/// Ask the network what happened to `envelope`.
+///
+/// The decision is based on the sender's account info:
+///
+/// - `next_nonce` beyond the transaction nonce means the nonce is used up; the certificate
+///   requested through `certificate_by_nonce` tells whether it was used by this transaction, and
+///   without it the outcome is [SubmissionStatus::NonceConsumed];
+/// - a `pending_confirmation` with the transaction nonce means validators are still working on it;
+/// - otherwise the transaction is unknown and the same envelope can be resent.
+pub async fn check_submission_status(
+    client: &impl ProxyRpcClient,
+    envelope: &TransactionEnvelope,
+) -> Result<SubmissionStatus, SubmitError> {
+    let transaction = &envelope.transaction;
+    let info = client
+        .get_account_info(
+            transaction.sender,
+            None,
+            None,
+            Some(NonceRange {
+                start: transaction.nonce,
+                limit: 1,
+            }),
+        )
+        .await
+        .map_err(SubmitError::Rejected)?;
+
+    if info.next_nonce > transaction.nonce {
+        let certificate = info
+            .requested_certificates
+            .into_iter()
+            .flatten()
+            .find(|cert| cert.envelope.transaction.nonce == transaction.nonce);
+        return match certificate {
+            Some(cert) if cert.envelope.transaction != *transaction => {
+                Err(SubmitError::NonceConflict(transaction.nonce))
+            }
+            Some(cert) => Ok(SubmissionStatus::Settled(cert)),
+            None => Ok(SubmissionStatus::NonceConsumed),
+        };
+    }
+
+    match info.pending_confirmation {
+        Some(pending) if pending.value.transaction.nonce == transaction.nonce => {
+            if pending.value.transaction != *transaction {
+                return Err(SubmitError::NonceConflict(transaction.nonce));
+            }
+            Ok(SubmissionStatus::Pending)
+        }
+        _ => Ok(SubmissionStatus::Unseen),
+    }
+}
+
+/// Errors after which the request may or may not have reached the proxy.
+pub(crate) fn is_ambiguous(err: &ClientError) -> bool {
+    matches!(
+        err,
+        ClientError::Transport(_)
+            | ClientError::RestartNeeded(_)
+            | ClientError::RequestTimeout
+            | ClientError::ServiceDisconnect
+            | ClientError::ParseError(_)
+    )
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Retrying submission
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Submit `envelope`, retrying with exponential backoff when the outcome is unknown.
+///
+/// After every ambiguous failure (timeouts, dropped connections, garbled responses) the sender's
+/// account is inspected with [check_submission_status] before anything is resent:
+///
+/// - if the transaction settled, [SubmitOutcome::AlreadySettled] is returned, and if its nonce was
+///   used without a certificate to show by which transaction, [SubmitError::NonceConsumed];
+/// - if it is still pending, the account is polled again instead of resubmitting, until
+///   [RetryConfig::max_status_checks] lookups suggest that nobody is completing it any more;
+/// - otherwise the *same* envelope is submitted again.
+///
+/// The envelope is never re-signed and its nonce is never changed, so a retry can at worst be
+/// rejected as a duplicate; it can never pay twice.
+pub async fn submit_transaction_with_retry(
+    client: &impl ProxyRpcClient,
+    envelope: &TransactionEnvelope,
+    config: &RetryConfig,
+) -> Result<SubmitOutcome, SubmitError> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let err = match client
+            .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+            .await
+        {
+            Ok(result) => return Ok(SubmitOutcome::Submitted(result)),
+            Err(err) => err,
+        };
+
+        if !is_ambiguous(&err) {
+            // The proxy answered, but a rejection such as a validator timeout does not rule out
+            // that the transaction went through in the meantime.
+            return match check_submission_status(client, envelope).await {
+                Ok(SubmissionStatus::Settled(cert)) => Ok(SubmitOutcome::AlreadySettled(cert)),
+                Ok(SubmissionStatus::NonceConsumed) => {
+                    Err(SubmitError::NonceConsumed(envelope.transaction.nonce))
+                }
+                Ok(_) | Err(SubmitError::Rejected(_)) => Err(SubmitError::Rejected(err)),
+                Err(conflict) => Err(conflict),
+            };
+        }
+
+        if attempt >= config.max_attempts {
+            return Err(SubmitError::Exhausted {
+                attempts: attempt,
+                last_error: err,
+            });
+        }
+
+        let mut checks = 0;
+        loop {
+            tokio::time::sleep(config.backoff(attempt - 1 + checks)).await;
+            match check_submission_status(client, envelope).await {
+                Ok(SubmissionStatus::Settled(cert)) => {
+                    return Ok(SubmitOutcome::AlreadySettled(cert));
+                }
+                Ok(SubmissionStatus::NonceConsumed) => {
+                    return Err(SubmitError::NonceConsumed(envelope.transaction.nonce));
+                }
+                Ok(SubmissionStatus::Unseen) => break,
+                Ok(SubmissionStatus::Pending) | Err(SubmitError::Rejected(_)) => {}
+                Err(err) => return Err(err),
+            }
+            checks += 1;
+            if checks >= config.max_status_checks {
+                // Validators vote for the same envelope again, so resubmitting lets the proxy
+                // finish a certificate that was abandoned halfway.
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,544 @@
+//! Generate `docs/proxy/rpc.md` from the [ProxyRpc](crate::client::ProxyRpc) trait and the JSON
+//! Schemas of the wire types, so that the documentation follows the Rust definitions.
+//!
+//! Endpoint descriptions, parameters and return types are read from the doc comments and
+//! signatures in `client.rs`; data type sections are rendered from [all_schemas], which carry the
+//! doc comments of `fastset_types.rs`. Generic types, which have no schema of their own, are
+//! described by hand in `rpc_docs/generic_types.md`, appended to the generated sections.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    Attribute, Expr, GenericArgument, Ident, Item, LitStr, Meta, PathArguments, ReturnType, Token,
+    TraitItem, Type,
+};
+
+use crate::schema::{all_schemas, definitions};
+
+const CLIENT_SOURCE: &str = include_str!("client.rs");
+const GENERIC_TYPES: &str = include_str!("rpc_docs/generic_types.md");
+const RPC_TRAIT: &str = "ProxyRpc";
+const NAMESPACE: &str = "proxy";
+
+/// A method or subscription of the proxy, as declared in the [ProxyRpc](crate::client::ProxyRpc)
+/// trait.
+pub struct Endpoint {
+    /// Full method name, including the namespace
+    pub name: String,
+    /// The doc comment, with one line per line of comment
+    pub docs: String,
+    pub params: Vec<(String, Type)>,
+    pub kind: EndpointKind,
+}
+
+pub enum EndpointKind {
+    Method {
+        returns: Type,
+    },
+    Subscription {
+        /// Method name of the notifications, including the namespace
+        notification: String,
+        unsubscribe: String,
+        item: Type,
+    },
+}
+
+/// Read the endpoints of the proxy from `client.rs`, in declaration order.
+pub fn endpoints() -> syn::Result<Vec<Endpoint>> {
+    let file = syn::parse_file(CLIENT_SOURCE)?;
+    let rpc_trait = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Trait(item) if item.ident == RPC_TRAIT => Some(item),
+            _ => None,
+        })
+        .expect("client.rs declares the RPC trait");
+    let mut endpoints = Vec::new();
+    for item in &rpc_trait.items {
+        let TraitItem::Fn(method) = item else {
+            continue;
+        };
+        let Some(attr) = method
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("method") || attr.path().is_ident("subscription"))
+        else {
+            continue;
+        };
+        let args = attr.parse_args::<RpcAttributeArgs>()?;
+        let mut params = Vec::new();
+        for input in method.sig.inputs.iter() {
+            if let syn::FnArg::Typed(input) = input {
+                let syn::Pat::Ident(pat) = &*input.pat else {
+                    return Err(syn::Error::new_spanned(&input.pat, "unsupported parameter"));
+                };
+                params.push((pat.ident.to_string(), (*input.ty).clone()));
+            }
+        }
+        let kind = if attr.path().is_ident("method") {
+            let ReturnType::Type(_, output) = &method.sig.output else {
+                return Err(syn::Error::new_spanned(&method.sig, "missing return type"));
+            };
+            EndpointKind::Method {
+                returns: first_type_argument(output)
+                    .ok_or_else(|| syn::Error::new_spanned(output, "expected RpcResult<T>"))?
+                    .clone(),
+            }
+        } else {
+            EndpointKind::Subscription {
+                notification: namespaced(args.get_str(attr, "notification")?),
+                unsubscribe: namespaced(args.get_str(attr, "unsubscribe")?),
+                item: args.get_type(attr, "item")?,
+            }
+        };
+        endpoints.push(Endpoint {
+            name: namespaced(args.get_str(attr, "name")?),
+            docs: doc_comment(&method.attrs),
+            params,
+            kind,
+        });
+    }
+    Ok(endpoints)
+}
+
+fn namespaced(name: &str) -> String {
+    format!("{NAMESPACE}_{name}")
+}
+
+/// The `T` in `RpcResult<T>`.
+fn first_type_argument(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+fn doc_comment(attrs: &[Attribute]) -> String {
+    let lines: Vec<_> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(line),
+                    ..
+                }) => Some(line.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_string())
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// The arguments of a `#[method(...)]` or `#[subscription(...)]` attribute, such as
+/// `name = "subscribeAccountInfo" => "accountInfo"` or `item = AccountInfoResponse`.
+struct RpcAttributeArgs(BTreeMap<String, RpcAttributeValue>);
+
+enum RpcAttributeValue {
+    Str(String),
+    Type(Type),
+}
+
+impl Parse for RpcAttributeArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = BTreeMap::new();
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if input.peek(LitStr) {
+                args.insert(
+                    key.to_string(),
+                    RpcAttributeValue::Str(input.parse::<LitStr>()?.value()),
+                );
+                if input.peek(Token![=>]) {
+                    input.parse::<Token![=>]>()?;
+                    args.insert(
+                        "notification".to_string(),
+                        RpcAttributeValue::Str(input.parse::<LitStr>()?.value()),
+                    );
+                }
+            } else {
+                args.insert(key.to_string(), RpcAttributeValue::Type(input.parse()?));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self(args))
+    }
+}
+
+impl RpcAttributeArgs {
+    fn get_str(&self, attr: &Attribute, key: &str) -> syn::Result<&str> {
+        match self.0.get(key) {
+            Some(RpcAttributeValue::Str(value)) => Ok(value),
+            _ => Err(syn::Error::new_spanned(
+                attr,
+                format!("missing string argument `{key}`"),
+            )),
+        }
+    }
+
+    fn get_type(&self, attr: &Attribute, key: &str) -> syn::Result<Type> {
+        match self.0.get(key) {
+            Some(RpcAttributeValue::Type(value)) => Ok(value.clone()),
+            _ => Err(syn::Error::new_spanned(
+                attr,
+                format!("missing type argument `{key}`"),
+            )),
+        }
+    }
+}
+
+/// Render the complete `rpc.md`.
+pub fn render() -> syn::Result<String> {
+    let endpoints = endpoints()?;
+    let schema_names: BTreeSet<_> = all_schemas().into_iter().map(|(name, _)| name).collect();
+    let types = definitions();
+    let names: BTreeSet<&str> = types.keys().map(String::as_str).collect();
+
+    let mut out = String::from("# Proxy JSON-RPC API Documentation\n\n");
+    out.push_str(
+        "<!-- Generated by `cargo run --features docs --bin fastset-rpc-docs` from the doc comments in \
+         `rust-examples/src/client.rs`, `rust-examples/src/fastset_types.rs` and \
+         `rust-examples/src/rpc_docs/generic_types.md`. \
+         Do not edit by hand. -->\n\n",
+    );
+    out.push_str(
+        "The same API is described by the [OpenRPC document](openrpc.json), for use with OpenRPC \
+         tooling.\n\n",
+    );
+    out.push_str("## List of Endpoints\n\n");
+    for endpoint in &endpoints {
+        out.push_str(&format!(
+            "- [`{}`](#{})\n",
+            endpoint.name,
+            endpoint.name.to_lowercase()
+        ));
+    }
+    out.push_str("\n---\n");
+
+    let mut roots = Vec::new();
+    for endpoint in &endpoints {
+        out.push_str(&format!("\n## `{}`\n\n", endpoint.name));
+        if !endpoint.docs.is_empty() {
+            out.push_str(&link_types(&endpoint.docs, &names));
+            out.push_str("\n\n");
+        }
+        out.push_str("**Parameters**:\n\n");
+        if endpoint.params.is_empty() {
+            out.push_str("None\n\n");
+        } else {
+            out.push_str("| Name | Type |\n|------|------|\n");
+            for (name, ty) in &endpoint.params {
+                out.push_str(&format!("| `{name}` | {} |\n", rust_type(ty, &names)?));
+                collect_type_names(ty, &names, &mut roots);
+            }
+            out.push('\n');
+        }
+        match &endpoint.kind {
+            EndpointKind::Method { returns } => {
+                out.push_str(&format!(
+                    "**Returns**:\n\n{}\n",
+                    rust_type(returns, &names)?
+                ));
+                collect_type_names(returns, &names, &mut roots);
+            }
+            EndpointKind::Subscription {
+                notification,
+                unsubscribe,
+                item,
+            } => {
+                out.push_str(&format!(
+                    "**Notifications** (`{notification}`):\n\n{}\n\n**Unsubscribe**: `{unsubscribe}`\n",
+                    rust_type(item, &names)?
+                ));
+                collect_type_names(item, &names, &mut roots);
+            }
+        }
+        out.push_str("\n---\n");
+    }
+
+    out.push_str("\n## Data Types\n");
+    let mut order = Vec::new();
+    for name in roots
+        .iter()
+        .map(String::as_str)
+        .chain(schema_names.iter().copied())
+    {
+        visit(name, &types, &mut order);
+    }
+    for name in &order {
+        out.push_str("\n---\n\n");
+        out.push_str(&type_section(
+            name,
+            &types[name],
+            schema_names.contains(name.as_str()),
+            &names,
+        ));
+    }
+    out.push_str("\n---\n\n");
+    out.push_str(GENERIC_TYPES);
+    Ok(out)
+}
+
+/// Add `name` and, depth first, the types it refers to.
+fn visit(name: &str, types: &BTreeMap<String, Value>, order: &mut Vec<String>) {
+    if order.iter().any(|visited| visited == name) {
+        return;
+    }
+    order.push(name.to_string());
+    let mut refs = Vec::new();
+    collect_refs(&types[name], &mut refs);
+    for reference in refs {
+        visit(&reference, types, order);
+    }
+}
+
+fn collect_refs(schema: &Value, refs: &mut Vec<String>) {
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => {
+                        refs.push(reference.trim_start_matches("#/$defs/").to_string())
+                    }
+                    ("$defs", _) => {}
+                    _ => collect_refs(value, refs),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_refs(value, refs)),
+        _ => {}
+    }
+}
+
+fn collect_type_names(ty: &Type, names: &BTreeSet<&str>, found: &mut Vec<String>) {
+    if let Type::Path(path) = ty
+        && let Some(segment) = path.path.segments.last()
+    {
+        let ident = segment.ident.to_string();
+        if names.contains(ident.as_str()) {
+            found.push(ident);
+        }
+        if let PathArguments::AngleBracketed(args) = &segment.arguments {
+            for arg in &args.args {
+                if let GenericArgument::Type(ty) = arg {
+                    collect_type_names(ty, names, found);
+                }
+            }
+        }
+    }
+}
+
+fn type_link(name: &str) -> String {
+    format!("[`{name}`](#{})", name.to_lowercase())
+}
+
+/// A Rust parameter or return type, linking the documented types.
+fn rust_type(ty: &Type, names: &BTreeSet<&str>) -> syn::Result<String> {
+    match ty {
+        Type::Path(path) => {
+            let segment = path.path.segments.last().expect("non-empty type path");
+            let ident = segment.ident.to_string();
+            let base = if names.contains(ident.as_str()) {
+                type_link(&ident)
+            } else {
+                ident
+            };
+            let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return Ok(base);
+            };
+            let args = args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(rust_type(ty, names)),
+                    _ => None,
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            Ok(format!("{base}< {} >", args.join(", ")))
+        }
+        Type::Tuple(tuple) if tuple.elems.is_empty() => Ok("()".to_string()),
+        _ => Err(syn::Error::new_spanned(ty, "unsupported type in RPC trait")),
+    }
+}
+
+/// A JSON Schema as a type expression, such as `Array < uint8 ; length=32 >`.
+fn schema_type(schema: &Value) -> String {
+    // Types without a documented encoding are modelled as empty enums, which match nothing.
+    if schema
+        .get("not")
+        .is_some_and(|not| not == &Value::Object(Default::default()))
+    {
+        return "undocumented".to_string();
+    }
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return type_link(reference.trim_start_matches("#/$defs/"));
+    }
+    if let Some(variants) = schema.get("anyOf").and_then(Value::as_array) {
+        let non_null: Vec<_> = variants.iter().filter(|s| !is_null(s)).collect();
+        return match non_null.as_slice() {
+            [inner] if variants.len() == 2 => format!("Option < {} >", schema_type(inner)),
+            _ => non_null
+                .iter()
+                .map(|s| schema_type(s))
+                .collect::<Vec<_>>()
+                .join(" or "),
+        };
+    }
+    let (ty, nullable) = match schema.get("type") {
+        Some(Value::String(ty)) => (ty.as_str(), false),
+        Some(Value::Array(types)) => (
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|ty| *ty != "null")
+                .unwrap_or("null"),
+            types.iter().any(|ty| ty == "null"),
+        ),
+        _ => ("any", false),
+    };
+    let rendered = match ty {
+        "array" => {
+            let min = schema.get("minItems").and_then(Value::as_u64);
+            let max = schema.get("maxItems").and_then(Value::as_u64);
+            if let Some(items) = schema.get("prefixItems").and_then(Value::as_array) {
+                let items: Vec<_> = items.iter().map(schema_type).collect();
+                format!("( {} )", items.join(", "))
+            } else if max == Some(0) {
+                "empty array".to_string()
+            } else {
+                let items = schema.get("items").map_or("any".to_string(), schema_type);
+                match (min, max) {
+                    (Some(min), Some(max)) if min == max => {
+                        format!("Array < {items} ; length={min} >")
+                    }
+                    _ => format!("Array < {items} >"),
+                }
+            }
+        }
+        "integer" => schema
+            .get("format")
+            .and_then(Value::as_str)
+            .unwrap_or("integer")
+            .to_string(),
+        ty => ty.to_string(),
+    };
+    if nullable {
+        format!("Option < {rendered} >")
+    } else {
+        rendered
+    }
+}
+
+fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+fn type_section(name: &str, schema: &Value, has_file: bool, names: &BTreeSet<&str>) -> String {
+    let mut out = format!("### {name}\n\n");
+    if let Some(description) = schema.get("description").and_then(Value::as_str) {
+        out.push_str(&link_types(description, names));
+        out.push_str("\n\n");
+    }
+    if has_file {
+        out.push_str(&format!("**JSON Schema**: [{name}]({name}.json)\n\n"));
+    }
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        let required: Vec<_> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        out.push_str("#### Fields:\n\n| Field | Type | Notes |\n|-------|------|-------|\n");
+        for (field, field_schema) in properties {
+            let optional = if required.contains(&field.as_str()) {
+                ""
+            } else {
+                " (optional)"
+            };
+            out.push_str(&format!(
+                "| `{field}` | {}{optional} |{}|\n",
+                schema_type(field_schema),
+                table_notes(field_schema, names)
+            ));
+        }
+    } else if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+        out.push_str("#### Variants:\n\n| Name | Type | Notes |\n|------|------|-------|\n");
+        for variant in variants {
+            let (variant_name, ty) = match (
+                variant.get("properties").and_then(Value::as_object),
+                variant
+                    .get("const")
+                    .or_else(|| variant.get("enum").and_then(|e| e.get(0))),
+            ) {
+                (Some(properties), _) if properties.len() == 1 => {
+                    let (name, schema) = properties.iter().next().expect("one property");
+                    (name.clone(), schema_type(schema))
+                }
+                (_, Some(Value::String(name))) => (name.clone(), String::new()),
+                _ => (String::new(), schema_type(variant)),
+            };
+            out.push_str(&format!(
+                "| `{variant_name}` | {ty} |{}|\n",
+                table_notes(variant, names)
+            ));
+        }
+    } else {
+        let ty = schema_type(schema);
+        if ty.contains("](#") {
+            out.push_str(&format!("{ty}\n"));
+        } else {
+            out.push_str(&format!("`{ty}`\n"));
+        }
+    }
+    out
+}
+
+/// A description on a single line, padded to fit in a table cell.
+fn table_notes(schema: &Value, names: &BTreeSet<&str>) -> String {
+    let description = schema
+        .get("description")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if description.is_empty() {
+        return " ".to_string();
+    }
+    let line = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!(" {} ", link_types(&line, names).replace('|', "\\|"))
+}
+
+/// Turn intra-doc links such as `[Amount]` into links to the type sections.
+fn link_types(text: &str, names: &BTreeSet<&str>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        let name = &rest[1..end];
+        let followed_by_link = rest[end + 1..].starts_with('(');
+        if names.contains(name) && !followed_by_link {
+            out.push_str(&format!("[{name}](#{})", name.to_lowercase()));
+            rest = &rest[end + 1..];
+        } else {
+            out.push('[');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
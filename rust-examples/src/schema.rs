@@ -0,0 +1,94 @@
+//! JSON Schemas for the types exchanged with the proxy, as they appear on the wire.
+//!
+//! Each schema is self-contained, with the types it refers to under `$defs`, so that a single file
+//! is enough to validate a request or response body.
+
+use std::collections::BTreeMap;
+
+use schemars::{JsonSchema, Schema, schema_for};
+use serde_json::Value;
+
+use crate::client::ProxySubmitTransactionResult;
+use crate::fastset_types::*;
+
+/// The schema of every wire type, keyed by the name used in the RPC documentation.
+pub fn all_schemas() -> Vec<(&'static str, Schema)> {
+    vec![
+        named::<AccountInfoResponse>("AccountInfoResponse"),
+        named::<AddressChange>("AddressChange"),
+        named::<Amount>("Amount"),
+        named::<Balance>("Balance"),
+        named::<ClaimData>("ClaimData"),
+        named::<ClaimType>("ClaimType"),
+        named::<CommitteeChange>("CommitteeChange"),
+        named::<CrossSignResponse>("CrossSignResponse"),
+        named::<ExternalClaim>("ExternalClaim"),
+        named::<ExternalClaimBody>("ExternalClaimBody"),
+        named::<FastSetAddress>("FastSetAddress"),
+        named::<Mint>("Mint"),
+        named::<MintOperation>("MintOperation"),
+        named::<MultiSig>("MultiSig"),
+        named::<MultiSigConfig>("MultiSigConfig"),
+        named::<Nonce>("Nonce"),
+        named::<NonceRange>("NonceRange"),
+        named::<Operation>("Operation"),
+        named::<OperationBundle>("OperationBundle"),
+        named::<PageRequest>("PageRequest"),
+        named::<Pagination>("Pagination"),
+        named::<ProxySubmitTransactionResult>("ProxySubmitTransactionResult"),
+        named::<PublicKeyBytes>("PublicKeyBytes"),
+        named::<Quorum>("Quorum"),
+        named::<Signature>("Signature"),
+        named::<SignatureOrMultiSig>("SignatureOrMultiSig"),
+        named::<State>("State"),
+        named::<StateInitialization>("StateInitialization"),
+        named::<StateKey>("StateKey"),
+        named::<StateReset>("StateReset"),
+        named::<StateUpdate>("StateUpdate"),
+        named::<TokenCreation>("TokenCreation"),
+        named::<TokenId>("TokenId"),
+        named::<TokenInfoResponse>("TokenInfoResponse"),
+        named::<TokenManagement>("TokenManagement"),
+        named::<TokenMetadata>("TokenMetadata"),
+        named::<TokenTransfer>("TokenTransfer"),
+        named::<TokenTransferOperation>("TokenTransferOperation"),
+        named::<Transaction>("Transaction"),
+        named::<TransactionCertificate>("TransactionCertificate"),
+        named::<TransactionEnvelope>("TransactionEnvelope"),
+        named::<TransactionInfo>("TransactionInfo"),
+        named::<UserData>("UserData"),
+        named::<ValidatedTransaction>("ValidatedTransaction"),
+        named::<ValidatorConfig>("ValidatorConfig"),
+        named::<VerifierSig>("VerifierSig"),
+    ]
+}
+
+/// The schema of `T`, titled `name` so that aliases such as [FastSetAddress] keep their own name.
+fn named<T: JsonSchema>(name: &'static str) -> (&'static str, Schema) {
+    let mut schema = schema_for!(T);
+    schema.insert("title".into(), name.into());
+    (name, schema)
+}
+
+/// Every wire type, alias and type they refer to by name, with references left pointing to
+/// `#/$defs/`.
+pub fn definitions() -> BTreeMap<String, Value> {
+    let mut types = BTreeMap::new();
+    for (name, schema) in all_schemas() {
+        let mut schema = schema.to_value();
+        let object = schema.as_object_mut().expect("schemas are objects");
+        object.remove("$schema");
+        if let Some(Value::Object(defs)) = object.remove("$defs") {
+            for (def_name, def) in defs {
+                types.entry(def_name).or_insert(def);
+            }
+        }
+        types.insert(name.to_string(), schema);
+    }
+    types
+}
+
+/// Render `schema` the way the schema files are written.
+pub fn to_file_contents(schema: &Schema) -> String {
+    serde_json::to_string_pretty(schema).expect("schema serialization") + "\n"
+}
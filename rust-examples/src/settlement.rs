@@ -0,0 +1,354 @@
+//! Calldata for the EVM settlement contract of a bridge, built offline from a burn certificate and
+//! the proxy's [CrossSignResponse] for it.
+//!
+//! Bridging a token out of FastSet takes three steps: burn it with a transfer to the burn address,
+//! have the proxy sign the certificate with `proxy_evmSignCertificate`, and call the settlement
+//! contract with the signed payload. This module covers the last step, up to an unsigned EIP-1559
+//! transaction to be signed by the relayer's EVM wallet.
+//!
+//! The calldata carries the ABI transaction exactly as signed by the proxy. Before using it, it is
+//! checked against the certificate by decoding it in the layout assumed by [crate::abi], which is
+//! unverified, so a genuine response may be refused with [SettlementError::Abi] until that layout is
+//! confirmed. The fields that layout leaves out, such as the user data, are not checked at all and
+//! are listed in [SettlementCalldata::check].
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::abi::{AbiCheck, AbiError, AbiValue, encode_values};
+use crate::evm::{CrossSignError, EvmAddress, keccak256};
+use crate::fastset_types::{
+    ClaimType, CrossSignResponse, FastSetAddress, PublicKeyBytes, TransactionCertificate,
+};
+
+/// The address tokens are burned to when bridged out of FastSet
+pub const BURN_ADDRESS: FastSetAddress = PublicKeyBytes([0; 32]);
+
+#[derive(Debug, Error)]
+pub enum SettlementError {
+    #[error("Malformed function signature {0:?}, expected e.g. \"settle(bytes,bytes)\".")]
+    MalformedSignature(String),
+    #[error("The function takes {expected} arguments, but {found} are given.")]
+    ArgumentCount { expected: usize, found: usize },
+    #[error("Argument {index} is {arg:?}, of type {expected}, but the function takes {found}.")]
+    ArgumentType {
+        index: usize,
+        arg: SettlementArg,
+        expected: &'static str,
+        found: String,
+    },
+    #[error("The certificate is a transfer to {0}, not a burn.")]
+    NotBurn(FastSetAddress),
+    #[error(transparent)]
+    CrossSign(#[from] CrossSignError),
+    #[error(transparent)]
+    Abi(#[from] AbiError),
+}
+
+/// A value passed to the settlement function, taken from the certificate or the proxy's response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementArg {
+    /// The ABI-encoded transaction signed by the proxy, as `bytes`
+    Transaction,
+    /// The proxy's 65-byte `r || s || v` signature, as `bytes`
+    Signature,
+    /// `bytes32`
+    Sender,
+    /// `bytes32`
+    Recipient,
+    /// `uint64`
+    Nonce,
+    /// `uint128`
+    TimestampNanos,
+    /// `bytes32`
+    TokenId,
+    /// `uint256`
+    Amount,
+    /// The user data of the transfer, often the EVM recipient, as `bytes32`; zero if there is none
+    UserData,
+}
+
+impl SettlementArg {
+    /// The Solidity type of the argument.
+    pub fn abi_type(self) -> &'static str {
+        match self {
+            Self::Transaction | Self::Signature => "bytes",
+            Self::Sender | Self::Recipient | Self::TokenId | Self::UserData => "bytes32",
+            Self::Nonce => "uint64",
+            Self::TimestampNanos => "uint128",
+            Self::Amount => "uint256",
+        }
+    }
+}
+
+/// Calldata settling a certificate, with what its check against the proxy's response left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementCalldata {
+    pub calldata: Vec<u8>,
+    /// Fields of the certificate that the signed ABI transaction is not known to cover
+    pub check: AbiCheck,
+}
+
+/// The settlement function to call, such as `settle(bytes,bytes)` with the transaction and the
+/// signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementFunction {
+    signature: String,
+    args: Vec<SettlementArg>,
+}
+
+impl Default for SettlementFunction {
+    fn default() -> Self {
+        Self {
+            signature: "settle(bytes,bytes)".to_string(),
+            args: vec![SettlementArg::Transaction, SettlementArg::Signature],
+        }
+    }
+}
+
+impl SettlementFunction {
+    /// A function with canonical `signature`, without spaces or parameter names, called with
+    /// `args`. The types of the parameters must be those of the arguments.
+    pub fn new(signature: &str, args: Vec<SettlementArg>) -> Result<Self, SettlementError> {
+        let malformed = || SettlementError::MalformedSignature(signature.to_string());
+        let (name, params) = signature
+            .strip_suffix(')')
+            .and_then(|rest| rest.split_once('('))
+            .ok_or_else(malformed)?;
+        let is_identifier = |s: &str| {
+            s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        if !is_identifier(name) {
+            return Err(malformed());
+        }
+        let params: Vec<_> = match params {
+            "" => Vec::new(),
+            params => params.split(',').collect(),
+        };
+        if params.len() != args.len() {
+            return Err(SettlementError::ArgumentCount {
+                expected: params.len(),
+                found: args.len(),
+            });
+        }
+        for (index, (param, arg)) in params.iter().zip(&args).enumerate() {
+            if *param != arg.abi_type() {
+                return Err(SettlementError::ArgumentType {
+                    index,
+                    arg: *arg,
+                    expected: arg.abi_type(),
+                    found: param.to_string(),
+                });
+            }
+        }
+        Ok(Self {
+            signature: signature.to_string(),
+            args,
+        })
+    }
+
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature)
+    }
+
+    /// The calldata settling `certificate`, which must be a transfer to [BURN_ADDRESS], after
+    /// checking that `response` is its payload, signed by `trusted`, in the fields of the assumed
+    /// ABI layout.
+    pub fn calldata(
+        &self,
+        certificate: &TransactionCertificate,
+        response: &CrossSignResponse,
+        trusted: &EvmAddress,
+    ) -> Result<SettlementCalldata, SettlementError> {
+        let transaction = &certificate.envelope.transaction;
+        let ClaimType::TokenTransfer(transfer) = &transaction.claim else {
+            return Err(AbiError::UnsupportedClaim(transaction.claim.name()).into());
+        };
+        if transaction.recipient != BURN_ADDRESS {
+            return Err(SettlementError::NotBurn(transaction.recipient));
+        }
+        response.verify(trusted)?;
+        let check = response.check_certificate(certificate)?;
+        let mut values = Vec::new();
+        for arg in &self.args {
+            values.push(match arg {
+                SettlementArg::Transaction => AbiValue::Bytes(response.transaction.clone()),
+                SettlementArg::Signature => AbiValue::Bytes(response.signature_bytes()?),
+                SettlementArg::Sender => AbiValue::Word(transaction.sender.0),
+                SettlementArg::Recipient => AbiValue::Word(transaction.recipient.0),
+                SettlementArg::Nonce => AbiValue::uint(transaction.nonce.0.into()),
+                SettlementArg::TimestampNanos => AbiValue::uint(transaction.timestamp_nanos),
+                SettlementArg::TokenId => AbiValue::Word(transfer.token_id.0),
+                SettlementArg::Amount => AbiValue::Word(transfer.amount.to_be_bytes()),
+                SettlementArg::UserData => AbiValue::Word(transfer.user_data.0.unwrap_or([0; 32])),
+            });
+        }
+        let mut calldata = self.selector().to_vec();
+        calldata.extend(encode_values(&values));
+        Ok(SettlementCalldata { calldata, check })
+    }
+}
+
+impl FromStr for SettlementFunction {
+    type Err = SettlementError;
+
+    /// Parse a signature whose parameters are named after the [SettlementArg]s passed to them, such
+    /// as `settle(bytes transaction,bytes signature,bytes32 user_data)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || SettlementError::MalformedSignature(s.to_string());
+        let (name, params) = s
+            .strip_suffix(')')
+            .and_then(|rest| rest.split_once('('))
+            .ok_or_else(malformed)?;
+        let mut types = Vec::new();
+        let mut args = Vec::new();
+        for param in params.split(',').filter(|param| !param.trim().is_empty()) {
+            let (ty, arg) = param.trim().split_once(' ').ok_or_else(malformed)?;
+            let arg = serde_json::from_value(arg.trim().into()).map_err(|_| malformed())?;
+            types.push(ty);
+            args.push(arg);
+        }
+        Self::new(&format!("{}({})", name.trim(), types.join(",")), args)
+    }
+}
+
+/// The 4-byte selector of a canonical function signature.
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// The chain-specific fields of an EIP-1559 transaction, all chosen by the relayer so that no EVM
+/// node is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip1559Params {
+    pub chain_id: u64,
+    /// Nonce of the relayer's EVM account
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_gas: u128,
+    pub gas_limit: u64,
+}
+
+/// An unsigned EIP-1559 transaction, with an empty access list. It serializes to JSON like the
+/// transaction objects of `eth_signTransaction`, with `0x`-prefixed hex quantities.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip1559Transaction {
+    /// Always 2
+    #[serde(rename = "type", serialize_with = "quantity")]
+    pub tx_type: u8,
+    #[serde(serialize_with = "quantity")]
+    pub chain_id: u64,
+    #[serde(serialize_with = "quantity")]
+    pub nonce: u64,
+    #[serde(serialize_with = "quantity")]
+    pub max_priority_fee_per_gas: u128,
+    #[serde(serialize_with = "quantity")]
+    pub max_fee_per_gas: u128,
+    #[serde(rename = "gas", serialize_with = "quantity")]
+    pub gas_limit: u64,
+    pub to: EvmAddress,
+    #[serde(serialize_with = "quantity")]
+    pub value: u128,
+    #[serde(serialize_with = "data")]
+    pub data: Vec<u8>,
+}
+
+impl Eip1559Transaction {
+    /// A call of `contract` with `calldata` and no value.
+    pub fn call(contract: EvmAddress, calldata: Vec<u8>, params: Eip1559Params) -> Self {
+        Self {
+            tx_type: 2,
+            chain_id: params.chain_id,
+            nonce: params.nonce,
+            max_priority_fee_per_gas: params.max_priority_fee_per_gas,
+            max_fee_per_gas: params.max_fee_per_gas,
+            gas_limit: params.gas_limit,
+            to: contract,
+            value: 0,
+            data: calldata,
+        }
+    }
+
+    /// The bytes whose Keccak-256 hash the sender signs:
+    /// `0x02 || rlp([chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gas, to, value, data,
+    /// accessList])`.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let fields = Rlp::List(vec![
+            Rlp::uint(self.chain_id.into()),
+            Rlp::uint(self.nonce.into()),
+            Rlp::uint(self.max_priority_fee_per_gas),
+            Rlp::uint(self.max_fee_per_gas),
+            Rlp::uint(self.gas_limit.into()),
+            Rlp::Bytes(self.to.0.to_vec()),
+            Rlp::uint(self.value),
+            Rlp::Bytes(self.data.clone()),
+            Rlp::List(Vec::new()),
+        ]);
+        let mut payload = vec![self.tx_type];
+        payload.extend(fields.encode());
+        payload
+    }
+
+    pub fn signing_hash(&self) -> [u8; 32] {
+        keccak256(&self.signing_payload())
+    }
+}
+
+/// An RLP item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rlp {
+    Bytes(Vec<u8>),
+    List(Vec<Rlp>),
+}
+
+impl Rlp {
+    /// An integer, as its big-endian bytes without leading zeros.
+    pub fn uint(value: u128) -> Self {
+        let bytes = value.to_be_bytes();
+        let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(16);
+        Self::Bytes(bytes[start..].to_vec())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Bytes(bytes) if bytes.len() == 1 && bytes[0] < 0x80 => bytes.clone(),
+            Self::Bytes(bytes) => [rlp_header(0x80, bytes.len()), bytes.clone()].concat(),
+            Self::List(items) => {
+                let payload: Vec<u8> = items.iter().flat_map(Rlp::encode).collect();
+                [rlp_header(0xc0, payload.len()), payload].concat()
+            }
+        }
+    }
+}
+
+fn rlp_header(offset: u8, len: usize) -> Vec<u8> {
+    if len <= 55 {
+        return vec![offset + len as u8];
+    }
+    let len = len.to_be_bytes();
+    let start = len.iter().position(|byte| *byte != 0).expect("len > 55");
+    let mut header = vec![offset + 55 + (len.len() - start) as u8];
+    header.extend_from_slice(&len[start..]);
+    header
+}
+
+fn quantity<S: Serializer, T: Into<u128> + Copy>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{:#x}", (*value).into()))
+}
+
+fn data<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("0x{}", hex::encode(bytes)))
+}
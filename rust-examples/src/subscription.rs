@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream, StreamExt};
+use jsonrpsee::core::ClientError;
+use jsonrpsee::types::error::METHOD_NOT_FOUND_CODE;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::client::{ProxyRpcClient, ProxySubscriptionRpcClient};
+use crate::fastset_types::{
+    AccountInfoResponse, Balance, FastSetAddress, Nonce, NonceRange, TokenId,
+    TransactionCertificate, ValidatedTransaction,
+};
+
+/// Maximum number of certificates requested per poll when following an account's history.
+const CERTIFICATE_PAGE_SIZE: usize = 64;
+
+/// Whether `err` means that the transport or the proxy does not offer subscriptions, in which case
+/// the streams below fall back to polling.
+fn subscriptions_unsupported(err: &ClientError) -> bool {
+    match err {
+        ClientError::HttpNotImplemented => true,
+        ClientError::Call(err) => err.code() == METHOD_NOT_FOUND_CODE,
+        _ => false,
+    }
+}
+
+fn poll_interval(period: Duration) -> Interval {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Account updates
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Stream the state of `address`, first as it is now and then every time it changes.
+///
+/// Uses the hypothetical `proxy_subscribeAccountInfo` of [ProxySubscriptionRpcClient] when the
+/// client is connected over WebSocket and the proxy supports it. Otherwise `proxy_getAccountInfo`
+/// is polled every `period`, and a response is only yielded when it differs from the previous one.
+pub async fn account_updates<C>(
+    client: Arc<C>,
+    address: FastSetAddress,
+    token_balances_filter: Option<Vec<TokenId>>,
+    period: Duration,
+) -> Result<BoxStream<'static, Result<AccountInfoResponse, ClientError>>, ClientError>
+where
+    C: ProxyRpcClient + ProxySubscriptionRpcClient + Send + Sync + 'static,
+{
+    match client
+        .subscribe_account_info(address, token_balances_filter.clone())
+        .await
+    {
+        Ok(subscription) => Ok(subscription
+            .map(|item| item.map_err(ClientError::ParseError))
+            .boxed()),
+        Err(err) if subscriptions_unsupported(&err) => Ok(poll_account_updates(
+            client,
+            address,
+            token_balances_filter,
+            period,
+        )),
+        Err(err) => Err(err),
+    }
+}
+
+fn poll_account_updates<C>(
+    client: Arc<C>,
+    address: FastSetAddress,
+    token_balances_filter: Option<Vec<TokenId>>,
+    period: Duration,
+) -> BoxStream<'static, Result<AccountInfoResponse, ClientError>>
+where
+    C: ProxyRpcClient + Send + Sync + 'static,
+{
+    let state = (poll_interval(period), None::<AccountInfoResponse>);
+    stream::unfold(state, move |(mut interval, mut last)| {
+        let client = client.clone();
+        let token_balances_filter = token_balances_filter.clone();
+        async move {
+            loop {
+                interval.tick().await;
+                match client
+                    .get_account_info(address, token_balances_filter.clone(), None, None)
+                    .await
+                {
+                    Ok(info) if last.as_ref() == Some(&info) => continue,
+                    Ok(info) => {
+                        last = Some(info.clone());
+                        return Some((Ok(info), (interval, last)));
+                    }
+                    Err(err) => return Some((Err(err), (interval, last))),
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Settled certificates
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Stream the certificates of transactions sent by `address`, in nonce order.
+///
+/// If `start_nonce` is passed, certificates from that nonce onwards are replayed first; otherwise
+/// only transactions settled from now on are yielded. Uses the hypothetical
+/// `proxy_subscribeCertificates` of [ProxySubscriptionRpcClient] when available and otherwise
+/// polls `proxy_getAccountInfo` with `certificate_by_nonce` every `period`. Certificates that the
+/// queried validator has already pruned are skipped.
+pub async fn settled_certificates<C>(
+    client: Arc<C>,
+    address: FastSetAddress,
+    start_nonce: Option<Nonce>,
+    period: Duration,
+) -> Result<BoxStream<'static, Result<TransactionCertificate, ClientError>>, ClientError>
+where
+    C: ProxyRpcClient + ProxySubscriptionRpcClient + Send + Sync + 'static,
+{
+    match client.subscribe_certificates(address, start_nonce).await {
+        Ok(subscription) => Ok(subscription
+            .map(|item| item.map_err(ClientError::ParseError))
+            .boxed()),
+        Err(err) if subscriptions_unsupported(&err) => {
+            let start = match start_nonce {
+                Some(nonce) => nonce,
+                None => {
+                    client
+                        .get_account_info(address, None, None, None)
+                        .await?
+                        .next_nonce
+                }
+            };
+            Ok(poll_settled_certificates(client, address, start, period))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+struct CertificatePoll {
+    interval: Interval,
+    /// Nonce of the next certificate to yield
+    cursor: Nonce,
+    /// Certificates fetched but not yielded yet
+    buffered: VecDeque<TransactionCertificate>,
+}
+
+fn poll_settled_certificates<C>(
+    client: Arc<C>,
+    address: FastSetAddress,
+    start: Nonce,
+    period: Duration,
+) -> BoxStream<'static, Result<TransactionCertificate, ClientError>>
+where
+    C: ProxyRpcClient + Send + Sync + 'static,
+{
+    let state = CertificatePoll {
+        interval: poll_interval(period),
+        cursor: start,
+        buffered: VecDeque::new(),
+    };
+    stream::unfold(state, move |mut state| {
+        let client = client.clone();
+        async move {
+            loop {
+                if let Some(cert) = state.buffered.pop_front() {
+                    return Some((Ok(cert), state));
+                }
+                state.interval.tick().await;
+                let range = NonceRange {
+                    start: state.cursor,
+                    limit: CERTIFICATE_PAGE_SIZE,
+                };
+                let info = match client
+                    .get_account_info(address, None, None, Some(range))
+                    .await
+                {
+                    Ok(info) => info,
+                    Err(err) => return Some((Err(err), state)),
+                };
+                let mut certificates: Vec<_> = info
+                    .requested_certificates
+                    .into_iter()
+                    .flatten()
+                    .filter(|cert| cert.envelope.transaction.nonce >= state.cursor)
+                    .collect();
+                certificates.sort_by_key(|cert| cert.envelope.transaction.nonce);
+                match certificates.last() {
+                    Some(last) => state.cursor = Nonce(last.envelope.transaction.nonce.0 + 1),
+                    // The requested range is settled but the validator no longer has it.
+                    None if info.next_nonce > state.cursor => {
+                        let page_end = state.cursor.0 + CERTIFICATE_PAGE_SIZE as u64;
+                        state.cursor = Nonce(page_end.min(info.next_nonce.0));
+                    }
+                    None => {}
+                }
+                state.buffered.extend(certificates);
+            }
+        }
+    })
+    .boxed()
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Account events
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A change of a watched account between two polls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountEvent {
+    pub address: FastSetAddress,
+    pub change: AccountChange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountChange {
+    BalanceChanged {
+        before: Balance,
+        after: Balance,
+    },
+    /// A token balance changed. Tokens missing from a response have a zero balance.
+    TokenBalanceChanged {
+        token_id: TokenId,
+        before: Balance,
+        after: Balance,
+    },
+    /// The account sent transactions, settling nonces `before` to `after - 1`.
+    NonceAdvanced {
+        before: Nonce,
+        after: Nonce,
+    },
+    /// A transaction was validated but not yet confirmed, when `after` is set, or a pending
+    /// transaction was confirmed or dropped, when `after` is `None`.
+    PendingConfirmationChanged {
+        before: Option<Box<ValidatedTransaction>>,
+        after: Option<Box<ValidatedTransaction>>,
+    },
+}
+
+/// The changes from `before` to `after`, two states of the same account: native balance first,
+/// then token balances by token ID, nonce and pending confirmation.
+pub fn account_changes(
+    before: &AccountInfoResponse,
+    after: &AccountInfoResponse,
+) -> Vec<AccountChange> {
+    let mut changes = Vec::new();
+    if before.balance != after.balance {
+        changes.push(AccountChange::BalanceChanged {
+            before: before.balance,
+            after: after.balance,
+        });
+    }
+    let before_tokens: BTreeMap<_, _> = before.token_balance.iter().copied().collect();
+    let after_tokens: BTreeMap<_, _> = after.token_balance.iter().copied().collect();
+    let token_ids: BTreeSet<_> = before_tokens.keys().chain(after_tokens.keys()).collect();
+    for token_id in token_ids {
+        let balance =
+            |tokens: &BTreeMap<_, Balance>| tokens.get(token_id).copied().unwrap_or_default();
+        let (before, after) = (balance(&before_tokens), balance(&after_tokens));
+        if before != after {
+            changes.push(AccountChange::TokenBalanceChanged {
+                token_id: *token_id,
+                before,
+                after,
+            });
+        }
+    }
+    if before.next_nonce != after.next_nonce {
+        changes.push(AccountChange::NonceAdvanced {
+            before: before.next_nonce,
+            after: after.next_nonce,
+        });
+    }
+    if before.pending_confirmation != after.pending_confirmation {
+        changes.push(AccountChange::PendingConfirmationChanged {
+            before: before.pending_confirmation.clone().map(Box::new),
+            after: after.pending_confirmation.clone().map(Box::new),
+        });
+    }
+    changes
+}
+
+/// `info` with the balances of `previous`, a state at the same nonce, where they are higher.
+///
+/// Until the account sends a transaction, its balances only grow with the transfers it receives,
+/// so a lower balance at the same nonce comes from a validator that has not seen some of them yet.
+fn without_regressions(
+    previous: &AccountInfoResponse,
+    mut info: AccountInfoResponse,
+) -> AccountInfoResponse {
+    info.balance = info.balance.max(previous.balance);
+    let mut tokens: BTreeMap<_, _> = info.token_balance.iter().copied().collect();
+    for (token_id, balance) in &previous.token_balance {
+        let seen = tokens.entry(*token_id).or_default();
+        *seen = (*seen).max(*balance);
+    }
+    info.token_balance = tokens.into_iter().collect();
+    info
+}
+
+struct AccountPoll {
+    interval: Interval,
+    last: HashMap<FastSetAddress, AccountInfoResponse>,
+    buffered: VecDeque<Result<AccountEvent, ClientError>>,
+}
+
+/// Stream the changes of `addresses`, polling `proxy_getAccountInfo` for each of them every
+/// `period`.
+///
+/// The accounts are read once before this returns, and each event is relative to the previous
+/// state seen, so a change is reported once however many polls see it. Responses with a lower
+/// `next_nonce` than already seen, from a validator lagging behind, are ignored, and so are
+/// balances lower than already seen at the same `next_nonce`, which a validator that missed some
+/// incoming transfers reports. Pending confirmations are reported as each response has them.
+pub async fn account_events<C>(
+    client: Arc<C>,
+    addresses: Vec<FastSetAddress>,
+    token_balances_filter: Option<Vec<TokenId>>,
+    period: Duration,
+) -> Result<BoxStream<'static, Result<AccountEvent, ClientError>>, ClientError>
+where
+    C: ProxyRpcClient + Send + Sync + 'static,
+{
+    let mut last = HashMap::new();
+    for address in &addresses {
+        let info = client
+            .get_account_info(*address, token_balances_filter.clone(), None, None)
+            .await?;
+        last.insert(*address, info);
+    }
+    let mut interval = poll_interval(period);
+    // The first tick completes at once, and the accounts were just read.
+    interval.tick().await;
+    let state = AccountPoll {
+        interval,
+        last,
+        buffered: VecDeque::new(),
+    };
+    Ok(stream::unfold(state, move |mut state| {
+        let client = client.clone();
+        let addresses = addresses.clone();
+        let token_balances_filter = token_balances_filter.clone();
+        async move {
+            loop {
+                if let Some(event) = state.buffered.pop_front() {
+                    return Some((event, state));
+                }
+                state.interval.tick().await;
+                for address in addresses.iter().copied() {
+                    let info = match client
+                        .get_account_info(address, token_balances_filter.clone(), None, None)
+                        .await
+                    {
+                        Ok(info) => info,
+                        Err(err) => {
+                            state.buffered.push_back(Err(err));
+                            continue;
+                        }
+                    };
+                    let previous = &state.last[&address];
+                    if info.next_nonce < previous.next_nonce {
+                        continue;
+                    }
+                    let info = if info.next_nonce == previous.next_nonce {
+                        without_regressions(previous, info)
+                    } else {
+                        info
+                    };
+                    let events = account_changes(previous, &info)
+                        .into_iter()
+                        .map(|change| Ok(AccountEvent { address, change }));
+                    state.buffered.extend(events);
+                    state.last.insert(address, info);
+                }
+            }
+        }
+    })
+    .boxed())
+}
@@ -0,0 +1,250 @@
+//! Signing and encoding test vectors shared with the TypeScript example, written to
+//! `test-vectors/signing.json` at the root of the repository.
+//!
+//! Every input is given in its JSON wire form. Signing cases also give the exact message signed,
+//! that is the `Transaction::` seed followed by the BCS bytes of the transaction, and the Ed25519
+//! signatures of fixed keys over it. Encoding cases give the BCS bytes of single values whose JSON
+//! and BCS forms differ, such as [Amount] and [Balance].
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::fastset_types::*;
+
+/// Version of the test vector file format
+pub const VECTORS_VERSION: u32 = 1;
+
+/// Secret keys of the fixed signers, from tests 1 to 3 of RFC 8032.
+const SECRET_KEYS: [&str; 3] = [
+    "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+    "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+    "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVectors {
+    pub version: u32,
+    pub keys: Vec<TestKey>,
+    pub signing: Vec<SigningCase>,
+    pub encoding: Vec<EncodingCase>,
+}
+
+/// A fixed signing key, hex-encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestKey {
+    pub secret_key: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigningCase {
+    pub name: String,
+    pub description: String,
+    pub transaction: Transaction,
+    /// Hex-encoded `Transaction::` seed followed by the BCS bytes of the transaction
+    pub signing_bytes: String,
+    /// Signatures of the signing bytes, by hex-encoded public key of one of the [TestKey]s
+    pub signatures: Vec<(String, String)>,
+    /// The envelope as submitted to `proxy_submitTransaction`
+    pub envelope: TransactionEnvelope,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncodingCase {
+    pub name: String,
+    /// Rust name of the type, as in the schemas
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub json: Value,
+    /// Hex-encoded BCS bytes
+    pub bcs: String,
+}
+
+pub fn test_keys() -> Vec<KeyPair> {
+    SECRET_KEYS
+        .iter()
+        .map(|secret| {
+            let mut bytes = [0; 32];
+            hex::decode_to_slice(secret, &mut bytes).expect("valid test key");
+            KeyPair::from_secret_bytes(&bytes)
+        })
+        .collect()
+}
+
+/// Generate all test vectors. The output is deterministic, since Ed25519 signatures are.
+pub fn generate() -> TestVectors {
+    let keys = test_keys();
+    let recipient = keys[1].public();
+    let transfer = |token_id, amount, user_data| {
+        ClaimType::TokenTransfer(TokenTransfer {
+            token_id,
+            amount,
+            user_data: UserData(user_data),
+        })
+    };
+    let transaction = |nonce, claim, archival| Transaction {
+        sender: keys[0].public(),
+        recipient,
+        nonce: Nonce(nonce),
+        timestamp_nanos: 1_700_000_000_123_456_789,
+        claim,
+        archival,
+    };
+    let single = |name: &str, description: &str, transaction: Transaction| {
+        signing_case(name, description, transaction, &keys[..1], None)
+    };
+    let max_amount: Amount = "f".repeat(64).parse().expect("valid amount");
+
+    let mut multisig_transaction = transaction(
+        3,
+        transfer(TokenId::native(), Amount::from(1_000), None),
+        false,
+    );
+    multisig_transaction.sender = PublicKeyBytes([0x4d; 32]);
+    let multisig_config = MultiSigConfig {
+        authorized_signers: keys.iter().map(KeyPair::public).collect(),
+        quorum: Quorum(2),
+        nonce: Nonce(0),
+    };
+
+    let signing = vec![
+        single(
+            "native_transfer",
+            "Transfer of the native token without user data",
+            transaction(
+                0,
+                transfer(TokenId::native(), Amount::from(65_535), None),
+                false,
+            ),
+        ),
+        single(
+            "token_transfer_with_user_data",
+            "Transfer of a custom token with user data",
+            transaction(
+                7,
+                transfer(TokenId([0x11; 32]), Amount::from(1_250), Some([0xab; 32])),
+                false,
+            ),
+        ),
+        single(
+            "max_amount",
+            "Transfer of 2^256 - 1 least units, whose BCS encoding is 32 bytes of 0xff",
+            transaction(1, transfer(TokenId::native(), max_amount, None), false),
+        ),
+        single(
+            "zero_amount",
+            "Transfer of nothing",
+            transaction(2, transfer(TokenId::native(), Amount::from(0), None), false),
+        ),
+        single(
+            "archival",
+            "Transaction to be archived by the validators",
+            transaction(4, transfer(TokenId::native(), Amount::from(1), None), true),
+        ),
+        single(
+            "max_nonce_and_timestamp",
+            "Largest nonce and timestamp, which exceed the safe integer range of JavaScript",
+            Transaction {
+                nonce: Nonce(u64::MAX),
+                timestamp_nanos: u128::MAX,
+                ..transaction(0, transfer(TokenId::native(), Amount::from(1), None), false)
+            },
+        ),
+        signing_case(
+            "multisig",
+            "Transfer from a 2-of-3 multisig account, signed by its first two members. The sender \
+             is a placeholder address: only the member signatures are checked here.",
+            multisig_transaction,
+            &keys[..2],
+            Some(multisig_config.clone()),
+        ),
+    ];
+
+    let min_balance: Balance = format!("-{}", "f".repeat(64))
+        .parse()
+        .expect("valid balance");
+    let encoding = vec![
+        encoding_case("amount_zero", "Amount", &Amount::from(0)),
+        encoding_case("amount_u64_boundary", "Amount", &Amount::from(u64::MAX)),
+        encoding_case("amount_max", "Amount", &max_amount),
+        encoding_case("balance_zero", "Balance", &Balance::from(Amount::from(0))),
+        encoding_case(
+            "balance_positive",
+            "Balance",
+            &Balance::from(Amount::from(1_000)),
+        ),
+        encoding_case(
+            "balance_negative",
+            "Balance",
+            &"-1".parse::<Balance>().expect("valid balance"),
+        ),
+        encoding_case("balance_min", "Balance", &min_balance),
+        encoding_case("user_data_none", "UserData", &UserData(None)),
+        encoding_case("user_data_some", "UserData", &UserData(Some([0xab; 32]))),
+        encoding_case("multisig_config", "MultiSigConfig", &multisig_config),
+    ];
+
+    TestVectors {
+        version: VECTORS_VERSION,
+        keys: SECRET_KEYS
+            .iter()
+            .zip(&keys)
+            .map(|(secret, key)| TestKey {
+                secret_key: secret.to_string(),
+                public_key: hex::encode(key.public().0),
+            })
+            .collect(),
+        signing,
+        encoding,
+    }
+}
+
+fn signing_case(
+    name: &str,
+    description: &str,
+    transaction: Transaction,
+    signers: &[KeyPair],
+    multisig: Option<MultiSigConfig>,
+) -> SigningCase {
+    let mut signing_bytes = Vec::new();
+    transaction.write(&mut signing_bytes);
+    let signatures: Vec<_> = signers
+        .iter()
+        .map(|key| (key.public(), Signature::new(&transaction, key)))
+        .collect();
+    let signature = match multisig {
+        Some(config) => SignatureOrMultiSig::MultiSig(MultiSig {
+            config,
+            signatures: signatures.clone(),
+        }),
+        None => SignatureOrMultiSig::Signature(signatures[0].1),
+    };
+    SigningCase {
+        name: name.to_string(),
+        description: description.to_string(),
+        signing_bytes: hex::encode(signing_bytes),
+        signatures: signatures
+            .iter()
+            .map(|(signer, signature)| (hex::encode(signer.0), hex::encode(signature.0)))
+            .collect(),
+        envelope: TransactionEnvelope {
+            transaction: transaction.clone(),
+            signature,
+        },
+        transaction,
+    }
+}
+
+fn encoding_case<T: Serialize>(name: &str, type_name: &str, value: &T) -> EncodingCase {
+    EncodingCase {
+        name: name.to_string(),
+        type_name: type_name.to_string(),
+        json: serde_json::to_value(value).expect("JSON serialization"),
+        bcs: hex::encode(bcs::to_bytes(value).expect("BCS serialization")),
+    }
+}
+
+/// Render the vectors the way `signing.json` is written.
+pub fn to_file_contents(vectors: &TestVectors) -> String {
+    serde_json::to_string_pretty(vectors).expect("test vector serialization") + "\n"
+}
@@ -0,0 +1,254 @@
+//! A cache of [TokenMetadata], so that tools formatting amounts of the same tokens over and over do
+//! not call `proxy_getTokenInfo` each time.
+//!
+//! Metadata is refetched once it is older than the time to live of the registry, or as soon as a
+//! newer `update_id` than the cached one is noted, since the `update_id` of a token changes with
+//! every management operation. The registry can be saved to a file and loaded again, to start warm.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use jsonrpsee::core::ClientError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::client::ProxyRpcClient;
+use crate::fastset_types::{Nonce, TokenId, TokenMetadata};
+use crate::inspect::TokenLabel;
+
+/// Version of the registry file format
+pub const REGISTRY_VERSION: u32 = 1;
+/// How long metadata is used before it is refetched, unless configured otherwise
+pub const DEFAULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Error)]
+pub enum TokenRegistryError {
+    #[error("Unsupported registry version {0}, expected {REGISTRY_VERSION}.")]
+    UnsupportedVersion(u32),
+    #[error("Cannot access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{path} is malformed: {source}")]
+    Format {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Entry {
+    /// `None` for tokens the proxy has no metadata for
+    metadata: Option<TokenMetadata>,
+    fetched_at: SystemTime,
+    /// The highest `update_id` noted for the token
+    noted_update_id: Option<Nonce>,
+}
+
+impl Entry {
+    fn update_id(&self) -> Option<Nonce> {
+        self.metadata.as_ref().map(|metadata| metadata.update_id)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RegistryFile {
+    version: u32,
+    tokens: Vec<(TokenId, Entry)>,
+}
+
+/// Token metadata fetched from the proxy, by [TokenId].
+#[derive(Debug)]
+pub struct TokenRegistry {
+    ttl: Duration,
+    entries: Mutex<HashMap<TokenId, Entry>>,
+}
+
+impl Default for TokenRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+impl TokenRegistry {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Load a registry saved with [TokenRegistry::save]. A missing file is an empty registry.
+    /// Entries keep the time they were fetched at, so old ones are refetched on first use.
+    pub fn load(path: &Path, ttl: Duration) -> Result<Self, TokenRegistryError> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new(ttl)),
+            Err(source) => {
+                return Err(TokenRegistryError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                });
+            }
+        };
+        let file: RegistryFile =
+            serde_json::from_slice(&bytes).map_err(|source| TokenRegistryError::Format {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        if file.version != REGISTRY_VERSION {
+            return Err(TokenRegistryError::UnsupportedVersion(file.version));
+        }
+        Ok(Self {
+            ttl,
+            entries: Mutex::new(file.tokens.into_iter().collect()),
+        })
+    }
+
+    /// Write the registry to `path`, through a temporary file next to it that then replaces it, so
+    /// that a crash or a full disk cannot leave a truncated registry behind.
+    pub fn save(&self, path: &Path) -> Result<(), TokenRegistryError> {
+        let io_error = |source| TokenRegistryError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut tokens: Vec<_> = self.entries.lock().unwrap().clone().into_iter().collect();
+        tokens.sort_by_key(|(token_id, _)| *token_id);
+        let file = RegistryFile {
+            version: REGISTRY_VERSION,
+            tokens,
+        };
+        let json = serde_json::to_string_pretty(&file).expect("registry serialization");
+        let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+        let mut temp = std::fs::File::create(&temp_path).map_err(io_error)?;
+        std::io::Write::write_all(&mut temp, (json + "\n").as_bytes()).map_err(io_error)?;
+        temp.sync_all().map_err(io_error)?;
+        std::fs::rename(&temp_path, path).map_err(io_error)?;
+        // Make the rename itself durable.
+        #[cfg(unix)]
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::File::open(parent)
+                .and_then(|dir| dir.sync_all())
+                .map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    /// Note that `token_id` is at `update_id`, for instance from a management transaction seen
+    /// elsewhere. If the cached metadata is older, it is refetched on next use.
+    pub fn note_update_id(&self, token_id: TokenId, update_id: Nonce) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&token_id) {
+            entry.noted_update_id = entry.noted_update_id.max(Some(update_id));
+        }
+    }
+
+    /// The metadata of `token_ids`, fetching the tokens that are not cached, expired or stale in a
+    /// single call. Tokens the proxy has no metadata for map to `None`.
+    ///
+    /// Metadata older than the cached one, from a proxy lagging behind, does not replace it.
+    pub async fn metadata(
+        &self,
+        client: &impl ProxyRpcClient,
+        token_ids: &[TokenId],
+    ) -> Result<BTreeMap<TokenId, Option<TokenMetadata>>, ClientError> {
+        let now = SystemTime::now();
+        let missing: Vec<TokenId> = {
+            let entries = self.entries.lock().unwrap();
+            let mut missing: Vec<_> = token_ids
+                .iter()
+                .filter(|token_id| {
+                    entries
+                        .get(token_id)
+                        .is_none_or(|entry| !self.is_fresh(entry, now))
+                })
+                .copied()
+                .collect();
+            missing.sort();
+            missing.dedup();
+            missing
+        };
+        if !missing.is_empty() {
+            let info = client.get_token_info(missing).await?;
+            let mut entries = self.entries.lock().unwrap();
+            for (token_id, metadata) in info.requested_token_metadata {
+                let entry = entries.entry(token_id).or_insert(Entry {
+                    metadata: None,
+                    fetched_at: now,
+                    noted_update_id: None,
+                });
+                let fetched_update_id = metadata.as_ref().map(|metadata| metadata.update_id);
+                if fetched_update_id >= entry.update_id() {
+                    entry.metadata = metadata;
+                }
+                entry.fetched_at = now;
+            }
+        }
+        let entries = self.entries.lock().unwrap();
+        Ok(token_ids
+            .iter()
+            .map(|token_id| {
+                let metadata = entries
+                    .get(token_id)
+                    .and_then(|entry| entry.metadata.clone());
+                (*token_id, metadata)
+            })
+            .collect())
+    }
+
+    /// Labels for `token_ids`, like [token_labels](crate::inspect::token_labels) but from the
+    /// cache.
+    pub async fn labels(
+        &self,
+        client: &impl ProxyRpcClient,
+        token_ids: &[TokenId],
+        native_decimals: u8,
+    ) -> Result<BTreeMap<TokenId, TokenLabel>, ClientError> {
+        Ok(self
+            .metadata(client, token_ids)
+            .await?
+            .into_iter()
+            .filter_map(|(token_id, metadata)| {
+                TokenLabel::new(token_id, metadata, native_decimals).map(|label| (token_id, label))
+            })
+            .collect())
+    }
+
+    pub async fn name(
+        &self,
+        client: &impl ProxyRpcClient,
+        token_id: TokenId,
+    ) -> Result<Option<String>, ClientError> {
+        let mut metadata = self.metadata(client, &[token_id]).await?;
+        Ok(metadata
+            .remove(&token_id)
+            .flatten()
+            .map(|metadata| metadata.token_name))
+    }
+
+    pub async fn decimals(
+        &self,
+        client: &impl ProxyRpcClient,
+        token_id: TokenId,
+    ) -> Result<Option<u8>, ClientError> {
+        let mut metadata = self.metadata(client, &[token_id]).await?;
+        Ok(metadata
+            .remove(&token_id)
+            .flatten()
+            .map(|metadata| metadata.decimals))
+    }
+
+    fn is_fresh(&self, entry: &Entry, now: SystemTime) -> bool {
+        entry.update_id() >= entry.noted_update_id
+            && now
+                .duration_since(entry.fetched_at)
+                .is_ok_and(|age| age < self.ttl)
+    }
+}
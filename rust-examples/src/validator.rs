@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::committee::{CertificateError, Committee};
+use crate::fastset_types::{
+    AccountInfoResponse, Amount, FastSetAddress, KeyPair, Nonce, NonceRange, Signature, StateKey,
+    TokenId, TokenInfoResponse, TokenMetadata, TransactionCertificate, TransactionEnvelope,
+    ValidatedTransaction, ValidatorName, get_key_pair,
+};
+use crate::ledger::{Ledger, LedgerError};
+
+#[derive(Debug, Error)]
+pub enum ValidatorError {
+    #[error("Validator is offline.")]
+    Offline,
+    #[error("Validator did not answer in time.")]
+    Timeout,
+    /// The validator has not executed all previous transactions of the sender yet.
+    #[error("Validator is lagging: its next nonce for the sender is {next_nonce}.")]
+    Lagging { next_nonce: Nonce },
+    #[error("Already signed a different transaction with nonce {0}.")]
+    ConflictingTransaction(Nonce),
+    #[error(transparent)]
+    Certificate(#[from] CertificateError),
+    #[error(transparent)]
+    Ledger(LedgerError),
+}
+
+impl From<LedgerError> for ValidatorError {
+    fn from(err: LedgerError) -> Self {
+        match err {
+            LedgerError::UnexpectedNonce { expected, got } if got > expected => {
+                ValidatorError::Lagging {
+                    next_nonce: expected,
+                }
+            }
+            err => ValidatorError::Ledger(err),
+        }
+    }
+}
+
+/// Misbehaviour injected into a [Validator]. The default is an honest, responsive validator.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Faults {
+    /// Fail every request at once, like an unreachable validator
+    pub offline: bool,
+    /// Delay before handling each request, in milliseconds
+    pub delay_ms: u64,
+    /// Probability (between 0 and 1) that a request is silently dropped and never answered
+    pub drop_rate: f64,
+    /// Serve reads from a snapshot of the state taken when this fault was enabled
+    pub stale_reads: bool,
+    /// Vote for every valid transaction, even if it conflicts with an earlier vote
+    pub equivocate: bool,
+    /// Produce signatures that do not verify
+    pub invalid_signatures: bool,
+}
+
+/// An in-process stand-in for a FastSet validator.
+///
+/// Honest validators vote for (sign) at most one transaction per sender and nonce, after checking
+/// the sender's signature, nonce and balance, and execute transactions once they receive a
+/// certificate for them. See [Faults] for the ways in which a validator can be made to misbehave.
+pub struct Validator {
+    key: KeyPair,
+    ledger: Mutex<Ledger>,
+    /// The transaction each sender's next nonce is locked to, once voted for
+    pending: Mutex<HashMap<FastSetAddress, ValidatedTransaction>>,
+    faults: Mutex<Faults>,
+    /// The state served to reads while `faults.stale_reads` is set
+    stale: Mutex<Option<Ledger>>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self {
+            key: get_key_pair().1,
+            ledger: Mutex::default(),
+            pending: Mutex::default(),
+            faults: Mutex::default(),
+            stale: Mutex::default(),
+        }
+    }
+
+    pub fn name(&self) -> ValidatorName {
+        self.key.public()
+    }
+
+    /// Replace the faults of the validator. Enabling `stale_reads` freezes the state served to
+    /// reads at this point.
+    pub fn set_faults(&self, faults: Faults) {
+        let mut stale = self.stale.lock().unwrap();
+        match (faults.stale_reads, stale.is_some()) {
+            (true, false) => *stale = Some(self.ledger.lock().unwrap().clone()),
+            (false, true) => *stale = None,
+            _ => {}
+        }
+        *self.faults.lock().unwrap() = faults;
+    }
+
+    /// Take the validator offline (it then fails every request) or back online. A validator
+    /// coming back online has missed the transactions settled in the meantime.
+    pub fn set_online(&self, online: bool) {
+        self.faults.lock().unwrap().offline = !online;
+    }
+
+    /// Apply the configured availability faults to an incoming request.
+    async fn receive(&self) -> Result<Faults, ValidatorError> {
+        let faults = self.faults.lock().unwrap().clone();
+        if faults.offline {
+            return Err(ValidatorError::Offline);
+        }
+        if faults.delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(faults.delay_ms)).await;
+        }
+        if faults.drop_rate > 0.0 && rand::thread_rng().gen_bool(faults.drop_rate.min(1.0)) {
+            std::future::pending::<()>().await;
+        }
+        Ok(faults)
+    }
+
+    fn sign(&self, vote: &TransactionEnvelope, faults: &Faults) -> Signature {
+        let mut signature = Signature::new(&vote.transaction, &self.key);
+        if faults.invalid_signatures {
+            signature.0[0] ^= 0xff;
+        }
+        signature
+    }
+
+    pub fn add_token(&self, token_id: TokenId, metadata: TokenMetadata) {
+        self.ledger.lock().unwrap().add_token(token_id, metadata);
+    }
+
+    /// Mint funds outside of consensus, as the faucet does. This works even while the validator is
+    /// offline, so that faucet drips never leave validators inconsistent.
+    pub fn credit(
+        &self,
+        recipient: FastSetAddress,
+        token_id: TokenId,
+        amount: Amount,
+    ) -> Result<(), ValidatorError> {
+        Ok(self
+            .ledger
+            .lock()
+            .unwrap()
+            .credit(recipient, token_id, amount)?)
+    }
+
+    /// Vote for `envelope` if it is valid and does not conflict with an earlier vote.
+    pub async fn handle_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<ValidatedTransaction, ValidatorError> {
+        let faults = self.receive().await?;
+        envelope.verify().map_err(CertificateError::Sender)?;
+        let transaction = &envelope.transaction;
+        let ledger = self.ledger.lock().unwrap();
+        ledger.check(transaction)?;
+
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(vote) = pending.get(&transaction.sender)
+            && vote.value.transaction.nonce == transaction.nonce
+            && !faults.equivocate
+        {
+            return if vote.value == *envelope {
+                Ok(vote.clone())
+            } else {
+                Err(ValidatorError::ConflictingTransaction(transaction.nonce))
+            };
+        }
+        let vote = ValidatedTransaction {
+            value: envelope.clone(),
+            validator: self.name(),
+            signature: self.sign(envelope, &faults),
+        };
+        pending.insert(transaction.sender, vote.clone());
+        Ok(vote)
+    }
+
+    /// Execute a certified transaction. Certificates for transactions that were already executed
+    /// are accepted again without effect.
+    pub async fn handle_certificate(
+        &self,
+        certificate: &TransactionCertificate,
+        committee: &Committee,
+    ) -> Result<(), ValidatorError> {
+        self.receive().await?;
+        committee.verify_certificate(certificate)?;
+        let transaction = &certificate.envelope.transaction;
+        let mut ledger = self.ledger.lock().unwrap();
+        if transaction.nonce < ledger.account(&transaction.sender).next_nonce {
+            return Ok(());
+        }
+        ledger.execute(certificate.clone())?;
+        let mut pending = self.pending.lock().unwrap();
+        if pending
+            .get(&transaction.sender)
+            .is_some_and(|vote| vote.value.transaction.nonce <= transaction.nonce)
+        {
+            pending.remove(&transaction.sender);
+        }
+        Ok(())
+    }
+
+    pub async fn account_info(
+        &self,
+        address: FastSetAddress,
+        token_balances_filter: Option<Vec<TokenId>>,
+        state_key_filter: Option<Vec<StateKey>>,
+        certificate_by_nonce: Option<NonceRange>,
+    ) -> Result<AccountInfoResponse, ValidatorError> {
+        self.receive().await?;
+        let stale = self.stale.lock().unwrap();
+        let ledger = self.ledger.lock().unwrap();
+        let mut info = stale.as_ref().unwrap_or(&ledger).account_info(
+            address,
+            token_balances_filter,
+            state_key_filter,
+            certificate_by_nonce,
+        );
+        info.pending_confirmation = self
+            .pending
+            .lock()
+            .unwrap()
+            .get(&address)
+            .filter(|vote| vote.value.transaction.nonce == info.next_nonce)
+            .cloned();
+        Ok(info)
+    }
+
+    pub async fn token_info(
+        &self,
+        token_ids: Vec<TokenId>,
+    ) -> Result<TokenInfoResponse, ValidatorError> {
+        self.receive().await?;
+        let stale = self.stale.lock().unwrap();
+        let ledger = self.ledger.lock().unwrap();
+        Ok(stale.as_ref().unwrap_or(&ledger).token_info(token_ids))
+    }
+}
@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+
+use rust_examples::abi::{
+    ABI_TRANSACTION_LEN, AbiClaim, AbiError, AbiTransaction, UNCHECKED_FIELDS, WordDiff,
+    diff_words, encode_certificate,
+};
+use rust_examples::fastset_types::{
+    Amount, ClaimType, CrossSignResponse, Nonce, TokenId, TransactionCertificate, get_key_pair,
+};
+use serde::Deserialize;
+
+mod common;
+use common::transfer;
+
+fn certificate() -> TransactionCertificate {
+    let (_, sender) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    TransactionCertificate {
+        envelope: transfer(&sender, recipient, Nonce(7), 1_000),
+        signatures: Vec::new(),
+    }
+}
+
+/// The ABI words of the transaction of `certificate`, written out by hand.
+fn abi_words(certificate: &TransactionCertificate) -> Vec<[u8; 32]> {
+    let transaction = &certificate.envelope.transaction;
+    let ClaimType::TokenTransfer(transfer) = &transaction.claim else {
+        panic!("a transfer");
+    };
+    let mut nonce = [0; 32];
+    nonce[24..].copy_from_slice(&transaction.nonce.0.to_be_bytes());
+    let mut timestamp = [0; 32];
+    timestamp[16..].copy_from_slice(&transaction.timestamp_nanos.to_be_bytes());
+    let mut amount = [0; 32];
+    amount[30..].copy_from_slice(&1_000u16.to_be_bytes());
+    assert_eq!(transfer.amount, Amount::from(1_000));
+    vec![
+        transaction.sender.0,
+        transaction.recipient.0,
+        nonce,
+        timestamp,
+        [0; 32],
+        transfer.token_id.0,
+        amount,
+    ]
+}
+
+fn response(words: &[[u8; 32]]) -> CrossSignResponse {
+    CrossSignResponse {
+        format: "eip191-abi".to_string(),
+        signature: String::new(),
+        transaction: words.concat(),
+    }
+}
+
+#[test]
+fn abi_transactions_decode_to_the_certified_transfer() {
+    let certificate = certificate();
+    let response = response(&abi_words(&certificate));
+    let decoded = response.decode_transaction().unwrap();
+    assert_eq!(
+        decoded,
+        AbiTransaction {
+            sender: certificate.envelope.transaction.sender,
+            recipient: certificate.envelope.transaction.recipient,
+            nonce: Nonce(7),
+            timestamp_nanos: certificate.envelope.transaction.timestamp_nanos,
+            claim: AbiClaim::TokenTransfer,
+            token_id: TokenId::native(),
+            amount: Amount::from(1_000),
+        }
+    );
+    let check = response.check_certificate(&certificate).unwrap();
+    assert_eq!(check.unchecked, UNCHECKED_FIELDS);
+}
+
+#[test]
+fn abi_transactions_of_other_transfers_are_rejected() {
+    let certificate = certificate();
+    let mut words = abi_words(&certificate);
+    words[2][31] += 1;
+    words[6][31] += 1;
+    assert_eq!(
+        response(&words).check_certificate(&certificate),
+        Err(AbiError::Mismatch(vec!["nonce", "amount"]))
+    );
+
+    let other = self::certificate();
+    assert_eq!(
+        response(&abi_words(&other)).check_certificate(&certificate),
+        Err(AbiError::Mismatch(vec![
+            "sender",
+            "recipient",
+            "timestampNanos"
+        ]))
+    );
+}
+
+#[test]
+fn malformed_abi_transactions_are_rejected() {
+    let words = abi_words(&certificate());
+    assert_eq!(
+        AbiTransaction::decode(&words[..6].concat()),
+        Err(AbiError::Length(ABI_TRANSACTION_LEN - 32))
+    );
+    assert_eq!(
+        AbiTransaction::decode(&[words.concat(), vec![0]].concat()),
+        Err(AbiError::Length(ABI_TRANSACTION_LEN + 1))
+    );
+
+    for (index, name) in [(2, "nonce"), (3, "timestampNanos"), (4, "claim")] {
+        let mut words = words.clone();
+        words[index][0] = 1;
+        assert_eq!(
+            AbiTransaction::decode(&words.concat()),
+            Err(AbiError::OutOfRange(name))
+        );
+    }
+
+    let mut words = words;
+    words[4][31] = 9;
+    assert_eq!(
+        AbiTransaction::decode(&words.concat()),
+        Err(AbiError::UnknownClaim(9))
+    );
+}
+
+#[test]
+fn local_encoding_matches_the_abi_layout() {
+    let certificate = certificate();
+    let words = abi_words(&certificate);
+    assert_eq!(encode_certificate(&certificate).unwrap(), words.concat());
+    assert!(
+        response(&words)
+            .diff_encoding(&certificate)
+            .unwrap()
+            .is_empty()
+    );
+
+    let extremes = AbiTransaction {
+        nonce: Nonce(u64::MAX),
+        timestamp_nanos: u128::MAX,
+        amount: "f".repeat(64).parse().unwrap(),
+        ..response(&words).decode_transaction().unwrap()
+    };
+    let encoded = extremes.encode();
+    assert_eq!(encoded.len(), ABI_TRANSACTION_LEN);
+    assert_eq!(encoded[64..88], [0; 24]);
+    assert_eq!(encoded[96..112], [0; 16]);
+    assert_eq!(encoded[192..], [0xff; 32]);
+    assert_eq!(AbiTransaction::decode(&encoded).unwrap(), extremes);
+}
+
+#[test]
+fn encoding_differences_are_reported_by_word() {
+    let certificate = certificate();
+    let mut words = abi_words(&certificate);
+    words[6][31] ^= 0xff;
+    let mut signed = words.concat();
+    signed.extend_from_slice(&[1; 8]);
+
+    let diffs = response(&words).diff_encoding(&certificate).unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].offset, 192);
+    assert_eq!(diffs[0].field, Some("amount"));
+
+    let diffs = diff_words(&encode_certificate(&certificate).unwrap(), &signed);
+    assert_eq!(
+        diffs[1],
+        WordDiff {
+            offset: ABI_TRANSACTION_LEN,
+            field: None,
+            expected: None,
+            actual: Some(vec![1; 8]),
+        }
+    );
+    assert_eq!(
+        diffs[1].to_string(),
+        "0x00e0 (past the end of the transaction)\n  expected -\n  actual   0101010101010101"
+    );
+}
+
+/// A response of a real proxy to `proxy_evmSignCertificate`, see
+/// `tests/evm_sign_certificate/README.md`.
+#[derive(Deserialize)]
+struct Capture {
+    certificate: TransactionCertificate,
+    response: CrossSignResponse,
+}
+
+fn captures() -> Vec<(PathBuf, Capture)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/evm_sign_certificate");
+    let mut captures = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let capture = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            captures.push((path, capture));
+        }
+    }
+    captures
+}
+
+#[test]
+fn captured_responses_follow_the_assumed_layout() {
+    for (path, capture) in captures() {
+        if let Err(error) = capture.response.check_certificate(&capture.certificate) {
+            panic!("{}: {error}", path.display());
+        }
+        let diffs = capture
+            .response
+            .diff_encoding(&capture.certificate)
+            .unwrap();
+        assert!(
+            diffs.is_empty(),
+            "{}: the encoding differs from the capture at\n{}",
+            path.display(),
+            diffs
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use proptest::prelude::*;
+use rust_examples::fastset_types::{Amount, Balance};
+
+/// Hex digits of a little-endian number, without leading zeros, computed independently of `bnum`.
+fn limbs_to_hex(limbs: &[u64]) -> String {
+    let hex: String = limbs
+        .iter()
+        .rev()
+        .map(|limb| format!("{limb:016x}"))
+        .collect();
+    match hex.trim_start_matches('0') {
+        "" => "0".to_string(),
+        digits => digits.to_string(),
+    }
+}
+
+fn limbs_to_bytes(limbs: &[u64]) -> Vec<u8> {
+    limbs.iter().flat_map(|limb| limb.to_le_bytes()).collect()
+}
+
+/// Two's complement negation of a little-endian number.
+fn negate(limbs: [u64; 5]) -> [u64; 5] {
+    let mut result = [0; 5];
+    let mut carry = true;
+    for (out, limb) in result.iter_mut().zip(limbs) {
+        let (sum, overflow) = (!limb).overflowing_add(carry as u64);
+        *out = sum;
+        carry = overflow;
+    }
+    result
+}
+
+fn json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn amount_round_trips_in_json_and_bcs(limbs in any::<[u64; 4]>()) {
+        let hex = limbs_to_hex(&limbs);
+        let amount: Amount = hex.parse().unwrap();
+        prop_assert_eq!(hex.to_uppercase().parse::<Amount>().unwrap(), amount);
+
+        let encoded = json(&amount);
+        prop_assert_eq!(&encoded, &format!("\"{hex}\""));
+        prop_assert_eq!(serde_json::from_str::<Amount>(&encoded).unwrap(), amount);
+
+        let bytes = bcs::to_bytes(&amount).unwrap();
+        prop_assert_eq!(&bytes, &limbs_to_bytes(&limbs));
+        prop_assert_eq!(bcs::from_bytes::<Amount>(&bytes).unwrap(), amount);
+
+        let mut be_bytes = limbs_to_bytes(&limbs);
+        be_bytes.reverse();
+        prop_assert_eq!(&amount.to_be_bytes()[..], &be_bytes[..]);
+        prop_assert_eq!(Amount::from_be_bytes(amount.to_be_bytes()), amount);
+    }
+
+    #[test]
+    fn balance_round_trips_in_json_and_bcs(negative in any::<bool>(), limbs in any::<[u64; 4]>()) {
+        let magnitude = limbs_to_hex(&limbs);
+        let hex = if negative && magnitude != "0" {
+            format!("-{magnitude}")
+        } else {
+            magnitude
+        };
+        let balance: Balance = hex.parse().unwrap();
+        prop_assert_eq!(balance.is_negative(), hex.starts_with('-'));
+
+        let encoded = json(&balance);
+        prop_assert_eq!(&encoded, &format!("\"{hex}\""));
+        prop_assert_eq!(serde_json::from_str::<Balance>(&encoded).unwrap(), balance);
+
+        let [a, b, c, d] = limbs;
+        let mut expected = [a, b, c, d, 0];
+        if negative {
+            expected = negate(expected);
+        }
+        let bytes = bcs::to_bytes(&balance).unwrap();
+        prop_assert_eq!(&bytes, &limbs_to_bytes(&expected));
+        prop_assert_eq!(bcs::from_bytes::<Balance>(&bytes).unwrap(), balance);
+    }
+
+    #[test]
+    fn amounts_convert_to_equal_balances(limbs in any::<[u64; 4]>()) {
+        let amount: Amount = limbs_to_hex(&limbs).parse().unwrap();
+        let balance = Balance::from(amount);
+        prop_assert!(!balance.is_negative());
+        prop_assert_eq!(json(&balance), json(&amount));
+    }
+
+    #[test]
+    fn balance_bcs_is_rejected_out_of_bounds(limbs in any::<[u64; 5]>(), top in prop_oneof![Just(0), Just(u64::MAX), any::<u64>()]) {
+        let mut limbs = limbs;
+        limbs[4] = top;
+        let low_is_zero = limbs[..4].iter().all(|limb| *limb == 0);
+        let in_bounds = top == 0 || (top == u64::MAX && !low_is_zero);
+        let decoded = bcs::from_bytes::<Balance>(&limbs_to_bytes(&limbs));
+        prop_assert_eq!(decoded.is_ok(), in_bounds);
+    }
+
+    #[test]
+    fn parsed_strings_are_well_formed_hex(s in "[-+ 0-9a-fA-FxX_.]{0,70}") {
+        if let Ok(amount) = s.parse::<Amount>() {
+            prop_assert!(!s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit()), "{}", s);
+            prop_assert_eq!(json(&amount).trim_matches('"').parse::<Amount>().unwrap(), amount);
+        }
+        if let Ok(balance) = s.parse::<Balance>() {
+            let digits = s.strip_prefix('-').unwrap_or(&s);
+            prop_assert!(!digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()), "{}", s);
+            prop_assert_eq!(json(&balance).trim_matches('"').parse::<Balance>().unwrap(), balance);
+        }
+    }
+
+    #[test]
+    fn bcs_of_the_wrong_length_is_rejected(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        prop_assert_eq!(bcs::from_bytes::<Amount>(&bytes).is_ok(), bytes.len() == 32);
+        if bytes.len() != 40 {
+            prop_assert!(bcs::from_bytes::<Balance>(&bytes).is_err());
+        }
+    }
+}
+
+#[test]
+fn balance_bounds_are_the_amount_range() {
+    let max_hex = "f".repeat(64);
+    assert_eq!(json(&Balance::max()), format!("\"{max_hex}\""));
+    assert_eq!(json(&Balance::min()), format!("\"-{max_hex}\""));
+    assert_eq!(
+        Balance::max(),
+        Balance::from(max_hex.parse::<Amount>().unwrap())
+    );
+
+    let max_bytes = limbs_to_bytes(&[u64::MAX, u64::MAX, u64::MAX, u64::MAX, 0]);
+    assert_eq!(bcs::to_bytes(&Balance::max()).unwrap(), max_bytes);
+    let min_bytes = limbs_to_bytes(&[1, 0, 0, 0, u64::MAX]);
+    assert_eq!(bcs::to_bytes(&Balance::min()).unwrap(), min_bytes);
+    assert_eq!(
+        bcs::from_bytes::<Balance>(&min_bytes).unwrap(),
+        Balance::min()
+    );
+    // -2^256, one below the minimum
+    assert!(bcs::from_bytes::<Balance>(&limbs_to_bytes(&[0, 0, 0, 0, u64::MAX])).is_err());
+    // 2^256, one above the maximum
+    assert!(bcs::from_bytes::<Balance>(&limbs_to_bytes(&[0, 0, 0, 0, 1])).is_err());
+
+    let one = Balance::from(Amount::from(1));
+    assert_eq!(Balance::max().checked_add(one), None);
+    assert_eq!(Balance::min().checked_sub(one), None);
+    let above = format!("1{}", "0".repeat(64));
+    assert!(above.parse::<Balance>().is_err());
+    assert!(format!("-{above}").parse::<Balance>().is_err());
+    assert!(above.parse::<Amount>().is_err());
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    for input in [
+        "", "+1", "0x1", " 1", "1 ", "-", "--1", "-+1", "g", "1.5", "ff_ff", "1h",
+    ] {
+        assert!(input.parse::<Amount>().is_err(), "{input:?}");
+        assert!(input.parse::<Balance>().is_err(), "{input:?}");
+    }
+    assert!("-1".parse::<Amount>().is_err());
+    assert_eq!(
+        "-ff".parse::<Balance>().unwrap().to_decimal_string(0),
+        "-255"
+    );
+    assert_eq!(
+        "-0".parse::<Balance>().unwrap(),
+        Balance::from(Amount::from(0))
+    );
+
+    for input in ["1", "null", "[1, 0, 0, 0]", "\"0x1\"", "\"\""] {
+        assert!(serde_json::from_str::<Amount>(input).is_err(), "{input}");
+        assert!(serde_json::from_str::<Balance>(input).is_err(), "{input}");
+    }
+}
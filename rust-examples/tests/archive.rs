@@ -0,0 +1,221 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use jsonrpsee::http_client::HttpClientBuilder;
+use rust_examples::api::faucet_drip;
+use rust_examples::archive::{ArchiveError, CertificateArchive};
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::committee::Committee;
+use rust_examples::fastset_types::{
+    Amount, ClaimType, MintOperation, Nonce, NonceRange, Operation, OperationBundle, TokenId,
+    TokenTransferOperation, TransactionCertificate, TransactionEnvelope, UserData, get_key_pair,
+};
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::offline::signing_digest;
+use rust_examples::subscription::settled_certificates;
+
+mod common;
+use common::transfer;
+
+#[tokio::test]
+async fn archived_certificates_are_indexed_and_reloaded() {
+    let proxy = MockProxy::new(4);
+    let committee = Committee::new(proxy.committee());
+    let (addr, _handle) = proxy.start().await.unwrap();
+    let client = Arc::new(
+        HttpClientBuilder::default()
+            .build(format!("http://{addr}"))
+            .unwrap(),
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("certificates.jsonl");
+    let mut archive = CertificateArchive::open(&path, Some(committee.clone())).unwrap();
+
+    let (sender, sender_key) = get_key_pair();
+    let (alice, _) = get_key_pair();
+    let (bob, _) = get_key_pair();
+    faucet_drip(&*client, sender, Amount::from(1_000))
+        .await
+        .unwrap();
+    let mut certificates = Vec::new();
+    for (nonce, recipient) in [alice, bob, alice].into_iter().enumerate() {
+        let envelope = transfer(&sender_key, recipient, Nonce(nonce as u64), 10);
+        let result = client
+            .submit_transaction(envelope.transaction, envelope.signature)
+            .await
+            .unwrap();
+        let ProxySubmitTransactionResult::Success(certificate) = &result else {
+            panic!("unexpected result {result:?}");
+        };
+        certificates.push(certificate.clone());
+        if nonce == 0 {
+            assert!(archive.insert_submit_result(&result).unwrap());
+            assert!(!archive.insert_submit_result(&result).unwrap());
+        }
+    }
+
+    // The rest comes from the history of the sender.
+    let history = settled_certificates(
+        client.clone(),
+        sender,
+        Some(Nonce(0)),
+        Duration::from_millis(20),
+    )
+    .await
+    .unwrap()
+    .take(3);
+    assert_eq!(archive.ingest(history).await.unwrap(), 2);
+    let info = client
+        .get_account_info(
+            sender,
+            None,
+            None,
+            Some(NonceRange {
+                start: Nonce(0),
+                limit: 3,
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(archive.insert_account_info(&info).unwrap(), 0);
+
+    let check = |archive: &CertificateArchive| {
+        assert_eq!(archive.len(), 3);
+        let hash = signing_digest(&certificates[1].envelope.transaction);
+        assert_eq!(archive.by_hash(&hash), Some(&certificates[1]));
+        assert_eq!(
+            archive.by_sender_nonce(sender, Nonce(2)),
+            Some(&certificates[2])
+        );
+        assert_eq!(archive.by_sender_nonce(sender, Nonce(3)), None);
+        assert_eq!(
+            archive.by_recipient(alice, ..),
+            vec![&certificates[0], &certificates[2]]
+        );
+        assert_eq!(archive.by_recipient(bob, ..), vec![&certificates[1]]);
+        assert_eq!(archive.by_sender(sender, ..).len(), 3);
+        assert_eq!(archive.by_token(TokenId::native(), ..).len(), 3);
+        assert!(archive.by_token(TokenId([0x11; 32]), ..).is_empty());
+
+        let timestamp = |index: usize| certificates[index].envelope.transaction.timestamp_nanos;
+        assert_eq!(
+            archive.all(timestamp(1)..=timestamp(2)),
+            vec![&certificates[1], &certificates[2]]
+        );
+        assert_eq!(
+            archive.by_recipient(alice, ..timestamp(2)),
+            vec![&certificates[0]]
+        );
+    };
+    check(&archive);
+    drop(archive);
+    check(&CertificateArchive::open(&path, Some(committee.clone())).unwrap());
+
+    // A line torn by a crash while appending is cut off when the archive is opened.
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let torn = format!("{contents}{{\"envelope\":{{\"transaction\"");
+    std::fs::write(&path, torn).unwrap();
+    check(&CertificateArchive::open(&path, Some(committee.clone())).unwrap());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+}
+
+#[tokio::test]
+async fn conflicting_or_unsigned_certificates_are_rejected() {
+    let proxy = MockProxy::new(1);
+    let committee = Committee::new(proxy.committee());
+    let (addr, _handle) = proxy.start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("certificates.jsonl");
+    let mut archive = CertificateArchive::open(&path, Some(committee)).unwrap();
+
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(1_000))
+        .await
+        .unwrap();
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    let result = client
+        .submit_transaction(envelope.transaction, envelope.signature)
+        .await
+        .unwrap();
+    assert!(archive.insert_submit_result(&result).unwrap());
+
+    let conflicting = TransactionCertificate {
+        envelope: transfer(&sender_key, recipient, Nonce(0), 20),
+        signatures: Vec::new(),
+    };
+    assert!(matches!(
+        archive.insert(conflicting.clone()),
+        Err(ArchiveError::Certificate(_))
+    ));
+    drop(archive);
+    let mut unverified = CertificateArchive::open(&path, None).unwrap();
+    assert!(matches!(
+        unverified.insert(conflicting),
+        Err(ArchiveError::Conflict {
+            nonce: Nonce(0),
+            ..
+        })
+    ));
+    assert_eq!(unverified.len(), 1);
+    drop(unverified);
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, contents.replace("\"version\":1", "\"version\":2")).unwrap();
+    assert!(matches!(
+        CertificateArchive::open(&path, None),
+        Err(ArchiveError::UnsupportedVersion(2))
+    ));
+    std::fs::write(&path, "{\"version\":1}\n{\"envelope\"\n").unwrap();
+    assert!(matches!(
+        CertificateArchive::open(&path, None),
+        Err(ArchiveError::Format { line: 2, .. })
+    ));
+}
+
+#[test]
+fn batches_are_indexed_by_the_recipients_of_their_operations() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("certificates.jsonl");
+    let mut archive = CertificateArchive::open(&path, None).unwrap();
+
+    let (sender, sender_key) = get_key_pair();
+    let (alice, _) = get_key_pair();
+    let (bob, _) = get_key_pair();
+    let mut transaction = transfer(&sender_key, sender, Nonce(0), 0).transaction;
+    transaction.claim = ClaimType::Batch(OperationBundle(vec![
+        Operation::TokenTransfer(TokenTransferOperation {
+            token_id: TokenId::native(),
+            recipient: alice,
+            amount: Amount::from(10),
+            user_data: UserData(None),
+        }),
+        Operation::Mint(MintOperation {
+            token_id: TokenId::native(),
+            recipient: bob,
+            amount: Amount::from(5),
+        }),
+        Operation::TokenTransfer(TokenTransferOperation {
+            token_id: TokenId::native(),
+            recipient: alice,
+            amount: Amount::from(20),
+            user_data: UserData(None),
+        }),
+    ]));
+    let batch = TransactionCertificate {
+        envelope: TransactionEnvelope::new(transaction, &sender_key),
+        signatures: Vec::new(),
+    };
+    assert!(archive.insert(batch.clone()).unwrap());
+
+    assert_eq!(archive.by_recipient(alice, ..), vec![&batch]);
+    assert_eq!(archive.by_recipient(bob, ..), vec![&batch]);
+    assert!(archive.by_recipient(sender, ..).is_empty());
+    drop(archive);
+    let archive = CertificateArchive::open(&path, None).unwrap();
+    assert_eq!(archive.by_recipient(bob, ..), vec![&batch]);
+}
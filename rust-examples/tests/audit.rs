@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use rust_examples::audit::{Discrepancy, HistoryIssue, Opening, Replay, audit_account};
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::fastset_types::{
+    Amount, Balance, ClaimType, KeyPair, Mint, MintOperation, Nonce, Operation, OperationBundle,
+    PublicKeyBytes, TokenCreation, TokenId, TokenMetadata, TokenTransferOperation,
+    TransactionCertificate, TransactionEnvelope, UserData, get_key_pair,
+};
+use rust_examples::mock_proxy::MockProxy;
+
+mod common;
+use common::transfer;
+
+const USDX: TokenId = TokenId([0x11; 32]);
+
+fn balance(amount: u64) -> Balance {
+    Balance::from(Amount::from(amount))
+}
+
+async fn settle(client: &HttpClient, envelope: TransactionEnvelope) -> TransactionCertificate {
+    let result = client
+        .submit_transaction(envelope.transaction, envelope.signature)
+        .await
+        .unwrap();
+    let ProxySubmitTransactionResult::Success(certificate) = result else {
+        panic!("unexpected result {result:?}");
+    };
+    certificate
+}
+
+fn usdx_transfer(
+    sender: &KeyPair,
+    recipient: PublicKeyBytes,
+    nonce: Nonce,
+    amount: u64,
+) -> TransactionEnvelope {
+    let mut transaction = transfer(sender, recipient, nonce, amount).transaction;
+    let ClaimType::TokenTransfer(transfer) = &mut transaction.claim else {
+        panic!("a transfer");
+    };
+    transfer.token_id = USDX;
+    TransactionEnvelope::new(transaction, sender)
+}
+
+/// An unsigned certificate of a transaction with `claim`.
+fn certificate(
+    sender: &KeyPair,
+    recipient: PublicKeyBytes,
+    nonce: Nonce,
+    claim: ClaimType,
+) -> TransactionCertificate {
+    let mut transaction = transfer(sender, recipient, nonce, 0).transaction;
+    transaction.claim = claim;
+    TransactionCertificate {
+        envelope: TransactionEnvelope::new(transaction, sender),
+        signatures: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn replayed_history_matches_the_proxy_or_points_at_certificates() {
+    let proxy = MockProxy::new(1);
+    proxy.add_token(
+        USDX,
+        TokenMetadata {
+            update_id: Nonce(0),
+            admin: PublicKeyBytes([0xad; 32]),
+            token_name: "USDX".to_string(),
+            decimals: 2,
+            total_supply: Amount::from(1_000_000),
+            mints: vec![],
+        },
+    );
+    let (addr, _handle) = proxy.start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+
+    let (alice, alice_key) = get_key_pair();
+    let (bob, bob_key) = get_key_pair();
+    let native = TokenId::native();
+    client
+        .faucet_drip(alice, Amount::from(1_000), None)
+        .await
+        .unwrap();
+    client
+        .faucet_drip(alice, Amount::from(500), Some(USDX))
+        .await
+        .unwrap();
+    client
+        .faucet_drip(bob, Amount::from(200), None)
+        .await
+        .unwrap();
+    let opening = Opening {
+        next_nonce: Nonce(0),
+        balances: BTreeMap::from([(native, balance(1_000)), (USDX, balance(500))]),
+    };
+
+    let sent = settle(&client, transfer(&alice_key, bob, Nonce(0), 100)).await;
+    let received = settle(&client, transfer(&bob_key, alice, Nonce(0), 30)).await;
+    let sent_usdx = settle(&client, usdx_transfer(&alice_key, bob, Nonce(1), 50)).await;
+    let burnt = settle(
+        &client,
+        transfer(&alice_key, PublicKeyBytes([0; 32]), Nonce(2), 10),
+    )
+    .await;
+    // Out of order and with a duplicate, as from several sources
+    let history = [&burnt, &received, &sent_usdx, &sent, &sent];
+
+    let audit = audit_account(&client, alice, &opening, history)
+        .await
+        .unwrap();
+    assert!(audit.is_clean(), "{audit:?}");
+    assert_eq!(audit.replay.next_nonce, Nonce(3));
+    assert_eq!(audit.replay.balance(native), balance(920));
+    assert_eq!(audit.replay.balance(USDX), balance(450));
+
+    // Without the incoming transfer, the native balance cannot be explained.
+    let audit = audit_account(&client, alice, &opening, [&sent, &sent_usdx, &burnt])
+        .await
+        .unwrap();
+    assert_eq!(
+        audit.discrepancies,
+        vec![Discrepancy::Balance {
+            token_id: native,
+            expected: balance(890),
+            reported: balance(920),
+            certificates: vec![sent.clone(), burnt.clone()],
+        }]
+    );
+
+    // A gap in the outgoing nonces stops the replay.
+    let audit = audit_account(&client, alice, &opening, [&sent, &received, &burnt])
+        .await
+        .unwrap();
+    assert_eq!(
+        audit.replay.issues,
+        vec![HistoryIssue::MissingNonces {
+            from: Nonce(1),
+            to: Nonce(2)
+        }]
+    );
+    assert!(matches!(
+        &audit.discrepancies[0],
+        Discrepancy::Nonce {
+            expected: Nonce(1),
+            reported: Nonce(3),
+            certificates,
+        } if certificates.is_empty()
+    ));
+
+    // A proxy behind the history does not account for the last transaction.
+    let replay = Replay::new(alice, &opening, history);
+    let mut info = audit.info.clone();
+    info.next_nonce = Nonce(2);
+    info.balance = balance(930);
+    info.token_balance = vec![(USDX, balance(450))];
+    assert_eq!(
+        replay.compare(&info),
+        vec![
+            Discrepancy::Nonce {
+                expected: Nonce(3),
+                reported: Nonce(2),
+                certificates: vec![burnt.clone()],
+            },
+            Discrepancy::Balance {
+                token_id: native,
+                expected: balance(920),
+                reported: balance(930),
+                certificates: vec![sent.clone(), burnt.clone(), received.clone()],
+            },
+        ]
+    );
+
+    let conflicting = TransactionCertificate {
+        envelope: transfer(&alice_key, bob, Nonce(0), 999),
+        signatures: Vec::new(),
+    };
+    let replay = Replay::new(alice, &opening, [&sent, &conflicting]);
+    assert_eq!(
+        replay.issues,
+        vec![HistoryIssue::ConflictingCertificates(Nonce(0))]
+    );
+    assert_eq!(replay.balance(native), balance(900));
+}
+
+#[test]
+fn mints_and_batches_are_replayed() {
+    let (minter, minter_key) = get_key_pair();
+    let (alice, alice_key) = get_key_pair();
+    let (bob, _) = get_key_pair();
+    let (carol, _) = get_key_pair();
+    let native = TokenId::native();
+    let opening = Opening {
+        next_nonce: Nonce(0),
+        balances: BTreeMap::from([(native, balance(1_000))]),
+    };
+
+    let minted = certificate(
+        &minter_key,
+        alice,
+        Nonce(0),
+        ClaimType::Mint(Mint {
+            token_id: USDX,
+            amount: Amount::from(70),
+        }),
+    );
+    let operations = vec![
+        Operation::TokenTransfer(TokenTransferOperation {
+            token_id: USDX,
+            recipient: carol,
+            amount: Amount::from(20),
+            user_data: UserData(None),
+        }),
+        Operation::Mint(MintOperation {
+            token_id: USDX,
+            recipient: carol,
+            amount: Amount::from(5),
+        }),
+        Operation::TokenTransfer(TokenTransferOperation {
+            token_id: native,
+            recipient: bob,
+            amount: Amount::from(100),
+            user_data: UserData(None),
+        }),
+    ];
+    let batch = certificate(
+        &alice_key,
+        bob,
+        Nonce(0),
+        ClaimType::Batch(OperationBundle(operations)),
+    );
+
+    let replay = Replay::new(alice, &opening, [&batch, &minted]);
+    assert!(replay.issues.is_empty(), "{replay:?}");
+    assert_eq!(replay.next_nonce, Nonce(1));
+    assert_eq!(replay.balance(native), balance(900));
+    assert_eq!(replay.balance(USDX), balance(50));
+
+    // The recipients of the operations are credited, even when they are not the recipient of the
+    // transaction, and minting debits no one.
+    let replay = Replay::new(carol, &Opening::default(), [&batch]);
+    assert_eq!(replay.balance(USDX), balance(25));
+    assert_eq!(replay.token_ids(), vec![USDX]);
+    let replay = Replay::new(bob, &Opening::default(), [&batch]);
+    assert_eq!(replay.balance(native), balance(100));
+    let replay = Replay::new(minter, &Opening::default(), [&minted]);
+    assert!(replay.issues.is_empty());
+    assert_eq!(replay.next_nonce, Nonce(1));
+    assert!(replay.balances.is_empty());
+
+    // A batch is applied entirely or not at all.
+    let indebted = Opening {
+        next_nonce: Nonce(0),
+        balances: BTreeMap::from([(native, Balance::min()), (USDX, balance(20))]),
+    };
+    let replay = Replay::new(alice, &indebted, [&batch]);
+    assert_eq!(
+        replay.issues,
+        vec![HistoryIssue::Overflow {
+            token_id: native,
+            nonce: Nonce(0),
+        }]
+    );
+    assert_eq!(replay.balance(USDX), balance(20));
+
+    // A token creation credits a token whose id is not computed, so it is reported.
+    let creation = TokenCreation {
+        token_name: "USDY".to_string(),
+        decimals: 2,
+        initial_amount: Amount::from(1_000),
+        mints: vec![],
+        user_data: UserData(None),
+    };
+    let created = certificate(
+        &alice_key,
+        alice,
+        Nonce(1),
+        ClaimType::Batch(OperationBundle(vec![Operation::TokenCreation(creation)])),
+    );
+    let replay = Replay::new(alice, &opening, [&batch, &created]);
+    assert_eq!(
+        replay.issues,
+        vec![HistoryIssue::UnreplayableClaim {
+            nonce: Nonce(1),
+            claim: "Batch",
+        }]
+    );
+    assert_eq!(replay.next_nonce, Nonce(2));
+}
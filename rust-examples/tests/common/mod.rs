@@ -0,0 +1,31 @@
+use std::time::SystemTime;
+
+use rust_examples::fastset_types::{
+    Amount, ClaimType, FastSetAddress, KeyPair, Nonce, TokenId, TokenTransfer, Transaction,
+    TransactionEnvelope, UserData,
+};
+
+/// A signed transfer of `amount` native tokens.
+pub fn transfer(
+    sender: &KeyPair,
+    recipient: FastSetAddress,
+    nonce: Nonce,
+    amount: u64,
+) -> TransactionEnvelope {
+    let transaction = Transaction {
+        sender: sender.public(),
+        recipient,
+        nonce,
+        timestamp_nanos: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos(),
+        claim: ClaimType::TokenTransfer(TokenTransfer {
+            token_id: TokenId::native(),
+            amount: Amount::from(amount),
+            user_data: UserData(None),
+        }),
+        archival: false,
+    };
+    TransactionEnvelope::new(transaction, sender)
+}
@@ -0,0 +1,145 @@
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use rust_examples::api::faucet_drip;
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::devnet::Devnet;
+use rust_examples::fastset_types::{
+    Amount, Balance, Nonce, TransactionCertificate, TransactionEnvelope, get_key_pair,
+};
+
+mod common;
+use common::transfer;
+
+async fn start(devnet: &Devnet) -> (HttpClient, jsonrpsee::server::ServerHandle) {
+    let (addr, handle) = devnet.proxy().start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, handle)
+}
+
+async fn submit(client: &HttpClient, envelope: &TransactionEnvelope) -> TransactionCertificate {
+    let result = client
+        .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+    let ProxySubmitTransactionResult::Success(certificate) = result else {
+        panic!("unexpected result {result:?}");
+    };
+    certificate
+}
+
+#[tokio::test]
+async fn certificate_has_quorum_and_all_validators_execute() {
+    let devnet = Devnet::new(4);
+    let (client, _handle) = start(&devnet).await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(100))
+        .await
+        .unwrap();
+
+    let certificate = submit(&client, &transfer(&sender_key, recipient, Nonce(0), 30)).await;
+    assert_eq!(certificate.signatures.len(), devnet.committee().quorum);
+    devnet.committee().verify_certificate(&certificate).unwrap();
+
+    for validator in devnet.validators() {
+        let info = validator
+            .account_info(recipient, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.balance, Balance::from(Amount::from(30)));
+        let info = validator
+            .account_info(sender, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.next_nonce, Nonce(1));
+        assert_eq!(info.pending_confirmation, None);
+    }
+}
+
+#[tokio::test]
+async fn settles_with_one_fault_and_fails_without_quorum() {
+    let devnet = Devnet::new(4);
+    let (client, _handle) = start(&devnet).await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(100))
+        .await
+        .unwrap();
+
+    devnet.validators()[0].set_online(false);
+    submit(&client, &transfer(&sender_key, recipient, Nonce(0), 10)).await;
+
+    devnet.validators()[1].set_online(false);
+    let envelope = transfer(&sender_key, recipient, Nonce(1), 10);
+    let result = client
+        .submit_transaction(envelope.transaction, envelope.signature)
+        .await;
+    assert!(result.is_err());
+    for validator in &devnet.validators()[2..] {
+        let info = validator
+            .account_info(sender, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.next_nonce, Nonce(1));
+        assert_eq!(info.balance, Balance::from(Amount::from(90)));
+    }
+}
+
+#[tokio::test]
+async fn lagging_validator_is_caught_up() {
+    let devnet = Devnet::new(4);
+    let (client, _handle) = start(&devnet).await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(100))
+        .await
+        .unwrap();
+
+    let lagging = &devnet.validators()[3];
+    lagging.set_online(false);
+    for nonce in 0..3 {
+        submit(&client, &transfer(&sender_key, recipient, Nonce(nonce), 1)).await;
+    }
+    lagging.set_online(true);
+    let info = lagging
+        .account_info(sender, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(info.next_nonce, Nonce(0));
+
+    submit(&client, &transfer(&sender_key, recipient, Nonce(3), 1)).await;
+    let info = lagging
+        .account_info(sender, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(info.next_nonce, Nonce(4));
+    assert_eq!(info.balance, Balance::from(Amount::from(96)));
+}
+
+#[tokio::test]
+async fn conflicting_transaction_is_not_certified() {
+    let devnet = Devnet::new(4);
+    let (client, _handle) = start(&devnet).await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let (other, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(100))
+        .await
+        .unwrap();
+
+    // Lock the nonce on a quorum of validators without completing the certificate.
+    let first = transfer(&sender_key, recipient, Nonce(0), 10);
+    for validator in &devnet.validators()[..3] {
+        validator.handle_transaction(&first).await.unwrap();
+    }
+    let second = transfer(&sender_key, other, Nonce(0), 10);
+    let result = client
+        .submit_transaction(second.transaction, second.signature)
+        .await;
+    assert!(result.is_err());
+
+    // The original transaction can still be completed.
+    let certificate = submit(&client, &first).await;
+    assert_eq!(certificate.envelope, first);
+}
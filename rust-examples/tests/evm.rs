@@ -0,0 +1,139 @@
+use k256::ecdsa::SigningKey;
+use rust_examples::evm::{
+    CrossSignError, EvmAddress, EvmAddressError, eip191_digest, recover_signer,
+};
+use rust_examples::fastset_types::CrossSignResponse;
+
+fn proxy_key() -> SigningKey {
+    let mut secret = [0; 32];
+    secret[31] = 1;
+    SigningKey::from_slice(&secret).unwrap()
+}
+
+/// Sign `transaction` the way the proxy does, with `v` as 27 or 28.
+fn cross_sign(key: &SigningKey, transaction: Vec<u8>) -> CrossSignResponse {
+    let (signature, recovery_id) = key
+        .sign_prehash_recoverable(&eip191_digest(&transaction))
+        .unwrap();
+    let mut bytes = signature.to_vec();
+    bytes.push(27 + recovery_id.to_byte());
+    CrossSignResponse {
+        format: "eip191-abi".to_string(),
+        signature: format!("0x{}", hex::encode(bytes)),
+        transaction,
+    }
+}
+
+#[test]
+fn addresses_and_digests_match_ethereum() {
+    // The address of the secret key 1, and the `hashMessage("hello world")` of ethers.
+    let address = EvmAddress::from_public_key(proxy_key().verifying_key());
+    assert_eq!(
+        address.to_string(),
+        "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+    );
+    assert_eq!(
+        hex::encode(eip191_digest(b"hello world")),
+        "d9eba16ed0ecae432b71fe008c98cc872bb4cc214d3220a36f365326cf807d68"
+    );
+
+    for s in [
+        "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf",
+        "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf",
+        "0x7E5F4552091A69125D5DFCB7B8C2659029395BDF",
+    ] {
+        assert_eq!(s.parse::<EvmAddress>().unwrap(), address);
+    }
+    assert!(matches!(
+        "0x7E5F4552091A69125d5DfCb7b8C2659029395BDF".parse::<EvmAddress>(),
+        Err(EvmAddressError::Checksum(_))
+    ));
+    for s in ["7e5f4552091a69125d5dfcb7b8c2659029395bdf", "0x7e5f", "0x"] {
+        assert!(matches!(
+            s.parse::<EvmAddress>(),
+            Err(EvmAddressError::Malformed)
+        ));
+    }
+}
+
+#[test]
+fn responses_signed_by_the_trusted_proxy_are_accepted() {
+    let key = proxy_key();
+    let trusted = EvmAddress::from_public_key(key.verifying_key());
+    let response = cross_sign(&key, vec![0xab; 320]);
+    assert_eq!(response.recover_signer().unwrap(), trusted);
+    response.verify(&trusted).unwrap();
+
+    // `v` may also be given as 0 or 1, and the signature without `0x`.
+    let mut signature = hex::decode(&response.signature[2..]).unwrap();
+    signature[64] -= 27;
+    let response = CrossSignResponse {
+        signature: hex::encode(signature),
+        ..response
+    };
+    response.verify(&trusted).unwrap();
+}
+
+#[test]
+fn responses_not_signed_by_the_trusted_proxy_are_rejected() {
+    let key = proxy_key();
+    let trusted = EvmAddress::from_public_key(key.verifying_key());
+    let response = cross_sign(&key, vec![0xab; 320]);
+
+    let other = SigningKey::from_slice(&[0x42; 32]).unwrap();
+    let forged = cross_sign(&other, response.transaction.clone());
+    assert!(matches!(
+        forged.verify(&trusted),
+        Err(CrossSignError::UntrustedSigner { signer, .. })
+            if signer == EvmAddress::from_public_key(other.verifying_key())
+    ));
+
+    let mut tampered = response.clone();
+    tampered.transaction[0] ^= 1;
+    assert!(tampered.verify(&trusted).is_err());
+
+    let unknown = CrossSignResponse {
+        format: "eip712".to_string(),
+        ..response.clone()
+    };
+    assert!(matches!(
+        unknown.verify(&trusted),
+        Err(CrossSignError::UnsupportedFormat(format)) if format == "eip712"
+    ));
+}
+
+#[test]
+fn malformed_signatures_are_rejected() {
+    let key = proxy_key();
+    let digest = eip191_digest(b"certificate");
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&digest).unwrap();
+    let mut valid = signature.to_vec();
+    valid.push(recovery_id.to_byte());
+    assert!(recover_signer(&digest, &valid).is_ok());
+
+    let mut bad_v = valid.clone();
+    bad_v[64] = 29;
+    let mut high_s = signature.r().to_bytes().to_vec();
+    high_s.extend_from_slice(&(-*signature.s()).to_bytes());
+    high_s.push(1 - recovery_id.to_byte());
+    for signature in [&valid[..64], &[], &bad_v, &high_s] {
+        assert!(
+            matches!(
+                recover_signer(&digest, signature),
+                Err(CrossSignError::MalformedSignature(_))
+            ),
+            "{}",
+            hex::encode(signature)
+        );
+    }
+
+    let response = CrossSignResponse {
+        format: "eip191-abi".to_string(),
+        signature: "0xnot hex".to_string(),
+        transaction: Vec::new(),
+    };
+    assert!(matches!(
+        response.recover_signer(),
+        Err(CrossSignError::MalformedSignature(_))
+    ));
+}
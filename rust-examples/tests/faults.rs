@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use rust_examples::api::faucet_drip;
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::committee::CertificateError;
+use rust_examples::devnet::{Devnet, Scenario, ScenarioError};
+use rust_examples::fastset_types::{Amount, Balance, KeyPair, Nonce, get_key_pair};
+use rust_examples::retry::{
+    RetryConfig, SubmitError, SubmitOutcome, submit_transaction_with_retry,
+};
+
+mod common;
+use common::transfer;
+
+/// Set up the devnet described by `tests/scenarios/<name>.json`.
+fn scenario(name: &str) -> Devnet {
+    let path = format!("{}/tests/scenarios/{name}.json", env!("CARGO_MANIFEST_DIR"));
+    let scenario: Scenario = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    Devnet::from_scenario(&scenario).unwrap()
+}
+
+async fn start(
+    devnet: &Devnet,
+    request_timeout: Duration,
+) -> (HttpClient, jsonrpsee::server::ServerHandle) {
+    let (addr, handle) = devnet.proxy().start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .request_timeout(request_timeout)
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, handle)
+}
+
+async fn funded_sender(client: &HttpClient) -> KeyPair {
+    let (sender, sender_key) = get_key_pair();
+    faucet_drip(client, sender, Amount::from(100))
+        .await
+        .unwrap();
+    sender_key
+}
+
+fn fast_retries() -> RetryConfig {
+    RetryConfig {
+        initial_backoff: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(200),
+        ..RetryConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn single_equivocator_cannot_certify_a_conflict() {
+    let devnet = scenario("equivocation");
+    let (client, _handle) = start(&devnet, Duration::from_secs(5)).await;
+    let sender_key = funded_sender(&client).await;
+    let (recipient, _) = get_key_pair();
+    let (other, _) = get_key_pair();
+    let validators = devnet.validators();
+
+    let first = transfer(&sender_key, recipient, Nonce(0), 10);
+    for validator in &validators[1..3] {
+        validator.handle_transaction(&first).await.unwrap();
+    }
+    let second = transfer(&sender_key, other, Nonce(0), 10);
+    let result = client
+        .submit_transaction(second.transaction.clone(), second.signature.clone())
+        .await;
+    assert!(result.is_err());
+
+    // The equivocator signs both transactions, and both votes verify.
+    let committee = devnet.committee();
+    for envelope in [&first, &second] {
+        let vote = validators[0].handle_transaction(envelope).await.unwrap();
+        committee.verify_vote(&vote, envelope).unwrap();
+    }
+
+    let outcome = submit_transaction_with_retry(&client, &first, &fast_retries())
+        .await
+        .unwrap();
+    let SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(certificate)) = outcome
+    else {
+        panic!("unexpected outcome {outcome:?}");
+    };
+    committee.verify_certificate(&certificate).unwrap();
+    assert_eq!(certificate.envelope, first);
+}
+
+#[tokio::test]
+async fn invalid_votes_are_left_out_of_certificates() {
+    let devnet = scenario("invalid_signatures");
+    let (client, _handle) = start(&devnet, Duration::from_secs(5)).await;
+    let sender_key = funded_sender(&client).await;
+    let (recipient, _) = get_key_pair();
+
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    let outcome = submit_transaction_with_retry(&client, &envelope, &fast_retries())
+        .await
+        .unwrap();
+    let SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(certificate)) = outcome
+    else {
+        panic!("unexpected outcome {outcome:?}");
+    };
+    devnet.committee().verify_certificate(&certificate).unwrap();
+    let byzantine = devnet.validators()[0].name();
+    assert!(
+        certificate
+            .signatures
+            .iter()
+            .all(|(name, _)| *name != byzantine)
+    );
+}
+
+#[tokio::test]
+async fn certificate_with_invalid_vote_is_rejected() {
+    let devnet = scenario("faulty_proxy");
+    let (client, _handle) = start(&devnet, Duration::from_secs(5)).await;
+    let sender_key = funded_sender(&client).await;
+    let (recipient, _) = get_key_pair();
+
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    let result = client
+        .submit_transaction(envelope.transaction, envelope.signature)
+        .await
+        .unwrap();
+    let ProxySubmitTransactionResult::Success(certificate) = result else {
+        panic!("unexpected result {result:?}");
+    };
+    assert_eq!(
+        devnet.committee().verify_certificate(&certificate),
+        Err(CertificateError::InvalidSignature(
+            devnet.validators()[0].name()
+        ))
+    );
+    // Honest validators refuse to execute it.
+    for validator in &devnet.validators()[1..] {
+        let info = validator
+            .account_info(recipient, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.balance, Balance::default());
+    }
+}
+
+#[tokio::test]
+async fn settles_despite_dropped_and_delayed_messages() {
+    let devnet = scenario("unresponsive");
+    let (client, _handle) = start(&devnet, Duration::from_secs(5)).await;
+    let sender_key = funded_sender(&client).await;
+    let (recipient, _) = get_key_pair();
+
+    for nonce in 0..2 {
+        let envelope = transfer(&sender_key, recipient, Nonce(nonce), 10);
+        let outcome = submit_transaction_with_retry(&client, &envelope, &fast_retries())
+            .await
+            .unwrap();
+        assert!(matches!(
+            outcome,
+            SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(_))
+        ));
+    }
+    let info = devnet.validators()[1]
+        .account_info(recipient, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(info.balance, Balance::from(Amount::from(20)));
+}
+
+#[tokio::test]
+async fn client_timeout_is_resolved_without_paying_twice() {
+    let devnet = scenario("slow_network");
+    // Settling takes a vote round and a confirmation round, reads take one round.
+    let (client, _handle) = start(&devnet, Duration::from_millis(450)).await;
+    let sender_key = funded_sender(&client).await;
+    let (recipient, _) = get_key_pair();
+
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    let outcome = submit_transaction_with_retry(&client, &envelope, &fast_retries())
+        .await
+        .unwrap();
+    let SubmitOutcome::AlreadySettled(certificate) = outcome else {
+        panic!("unexpected outcome {outcome:?}");
+    };
+    devnet.committee().verify_certificate(&certificate).unwrap();
+    for validator in devnet.validators() {
+        let info = validator
+            .account_info(recipient, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.balance, Balance::from(Amount::from(10)));
+    }
+}
+
+#[tokio::test]
+async fn stale_reads_never_cause_a_second_payment() {
+    let devnet = scenario("stale_reads");
+    let (client, _handle) = start(&devnet, Duration::from_secs(5)).await;
+    let sender_key = funded_sender(&client).await;
+    let (recipient, _) = get_key_pair();
+
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    client
+        .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+    let stale = devnet.validators()[0]
+        .account_info(recipient, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(stale.balance, Balance::default());
+
+    // Whichever validator answers the status lookups, resubmitting is either recognized as
+    // settled or rejected, and never executed again.
+    for _ in 0..devnet.validators().len() {
+        match submit_transaction_with_retry(&client, &envelope, &fast_retries()).await {
+            Ok(SubmitOutcome::AlreadySettled(_)) | Err(SubmitError::Rejected(_)) => {}
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+    for validator in &devnet.validators()[1..] {
+        let info = validator
+            .account_info(recipient, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.balance, Balance::from(Amount::from(10)));
+    }
+}
+
+#[test]
+fn scenarios_with_unknown_validators_are_rejected() {
+    let scenario = |json| serde_json::from_str::<Scenario>(json).unwrap();
+    assert_eq!(
+        Devnet::from_scenario(&scenario(r#"{ "validators": 0 }"#)).err(),
+        Some(ScenarioError::NoValidators)
+    );
+    assert_eq!(
+        Devnet::from_scenario(&scenario(
+            r#"{ "validators": 4, "faults": { "4": { "equivocate": true } } }"#
+        ))
+        .err(),
+        Some(ScenarioError::UnknownValidator {
+            index: 4,
+            validators: 4
+        })
+    );
+}
@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::server::ServerHandle;
+use rust_examples::api::faucet_drip;
+use rust_examples::client::ProxyRpcClient;
+use rust_examples::fastset_types::{Amount, Nonce, get_key_pair};
+use rust_examples::finality::{SettleConfig, SettleError, await_settled};
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::multi_proxy::{FailoverConfig, MultiProxyClient};
+
+mod common;
+use common::transfer;
+
+async fn start(proxy: MockProxy) -> (String, HttpClient, ServerHandle) {
+    let (addr, handle) = proxy.start().await.unwrap();
+    let url = format!("http://{addr}");
+    let client = HttpClientBuilder::default().build(&url).unwrap();
+    (url, client, handle)
+}
+
+fn config(confirmations: usize, timeout: Duration) -> SettleConfig {
+    SettleConfig {
+        confirmations,
+        timeout,
+        period: Duration::from_millis(20),
+    }
+}
+
+#[tokio::test]
+async fn settled_transactions_are_confirmed_by_enough_proxies() {
+    // Two proxies backed by separate ledgers, the second one lagging behind the first.
+    let (url, client, _handle) = start(MockProxy::new(1)).await;
+    let (lagging_url, lagging, _lagging_handle) = start(MockProxy::new(1)).await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 100);
+    faucet_drip(&client, sender, Amount::from(1_000))
+        .await
+        .unwrap();
+    client
+        .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+
+    let clients = [client, lagging.clone()];
+    let certificate = await_settled(
+        &clients,
+        sender,
+        Nonce(0),
+        &config(1, Duration::from_secs(5)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(certificate.envelope, envelope);
+
+    assert!(matches!(
+        await_settled(
+            &clients,
+            sender,
+            Nonce(0),
+            &config(2, Duration::from_millis(100))
+        )
+        .await,
+        Err(SettleError::Timeout {
+            confirmations: 1,
+            required: 2,
+            ..
+        })
+    ));
+    assert!(matches!(
+        await_settled(
+            &clients,
+            sender,
+            Nonce(0),
+            &config(3, Duration::from_secs(5))
+        )
+        .await,
+        Err(SettleError::NotEnoughProxies {
+            required: 3,
+            available: 2
+        })
+    ));
+
+    // The lagging proxy catches up while the client is waiting.
+    let catch_up = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        faucet_drip(&lagging, sender, Amount::from(1_000))
+            .await
+            .unwrap();
+        lagging
+            .submit_transaction(envelope.transaction, envelope.signature)
+            .await
+            .unwrap();
+    });
+    let multi = MultiProxyClient::new(&[url, lagging_url], FailoverConfig::default()).unwrap();
+    let certificate = multi
+        .await_settled(sender, Nonce(0), &config(2, Duration::from_secs(5)))
+        .await
+        .unwrap();
+    assert_eq!(certificate.envelope.transaction.nonce, Nonce(0));
+    catch_up.await.unwrap();
+}
+
+#[tokio::test]
+async fn unresponsive_proxies_do_not_delay_the_timeout() {
+    // Accepts connections but never answers them.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((connection, _)) = listener.accept().await {
+            connections.push(connection);
+        }
+    });
+    let unresponsive = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    let (_, client, _handle) = start(MockProxy::new(1)).await;
+    let (sender, _) = get_key_pair();
+
+    let start = tokio::time::Instant::now();
+    let result = await_settled(
+        &[unresponsive, client],
+        sender,
+        Nonce(0),
+        &config(1, Duration::from_millis(300)),
+    )
+    .await;
+    let elapsed = start.elapsed();
+    assert!(
+        matches!(
+            result,
+            Err(SettleError::Timeout {
+                confirmations: 0,
+                last_error: Some(_),
+                ..
+            })
+        ),
+        "{result:?}"
+    );
+    assert!(elapsed >= Duration::from_millis(300), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use jsonrpsee::http_client::HttpClientBuilder;
+use rust_examples::api::faucet_drip;
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::fastset_types::{
+    Amount, FastSetError, Nonce, TokenId, TransactionEnvelope, get_key_pair,
+};
+use rust_examples::inspect::{
+    DecodeError, Payload, PayloadKind, SignerRole, TokenLabel, describe, format_timestamp,
+    token_labels,
+};
+use rust_examples::mock_proxy::MockProxy;
+
+mod common;
+use common::transfer;
+
+#[test]
+fn envelope_decodes_from_bcs_hex_and_json() {
+    let (_, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let envelope = transfer(&sender_key, recipient, Nonce(4), 1500);
+    let expected = Payload::Envelope(envelope.clone());
+
+    let hex = format!("0x{}", hex::encode(bcs::to_bytes(&envelope).unwrap()));
+    assert_eq!(Payload::decode(&hex, None).unwrap(), expected);
+    let json = serde_json::to_string(&envelope).unwrap();
+    assert_eq!(Payload::decode(&json, None).unwrap(), expected);
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "proxy_submitTransaction",
+        "params": envelope,
+    });
+    assert_eq!(
+        Payload::decode(&request.to_string(), None).unwrap(),
+        expected
+    );
+
+    let transaction = hex::encode(bcs::to_bytes(&envelope.transaction).unwrap());
+    assert_eq!(
+        Payload::decode(&transaction, None).unwrap(),
+        Payload::Transaction(envelope.transaction.clone())
+    );
+    assert!(matches!(
+        Payload::decode(&hex, Some(PayloadKind::Transaction)),
+        Err(DecodeError::Invalid {
+            kind: PayloadKind::Transaction,
+            ..
+        })
+    ));
+    assert!(matches!(
+        Payload::decode("not a payload", None),
+        Err(DecodeError::UnknownFormat)
+    ));
+
+    let checks = expected.check_signatures();
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].role, SignerRole::Sender);
+    assert_eq!(checks[0].result, Ok(()));
+}
+
+#[tokio::test]
+async fn certificate_description_reports_tokens_and_signatures() {
+    let (addr, _handle) = MockProxy::new(3).start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(10_000))
+        .await
+        .unwrap();
+    let mut envelope = transfer(&sender_key, recipient, Nonce(0), 1250);
+    envelope.transaction.timestamp_nanos = 1_700_000_000_123_456_789;
+    envelope = TransactionEnvelope::new(envelope.transaction, &sender_key);
+    let result = client
+        .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+    let ProxySubmitTransactionResult::Success(mut certificate) = result else {
+        panic!("unexpected result {result:?}");
+    };
+    certificate.signatures[1].1.0[0] ^= 1;
+    let response = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": { "Success": certificate },
+    });
+    let payload = Payload::decode(&response.to_string(), None).unwrap();
+    assert_eq!(payload, Payload::Certificate(certificate.clone()));
+
+    let results: Vec<_> = payload
+        .check_signatures()
+        .into_iter()
+        .map(|check| (check.role, check.result))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            (SignerRole::Sender, Ok(())),
+            (SignerRole::Validator, Ok(())),
+            (SignerRole::Validator, Err(FastSetError::InvalidSignature)),
+            (SignerRole::Validator, Ok(())),
+        ]
+    );
+
+    let labels = token_labels(&client, payload.token_ids(), 2).await.unwrap();
+    assert_eq!(
+        labels,
+        BTreeMap::from([(
+            TokenId::native(),
+            TokenLabel {
+                name: "SET".to_string(),
+                decimals: 2
+            }
+        )])
+    );
+    let description = describe(&payload, &labels).to_string();
+    assert!(description.starts_with("certificate\n"), "{description}");
+    assert!(
+        description.contains(&format!("  sender:    {sender}\n")),
+        "{description}"
+    );
+    assert!(
+        description.contains("  timestamp: 2023-11-14T22:13:20.123456789Z"),
+        "{description}"
+    );
+    assert!(
+        description.contains("    amount:    12.5 SET (1250 least units)\n"),
+        "{description}"
+    );
+    let invalid = certificate.signatures[1].0;
+    assert!(
+        description.contains(&format!(
+            "    validator {invalid}: unverified (does not match the assumed convention: Invalid \
+             signature.)\n"
+        )),
+        "{description}"
+    );
+    assert_eq!(description.matches(": valid\n").count(), 1);
+    assert_eq!(
+        description
+            .matches(": unverified (matches the assumed convention)\n")
+            .count(),
+        2
+    );
+    assert!(description.contains("  note: the proxy API does not document how validators sign"));
+}
+
+#[test]
+fn timestamps_beyond_rfc3339_are_not_formatted() {
+    assert_eq!(
+        format_timestamp(0).as_deref(),
+        Some("1970-01-01T00:00:00.000000000Z")
+    );
+    assert_eq!(format_timestamp(u128::MAX), None);
+}
@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use jsonrpsee::RpcModule;
+use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::server::Server;
+use jsonrpsee::types::ErrorObjectOwned;
+use rust_examples::client::ProxySubmitTransactionResult;
+use rust_examples::fastset_types::{
+    AccountInfoResponse, Amount, Balance, FastSetAddress, Nonce, SignatureOrMultiSig, Transaction,
+    TransactionCertificate, TransactionEnvelope,
+};
+use rust_examples::load::{LATENCY_BUCKETS_MS, LatencySummary, LoadConfig, LoadError, run_load};
+use rust_examples::mock_proxy::MockProxy;
+use serde::Deserialize;
+
+#[tokio::test]
+async fn load_reports_latencies_errors_and_throughput() {
+    let (addr, _handle) = MockProxy::new(4).start().await.unwrap();
+    let client = Arc::new(
+        HttpClientBuilder::default()
+            .build(format!("http://{addr}"))
+            .unwrap(),
+    );
+
+    let config = LoadConfig {
+        accounts: 4,
+        concurrency: 2,
+        transfers: 40,
+        ..LoadConfig::default()
+    };
+    let report = run_load(client.clone(), config).await.unwrap();
+    assert_eq!(report.submitted, 40);
+    assert_eq!(report.settled, 40);
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert!(report.throughput > 0.0);
+    let latency = &report.settled_latency;
+    assert_eq!(
+        latency
+            .histogram
+            .iter()
+            .map(|bucket| bucket.count)
+            .sum::<usize>(),
+        40
+    );
+    assert!(latency.min <= latency.p50 && latency.p50 <= latency.p99);
+    assert!(latency.p99 <= latency.max);
+
+    // Unfunded accounts cannot pay, and the rate caps how fast they try.
+    let config = LoadConfig {
+        accounts: 2,
+        concurrency: 2,
+        transfers: 6,
+        rate: Some(50.0),
+        funding: Amount::from(0),
+        amount: Amount::from(10),
+    };
+    let report = run_load(client, config).await.unwrap();
+    assert_eq!(report.submitted, 6);
+    assert_eq!(report.settled, 0);
+    assert_eq!(report.errors.values().sum::<usize>(), 6);
+    assert!(report.errors.keys().all(|class| class.starts_with("call:")));
+    assert!(report.elapsed_secs >= 0.1);
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["latency"]["histogram"][0]["le_ms"], 1);
+    assert_eq!(
+        json["latency"]["histogram"][12]["le_ms"],
+        serde_json::Value::Null
+    );
+}
+
+#[tokio::test]
+async fn unusable_rates_are_rejected() {
+    let (addr, _handle) = MockProxy::new(1).start().await.unwrap();
+    let client = Arc::new(
+        HttpClientBuilder::default()
+            .build(format!("http://{addr}"))
+            .unwrap(),
+    );
+    for rate in [f64::INFINITY, f64::NAN, 0.0, -1.0, 1e10, f64::MIN_POSITIVE] {
+        let config = LoadConfig {
+            rate: Some(rate),
+            ..LoadConfig::default()
+        };
+        assert!(config.period().is_err(), "{rate}");
+        assert!(matches!(
+            run_load(client.clone(), config).await,
+            Err(LoadError::InvalidRate(_))
+        ));
+    }
+    let config = LoadConfig {
+        rate: Some(1e9),
+        ..LoadConfig::default()
+    };
+    assert_eq!(config.period().unwrap(), Some(Duration::from_nanos(1)));
+}
+
+#[test]
+fn latencies_are_bucketed_with_their_fractions() {
+    let latencies = [1_000, 1_001, 1_500, 2_000, 5_000_001].map(Duration::from_micros);
+    let summary = LatencySummary::new(latencies.to_vec());
+    let counts: Vec<_> = summary
+        .histogram
+        .iter()
+        .map(|bucket| bucket.count)
+        .collect();
+    let mut expected = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+    expected[0] = 1;
+    expected[1] = 3;
+    expected[LATENCY_BUCKETS_MS.len()] = 1;
+    assert_eq!(counts, expected);
+}
+
+#[tokio::test]
+async fn failed_transfers_are_resubmitted_unchanged() {
+    // A proxy that fails the first submission of every transaction, and settles it when it is
+    // submitted again.
+    let submitted: Arc<Mutex<Vec<Transaction>>> = Arc::default();
+    let mut module = RpcModule::new(submitted.clone());
+    module
+        .register_method("proxy_faucetDrip", |_, _, _| Ok::<_, ErrorObjectOwned>(()))
+        .unwrap();
+    module
+        .register_method("proxy_getAccountInfo", |params, submitted, _| {
+            #[derive(Deserialize)]
+            struct Params {
+                address: FastSetAddress,
+            }
+            let Params { address } = params.parse()?;
+            let submitted = submitted.lock().unwrap();
+            let settled = submitted
+                .iter()
+                .enumerate()
+                .filter(|(index, transaction)| {
+                    transaction.sender == address && submitted[..*index].contains(transaction)
+                })
+                .count();
+            Ok::<_, ErrorObjectOwned>(AccountInfoResponse {
+                sender: address,
+                balance: Balance::from(Amount::from(1_000)),
+                next_nonce: Nonce(settled as u64),
+                pending_confirmation: None,
+                requested_state: Vec::new(),
+                requested_certificates: None,
+                requested_validated_transaction: None,
+                token_balance: Vec::new(),
+            })
+        })
+        .unwrap();
+    module
+        .register_method("proxy_submitTransaction", |params, submitted, _| {
+            #[derive(Deserialize)]
+            struct Params {
+                transaction: Transaction,
+                signature: SignatureOrMultiSig,
+            }
+            let Params {
+                transaction,
+                signature,
+            } = params.parse()?;
+            let mut submitted = submitted.lock().unwrap();
+            let resubmitted = submitted.contains(&transaction);
+            submitted.push(transaction.clone());
+            if !resubmitted {
+                return Err(ErrorObjectOwned::owned(
+                    -32000,
+                    "validator timeout",
+                    None::<()>,
+                ));
+            }
+            Ok(ProxySubmitTransactionResult::Success(
+                TransactionCertificate {
+                    envelope: TransactionEnvelope {
+                        transaction,
+                        signature,
+                    },
+                    signatures: Vec::new(),
+                },
+            ))
+        })
+        .unwrap();
+    let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    let _handle = server.start(module);
+    let client = Arc::new(
+        HttpClientBuilder::default()
+            .build(format!("http://{addr}"))
+            .unwrap(),
+    );
+
+    let config = LoadConfig {
+        accounts: 2,
+        concurrency: 1,
+        transfers: 4,
+        ..LoadConfig::default()
+    };
+    let report = run_load(client, config).await.unwrap();
+    assert_eq!(report.submitted, 4);
+    assert_eq!(report.settled, 2);
+    let submitted = submitted.lock().unwrap();
+    assert_eq!(submitted[2], submitted[0]);
+    assert_eq!(submitted[3], submitted[1]);
+}
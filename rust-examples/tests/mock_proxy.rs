@@ -0,0 +1,307 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use jsonrpsee::http_client::HttpClientBuilder;
+use rust_examples::api::{connect_ws, faucet_drip, get_account_info};
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::fastset_types::{
+    Amount, Balance, Nonce, Signature, TokenId, ValidatedTransaction, get_key_pair,
+};
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::retry::{RetryConfig, SubmitOutcome, submit_transaction_with_retry};
+use rust_examples::subscription::{
+    AccountChange, AccountEvent, account_changes, account_events, account_updates,
+    settled_certificates,
+};
+
+mod common;
+use common::transfer;
+
+#[tokio::test]
+async fn transfer_flow_settles_on_mock_proxy() {
+    let proxy = MockProxy::new(4);
+    let committee = proxy.committee();
+    let (addr, _handle) = proxy.start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(1_000_000))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_account_info(&client, sender).await.balance,
+        Balance::from(Amount::from(1_000_000))
+    );
+
+    let nonce = get_account_info(&client, sender).await.next_nonce;
+    let envelope = transfer(&sender_key, recipient, nonce, 65535);
+    let outcome = submit_transaction_with_retry(&client, &envelope, &RetryConfig::default())
+        .await
+        .unwrap();
+    let SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(certificate)) = outcome
+    else {
+        panic!("unexpected outcome {outcome:?}");
+    };
+    assert_eq!(certificate.envelope, envelope);
+    assert_eq!(certificate.signatures.len(), committee.len());
+    for (validator, signature) in &certificate.signatures {
+        assert!(committee.contains(validator));
+        signature.verify(&envelope.transaction, validator).unwrap();
+    }
+
+    let sender_info = get_account_info(&client, sender).await;
+    assert_eq!(sender_info.next_nonce, Nonce(1));
+    assert_eq!(
+        sender_info.balance,
+        Balance::from(Amount::from(1_000_000 - 65535))
+    );
+    assert_eq!(
+        get_account_info(&client, recipient).await.balance,
+        Balance::from(Amount::from(65535))
+    );
+
+    // Replaying the envelope must not move funds a second time.
+    assert!(
+        client
+            .submit_transaction(envelope.transaction, envelope.signature)
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        get_account_info(&client, recipient).await.balance,
+        Balance::from(Amount::from(65535))
+    );
+}
+
+#[tokio::test]
+async fn rejects_overdraft_and_bad_signature() {
+    let (addr, _handle) = MockProxy::new(1).start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, recipient_key) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(10))
+        .await
+        .unwrap();
+
+    let overdraft = transfer(&sender_key, recipient, Nonce(0), 11);
+    assert!(
+        client
+            .submit_transaction(overdraft.transaction, overdraft.signature)
+            .await
+            .is_err()
+    );
+
+    let mut forged = transfer(&recipient_key, recipient, Nonce(0), 5);
+    forged.transaction.sender = sender;
+    assert!(
+        client
+            .submit_transaction(forged.transaction, forged.signature)
+            .await
+            .is_err()
+    );
+    assert_eq!(get_account_info(&client, sender).await.next_nonce, Nonce(0));
+}
+
+#[tokio::test]
+async fn subscriptions_over_websocket_and_polling() {
+    let (addr, _handle) = MockProxy::new(1).start().await.unwrap();
+    let ws = Arc::new(connect_ws(&format!("ws://{addr}")).await.unwrap());
+    let http = Arc::new(
+        HttpClientBuilder::default()
+            .build(format!("http://{addr}"))
+            .unwrap(),
+    );
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let period = Duration::from_millis(20);
+
+    let mut ws_accounts = account_updates(ws.clone(), recipient, None, period)
+        .await
+        .unwrap();
+    let mut polled_accounts = account_updates(http.clone(), recipient, None, period)
+        .await
+        .unwrap();
+    let mut ws_certificates = settled_certificates(ws.clone(), sender, None, period)
+        .await
+        .unwrap();
+    let mut polled_certificates = settled_certificates(http.clone(), sender, None, period)
+        .await
+        .unwrap();
+    assert_eq!(
+        ws_accounts.next().await.unwrap().unwrap().balance,
+        Balance::default()
+    );
+    assert_eq!(
+        polled_accounts.next().await.unwrap().unwrap().balance,
+        Balance::default()
+    );
+
+    faucet_drip(http.as_ref(), sender, Amount::from(100))
+        .await
+        .unwrap();
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 42);
+    http.submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+
+    let expected = Balance::from(Amount::from(42));
+    assert_eq!(ws_accounts.next().await.unwrap().unwrap().balance, expected);
+    assert_eq!(
+        polled_accounts.next().await.unwrap().unwrap().balance,
+        expected
+    );
+    assert_eq!(
+        ws_certificates.next().await.unwrap().unwrap().envelope,
+        envelope
+    );
+    assert_eq!(
+        polled_certificates.next().await.unwrap().unwrap().envelope,
+        envelope
+    );
+}
+
+#[tokio::test]
+async fn account_events_report_each_change_once() {
+    let (addr, _handle) = MockProxy::new(1).start().await.unwrap();
+    let client = Arc::new(
+        HttpClientBuilder::default()
+            .build(format!("http://{addr}"))
+            .unwrap(),
+    );
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let mut events = account_events(
+        client.clone(),
+        vec![sender, recipient],
+        None,
+        Duration::from_millis(20),
+    )
+    .await
+    .unwrap();
+    let balance = |amount| Balance::from(Amount::from(amount));
+
+    faucet_drip(client.as_ref(), sender, Amount::from(100))
+        .await
+        .unwrap();
+    assert_eq!(
+        events.next().await.unwrap().unwrap(),
+        AccountEvent {
+            address: sender,
+            change: AccountChange::BalanceChanged {
+                before: balance(0),
+                after: balance(100)
+            }
+        }
+    );
+
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 42);
+    client
+        .submit_transaction(envelope.transaction, envelope.signature)
+        .await
+        .unwrap();
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        received.push(events.next().await.unwrap().unwrap());
+    }
+    // The accounts may be polled on either side of the settlement, so the order varies.
+    for expected in [
+        AccountEvent {
+            address: sender,
+            change: AccountChange::BalanceChanged {
+                before: balance(100),
+                after: balance(58),
+            },
+        },
+        AccountEvent {
+            address: sender,
+            change: AccountChange::NonceAdvanced {
+                before: Nonce(0),
+                after: Nonce(1),
+            },
+        },
+        AccountEvent {
+            address: recipient,
+            change: AccountChange::BalanceChanged {
+                before: balance(0),
+                after: balance(42),
+            },
+        },
+    ] {
+        assert!(
+            received.contains(&expected),
+            "{expected:?} not in {received:?}"
+        );
+    }
+    // Later polls see the same states.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), events.next())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn account_changes_cover_tokens_and_pending_confirmations() {
+    let (addr, _handle) = MockProxy::new(1).start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    let (sender, sender_key) = get_key_pair();
+    let before = get_account_info(&client, sender).await;
+    let (usdx, eurx) = (TokenId([1; 32]), TokenId([2; 32]));
+    let balance = |amount| Balance::from(Amount::from(amount));
+
+    let envelope = transfer(&sender_key, sender, Nonce(0), 1);
+    let pending = ValidatedTransaction {
+        signature: Signature::new(&envelope.transaction, &sender_key),
+        value: envelope,
+        validator: sender,
+    };
+    let mut after = before.clone();
+    after.token_balance = vec![(eurx, balance(5)), (usdx, balance(7))];
+    after.pending_confirmation = Some(pending.clone());
+    assert_eq!(
+        account_changes(&before, &after),
+        vec![
+            AccountChange::TokenBalanceChanged {
+                token_id: usdx,
+                before: balance(0),
+                after: balance(7)
+            },
+            AccountChange::TokenBalanceChanged {
+                token_id: eurx,
+                before: balance(0),
+                after: balance(5)
+            },
+            AccountChange::PendingConfirmationChanged {
+                before: None,
+                after: Some(Box::new(pending.clone()))
+            },
+        ]
+    );
+
+    let mut cleared = after.clone();
+    cleared.token_balance = vec![(usdx, balance(7))];
+    cleared.pending_confirmation = None;
+    assert_eq!(
+        account_changes(&after, &cleared),
+        vec![
+            AccountChange::TokenBalanceChanged {
+                token_id: eurx,
+                before: balance(5),
+                after: balance(0)
+            },
+            AccountChange::PendingConfirmationChanged {
+                before: Some(Box::new(pending)),
+                after: None
+            },
+        ]
+    );
+    assert!(account_changes(&cleared, &cleared).is_empty());
+}
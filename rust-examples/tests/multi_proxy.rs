@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use jsonrpsee::RpcModule;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::server::{Server, ServerHandle};
+use jsonrpsee::types::ErrorObjectOwned;
+use rust_examples::api::faucet_drip;
+use rust_examples::client::ProxyRpcClient;
+use rust_examples::fastset_types::{
+    AccountInfoResponse, Amount, Balance, Nonce, TokenId, get_key_pair,
+};
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::multi_proxy::{ConsistencyError, FailoverConfig, MultiProxyClient};
+
+mod common;
+use common::transfer;
+
+async fn start() -> (String, HttpClient, ServerHandle) {
+    let (addr, handle) = MockProxy::new(1).start().await.unwrap();
+    let url = format!("http://{addr}");
+    let client = HttpClientBuilder::default().build(&url).unwrap();
+    (url, client, handle)
+}
+
+/// A proxy that always answers `info` to account lookups.
+async fn start_canned(info: AccountInfoResponse) -> (String, ServerHandle) {
+    let mut module = RpcModule::new(info);
+    module
+        .register_method("proxy_getAccountInfo", |_, info, _| {
+            Ok::<_, ErrorObjectOwned>(info.clone())
+        })
+        .unwrap();
+    let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    (format!("http://{addr}"), server.start(module))
+}
+
+/// The URL of a port nothing listens on.
+fn down_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+#[tokio::test]
+async fn requests_fail_over_and_skip_unhealthy_endpoints_until_cooldown() {
+    let down = down_url();
+    let (url, client, _handle) = start().await;
+    let (address, _) = get_key_pair();
+    faucet_drip(&client, address, Amount::from(100))
+        .await
+        .unwrap();
+    let config = FailoverConfig {
+        failure_threshold: 2,
+        cooldown: Duration::from_millis(200),
+    };
+    let multi = MultiProxyClient::new(&[down.clone(), url.clone()], config).unwrap();
+
+    // Each request first tries the endpoint that is down, until it reaches the threshold.
+    for _ in 0..2 {
+        assert_eq!(multi.healthy_endpoints(), [down.as_str(), url.as_str()]);
+        let info = multi
+            .get_account_info(address, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.balance, Balance::from(Amount::from(100)));
+    }
+    assert_eq!(multi.healthy_endpoints(), [url.as_str()]);
+    multi
+        .get_account_info(address, None, None, None)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(multi.healthy_endpoints(), [down.as_str(), url.as_str()]);
+
+    // Errors returned by a proxy do not count against it.
+    let multi = MultiProxyClient::new(&[&url], FailoverConfig::default()).unwrap();
+    let (sender, sender_key) = get_key_pair();
+    let overdraft = transfer(&sender_key, address, Nonce(0), 1);
+    for _ in 0..5 {
+        assert!(
+            multi
+                .submit_transaction(overdraft.transaction.clone(), overdraft.signature.clone())
+                .await
+                .is_err()
+        );
+    }
+    assert_eq!(multi.healthy_endpoints(), [url.as_str()]);
+    assert_eq!(
+        multi
+            .get_account_info(sender, None, None, None)
+            .await
+            .unwrap()
+            .next_nonce,
+        Nonce(0)
+    );
+
+    let all_down = MultiProxyClient::new(&[down], FailoverConfig::default()).unwrap();
+    assert!(
+        all_down
+            .get_account_info(address, None, None, None)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn consistent_reads_prefer_the_freshest_view_and_detect_disagreements() {
+    let down = down_url();
+    let (url, client, _handle) = start().await;
+    let (lagging_url, lagging, _lagging_handle) = start().await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    for client in [&client, &lagging] {
+        faucet_drip(client, sender, Amount::from(100))
+            .await
+            .unwrap();
+    }
+    // Only the first proxy has seen the transfer.
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    client
+        .submit_transaction(envelope.transaction, envelope.signature)
+        .await
+        .unwrap();
+
+    let multi = MultiProxyClient::new(
+        &[down.clone(), lagging_url.clone(), url.clone()],
+        FailoverConfig::default(),
+    )
+    .unwrap();
+    let read = multi
+        .consistent_account_info(sender, None, None, None, 3, 2)
+        .await
+        .unwrap();
+    assert_eq!(read.info.next_nonce, Nonce(1));
+    assert_eq!(read.info.balance, Balance::from(Amount::from(90)));
+    assert_eq!(read.responses, 2);
+    assert_eq!(read.lagging, [lagging_url.as_str()]);
+
+    assert!(matches!(
+        multi
+            .consistent_account_info(sender, None, None, None, 3, 3)
+            .await,
+        Err(ConsistencyError::Unavailable {
+            responses: 2,
+            required: 3,
+            last_error: Some(_),
+        })
+    ));
+
+    // The recipient has nonce 0 on both proxies, but only one of them credited it.
+    let Err(ConsistencyError::Disagreement { endpoints }) = multi
+        .consistent_account_info(recipient, None, None, None, 3, 2)
+        .await
+    else {
+        panic!("the proxies should disagree");
+    };
+    assert_eq!(endpoints.len(), 2);
+    assert!(endpoints.contains(&url) && endpoints.contains(&lagging_url));
+}
+
+#[tokio::test]
+async fn only_reads_and_submissions_fail_over() {
+    let down = down_url();
+    let (url, client, _handle) = start().await;
+    let (address, _) = get_key_pair();
+    let multi = MultiProxyClient::new(&[down, url], FailoverConfig::default()).unwrap();
+
+    // The drip may have reached the first proxy, so it is not sent to the second one.
+    assert!(
+        faucet_drip(&multi, address, Amount::from(100))
+            .await
+            .is_err()
+    );
+    let info = client
+        .get_account_info(address, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(info.balance, Balance::default());
+    let info = multi
+        .get_account_info(address, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(info.balance, Balance::default());
+}
+
+#[tokio::test]
+async fn token_balances_in_another_order_are_the_same_state() {
+    let (_, client, _handle) = start().await;
+    let (address, _) = get_key_pair();
+    let mut info = client
+        .get_account_info(address, None, None, None)
+        .await
+        .unwrap();
+    let balances = [
+        (TokenId([0x11; 32]), Balance::from(Amount::from(1))),
+        (TokenId([0x22; 32]), Balance::from(Amount::from(2))),
+    ];
+    info.token_balance = balances.to_vec();
+    let (first, _first_handle) = start_canned(info.clone()).await;
+    info.token_balance.reverse();
+    let (second, _second_handle) = start_canned(info.clone()).await;
+
+    let multi = MultiProxyClient::new(&[&first, &second], FailoverConfig::default()).unwrap();
+    let read = multi
+        .consistent_account_info(address, None, None, None, 2, 2)
+        .await
+        .unwrap();
+    assert_eq!(read.responses, 2);
+
+    info.token_balance[0].1 = Balance::from(Amount::from(3));
+    let (third, _third_handle) = start_canned(info).await;
+    let multi = MultiProxyClient::new(&[first, third], FailoverConfig::default()).unwrap();
+    assert!(matches!(
+        multi
+            .consistent_account_info(address, None, None, None, 2, 2)
+            .await,
+        Err(ConsistencyError::Disagreement { .. })
+    ));
+}
@@ -0,0 +1,117 @@
+use jsonrpsee::http_client::HttpClientBuilder;
+use rust_examples::api::{faucet_drip, get_account_info};
+use rust_examples::client::ProxySubmitTransactionResult;
+use rust_examples::fastset_types::{Amount, Balance, Nonce, TokenId, get_key_pair};
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::offline::{
+    OfflineError, SignedTransaction, UnsignedTransaction, prepare_transfer, signing_digest,
+    submit_signed,
+};
+use rust_examples::retry::{RetryConfig, SubmitOutcome};
+
+mod common;
+use common::transfer;
+
+#[tokio::test]
+async fn prepare_sign_offline_and_submit() {
+    let (addr, _handle) = MockProxy::new(1).start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let (sender, cold_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(1_000))
+        .await
+        .unwrap();
+
+    // Online: prepare the transaction with the sender's next nonce.
+    let unsigned = prepare_transfer(
+        &client,
+        sender,
+        recipient,
+        TokenId::native(),
+        Amount::from(250),
+        2,
+    )
+    .await
+    .unwrap();
+    assert_eq!(unsigned.transaction.nonce, Nonce(0));
+    assert!(
+        unsigned.summary.starts_with(&format!(
+            "Transfer 2.5 SET (250 least units of token {})\n",
+            TokenId::native()
+        )),
+        "{}",
+        unsigned.summary
+    );
+    assert_eq!(
+        unsigned.digest,
+        hex::encode(signing_digest(&unsigned.transaction))
+    );
+    let unsigned_path = dir.path().join("transfer.unsigned.json");
+    unsigned.save(&unsigned_path).unwrap();
+
+    // Offline: check and sign with the cold key.
+    let loaded = UnsignedTransaction::load(&unsigned_path).unwrap();
+    assert_eq!(loaded, unsigned);
+    let (_, other_key) = get_key_pair();
+    assert!(matches!(
+        loaded.sign(&other_key),
+        Err(OfflineError::WrongKey { .. })
+    ));
+    let signed_path = dir.path().join("transfer.signed.json");
+    loaded.sign(&cold_key).unwrap().save(&signed_path).unwrap();
+
+    // Online: submit the signed file.
+    let signed = SignedTransaction::load(&signed_path).unwrap();
+    let outcome = submit_signed(&client, &signed, &RetryConfig::default())
+        .await
+        .unwrap();
+    assert!(matches!(
+        outcome,
+        SubmitOutcome::Submitted(ProxySubmitTransactionResult::Success(_))
+    ));
+    assert_eq!(
+        get_account_info(&client, recipient).await.balance,
+        Balance::from(Amount::from(250))
+    );
+}
+
+#[test]
+fn tampered_files_are_refused() {
+    let (_, cold_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let envelope = transfer(&cold_key, recipient, Nonce(3), 100);
+    let unsigned = UnsignedTransaction::new(envelope.transaction, None);
+    unsigned.verify().unwrap();
+
+    // A summary edited to look harmless.
+    let mut edited = unsigned.clone();
+    edited.summary = edited.summary.replace("100 least units", "1 least units");
+    assert!(matches!(
+        edited.sign(&cold_key),
+        Err(OfflineError::SummaryMismatch)
+    ));
+
+    // A transaction changed behind an unchanged summary and digest.
+    let mut edited = unsigned.clone();
+    edited.transaction.recipient = get_key_pair().0;
+    assert!(matches!(
+        edited.sign(&cold_key),
+        Err(OfflineError::SummaryMismatch)
+    ));
+    let mut edited = unsigned.clone();
+    edited.transaction.timestamp_nanos += 1;
+    edited.summary = UnsignedTransaction::new(edited.transaction.clone(), None).summary;
+    assert!(matches!(
+        edited.sign(&cold_key),
+        Err(OfflineError::DigestMismatch)
+    ));
+
+    // A signed file whose envelope was swapped.
+    let mut signed = unsigned.sign(&cold_key).unwrap();
+    signed.verify().unwrap();
+    signed.envelope.signature = transfer(&cold_key, recipient, Nonce(4), 100).signature;
+    assert!(matches!(signed.verify(), Err(OfflineError::Signature(_))));
+}
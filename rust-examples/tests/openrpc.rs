@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use rust_examples::fastset_types::{Nonce, NonceRange, TokenId, get_key_pair};
+use rust_examples::openrpc::{document, to_file_contents};
+use serde_json::{Value, json};
+
+mod common;
+use common::transfer;
+
+#[test]
+fn openrpc_document_is_up_to_date() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../docs/proxy/openrpc.json");
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        to_file_contents(&document().unwrap()),
+        "{} is out of date, run `cargo run --features docs --bin fastset-openrpc`",
+        path.display()
+    );
+}
+
+/// Check named `params` against the parameter schemas of `method`, resolving references against
+/// the document components.
+fn params_are_valid(document: &Value, method: &str, params: &Value) -> bool {
+    let method = document["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["name"] == method)
+        .unwrap();
+    method["params"].as_array().unwrap().iter().all(|param| {
+        let name = param["name"].as_str().unwrap();
+        let Some(value) = params.get(name) else {
+            return param["required"] == false;
+        };
+        let schema = json!({
+            "allOf": [param["schema"]],
+            "components": document["components"],
+        });
+        jsonschema::validator_for(&schema).unwrap().is_valid(value)
+    })
+}
+
+#[test]
+fn named_params_validate_against_the_document() {
+    let document = document().unwrap();
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 1250);
+
+    let submit = json!({
+        "transaction": envelope.transaction,
+        "signature": envelope.signature,
+    });
+    assert!(params_are_valid(
+        &document,
+        "proxy_submitTransaction",
+        &submit
+    ));
+    let account_info = json!({
+        "address": sender,
+        "token_balances_filter": [TokenId::native()],
+        "certificate_by_nonce": NonceRange { start: Nonce(0), limit: 5 },
+    });
+    assert!(params_are_valid(
+        &document,
+        "proxy_getAccountInfo",
+        &account_info
+    ));
+    assert!(params_are_valid(
+        &document,
+        "proxy_getAccountInfo",
+        &json!({ "address": sender })
+    ));
+    assert!(!params_are_valid(
+        &document,
+        "proxy_getAccountInfo",
+        &json!({ "token_balances_filter": [] })
+    ));
+    assert!(!params_are_valid(
+        &document,
+        "proxy_faucetDrip",
+        &json!({ "recipient": sender, "amount": "0x10" })
+    ));
+
+    let names: Vec<_> = document["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect();
+    assert!(!names.iter().any(|name| name.contains("ubscribe")));
+    assert!(names.iter().all(|name| name.starts_with("proxy_")));
+}
+
+#[test]
+fn every_reference_resolves_to_a_component() {
+    let document = document().unwrap();
+    let text = document.to_string();
+    for reference in text.split("\"$ref\":\"").skip(1) {
+        let reference = &reference[..reference.find('"').unwrap()];
+        let name = reference
+            .strip_prefix("#/components/schemas/")
+            .unwrap_or_else(|| panic!("unexpected reference {reference}"));
+        assert!(
+            document["components"]["schemas"].get(name).is_some(),
+            "missing component {name}"
+        );
+    }
+}
@@ -0,0 +1,435 @@
+use std::sync::{Arc, Mutex};
+
+use jsonrpsee::RpcModule;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::server::{Server, ServerHandle};
+use jsonrpsee::types::ErrorObjectOwned;
+use rust_examples::api::{faucet_drip, get_account_info};
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::fastset_types::{
+    AccountInfoResponse, Amount, Balance, ClaimType, FastSetAddress, Nonce, Operation,
+    PublicKeyBytes, SignatureOrMultiSig, TokenId, TokenMetadata, Transaction,
+    TransactionCertificate, TransactionEnvelope, UserData, get_key_pair,
+};
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::payouts::{
+    PayoutConfig, PayoutError, RowProblem, RowStatus, parse_payouts, run_payouts,
+};
+use serde::Deserialize;
+
+mod common;
+use common::transfer;
+
+const USDX: TokenId = TokenId([0x11; 32]);
+
+async fn start() -> (HttpClient, jsonrpsee::server::ServerHandle) {
+    let proxy = MockProxy::new(1);
+    proxy.add_token(
+        USDX,
+        TokenMetadata {
+            update_id: Nonce(0),
+            admin: PublicKeyBytes([0xad; 32]),
+            token_name: "USDX".to_string(),
+            decimals: 6,
+            total_supply: Amount::from(1_000_000_000),
+            mints: vec![],
+        },
+    );
+    let (addr, handle) = proxy.start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, handle)
+}
+
+/// A proxy that always answers `info` to account lookups, and `submitted` to submissions, or a
+/// validator timeout.
+async fn start_canned(
+    info: AccountInfoResponse,
+    submitted: Option<ProxySubmitTransactionResult>,
+) -> (HttpClient, ServerHandle) {
+    let mut module = RpcModule::new((info, submitted));
+    module
+        .register_method("proxy_getAccountInfo", |_, state, _| {
+            Ok::<_, ErrorObjectOwned>(state.0.clone())
+        })
+        .unwrap();
+    module
+        .register_method("proxy_submitTransaction", |_, state, _| {
+            state
+                .1
+                .clone()
+                .ok_or_else(|| ErrorObjectOwned::owned(-32000, "validator timeout", None::<()>))
+        })
+        .unwrap();
+    let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, server.start(module))
+}
+
+/// A proxy that certifies every transaction submitted to it, whatever its claim, answering `info`
+/// with the next nonce after them to account lookups.
+async fn start_certifying(
+    info: AccountInfoResponse,
+) -> (HttpClient, ServerHandle, Arc<Mutex<Vec<Transaction>>>) {
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let mut module = RpcModule::new((info, submitted.clone()));
+    module
+        .register_method("proxy_getAccountInfo", |_, state, _| {
+            let mut info = state.0.clone();
+            info.next_nonce = Nonce(state.1.lock().unwrap().len() as u64);
+            Ok::<_, ErrorObjectOwned>(info)
+        })
+        .unwrap();
+    module
+        .register_method("proxy_submitTransaction", |params, state, _| {
+            #[derive(Deserialize)]
+            struct Params {
+                transaction: Transaction,
+                signature: SignatureOrMultiSig,
+            }
+            let Params {
+                transaction,
+                signature,
+            } = params.parse()?;
+            state.1.lock().unwrap().push(transaction.clone());
+            Ok::<_, ErrorObjectOwned>(ProxySubmitTransactionResult::Success(
+                TransactionCertificate {
+                    envelope: TransactionEnvelope {
+                        transaction,
+                        signature,
+                    },
+                    signatures: Vec::new(),
+                },
+            ))
+        })
+        .unwrap();
+    let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, server.start(module), submitted)
+}
+
+async fn balance(client: &HttpClient, address: FastSetAddress) -> Balance {
+    get_account_info(client, address).await.balance
+}
+
+fn native(amount: u64) -> Balance {
+    Balance::from(Amount::from(amount))
+}
+
+#[tokio::test]
+async fn every_invalid_row_is_reported() {
+    let (client, _handle) = start().await;
+    let (alice, _) = get_key_pair();
+    let csv = format!(
+        "recipient,amount,token,memo\n\
+         {alice},1.5,,\"invoice 7, March\"\n\
+         {alice},2,0x{usdx},0x{memo}\n\
+         \n\
+         not-an-address,1,,\n\
+         {alice},1.001,,\n\
+         {alice},1,0x22,\n\
+         {alice},1,{unknown},\n\
+         {alice},0,,\n\
+         {alice},1,,this memo is definitely too long for user data\n\
+         {alice},1\n\
+         {alice},1,,\"open\n",
+        usdx = hex::encode([0x11; 32]),
+        memo = hex::encode([0xee; 32]),
+        unknown = hex::encode([0x22; 32]),
+    );
+    let Err(PayoutError::InvalidRows(errors)) = parse_payouts(&client, &csv, 2).await else {
+        panic!("the rows should be invalid");
+    };
+    let problems: Vec<_> = errors
+        .iter()
+        .map(|error| (error.line, &error.problem))
+        .collect();
+    assert!(matches!(
+        problems[..],
+        [
+            (5, RowProblem::Recipient(_)),
+            (6, RowProblem::Amount(_)),
+            (7, RowProblem::Token(_)),
+            (8, RowProblem::UnknownToken(_)),
+            (9, RowProblem::ZeroAmount),
+            (10, RowProblem::MemoTooLong),
+            (
+                11,
+                RowProblem::FieldCount {
+                    expected: 4,
+                    found: 2
+                }
+            ),
+            (12, RowProblem::UnterminatedQuote),
+        ]
+    ));
+
+    let valid: String = csv
+        .lines()
+        .take(3)
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let payouts = parse_payouts(&client, &valid, 2).await.unwrap();
+    assert_eq!(payouts.len(), 2);
+    assert_eq!(payouts[0].amount, Amount::from(150));
+    let mut memo = [0; 32];
+    memo[..16].copy_from_slice(b"invoice 7, March");
+    assert_eq!(payouts[0].memo, UserData(Some(memo)));
+    assert_eq!(payouts[1].token_id, USDX);
+    assert_eq!(payouts[1].amount, Amount::from(2_000_000));
+    assert_eq!(payouts[1].memo, UserData(Some([0xee; 32])));
+
+    assert!(matches!(
+        parse_payouts(&client, "address,amount\n", 2).await,
+        Err(PayoutError::MalformedHeader(_))
+    ));
+}
+
+#[tokio::test]
+async fn interrupted_payouts_resume_without_paying_twice() {
+    let (client, _handle) = start().await;
+    let (sender, sender_key) = get_key_pair();
+    let (alice, _) = get_key_pair();
+    let (bob, _) = get_key_pair();
+    let (carol, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(250))
+        .await
+        .unwrap();
+    let csv = format!("recipient,amount\n{alice},1\n{bob},1\n{carol},1\n");
+    let payouts = parse_payouts(&client, &csv, 2).await.unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let journal = dir.path().join("payouts.journal");
+    let config = PayoutConfig::default();
+
+    // The sender cannot afford the last row.
+    let report = run_payouts(&client, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert!(!report.is_complete());
+    assert!(matches!(
+        report.rows[1].status,
+        RowStatus::Paid {
+            nonce: Nonce(1),
+            ..
+        }
+    ));
+    assert!(matches!(report.rows[2].status, RowStatus::Failed(_)));
+    assert_eq!(report.to_string(), "2 paid, 1 failed, 0 pending.");
+
+    // Meanwhile, another transaction takes the nonce signed for the last row.
+    faucet_drip(&client, sender, Amount::from(1_000))
+        .await
+        .unwrap();
+    let other = transfer(&sender_key, alice, Nonce(2), 5);
+    client
+        .submit_transaction(other.transaction, other.signature)
+        .await
+        .unwrap();
+
+    let report = run_payouts(&client, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert!(report.is_complete());
+    assert!(matches!(
+        report.rows[2].status,
+        RowStatus::Paid {
+            nonce: Nonce(3),
+            ..
+        }
+    ));
+    assert_eq!(balance(&client, alice).await, native(105));
+    assert_eq!(balance(&client, bob).await, native(100));
+    assert_eq!(balance(&client, carol).await, native(100));
+    let csv_report = report.to_csv();
+    assert_eq!(csv_report.lines().count(), 4);
+    let RowStatus::Paid { hash, .. } = report.rows[0].status else {
+        unreachable!()
+    };
+    assert!(
+        csv_report
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(&hex::encode(hash))
+    );
+
+    // Losing the end of the journal only resends envelopes that already settled.
+    let text = std::fs::read_to_string(&journal).unwrap();
+    let truncated: String = text
+        .lines()
+        .filter(|line| !line.contains("\"settled\""))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    std::fs::write(&journal, truncated).unwrap();
+    let resumed = run_payouts(&client, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert_eq!(resumed, report);
+
+    // A line torn by a crash while appending to the journal is cut off.
+    let mut torn = std::fs::read_to_string(&journal).unwrap();
+    torn.push_str("{\"settled\":{\"li");
+    std::fs::write(&journal, torn).unwrap();
+    let resumed = run_payouts(&client, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert_eq!(resumed, report);
+    assert!(std::fs::read_to_string(&journal).unwrap().ends_with('\n'));
+    assert_eq!(get_account_info(&client, sender).await.next_nonce, Nonce(4));
+    assert_eq!(balance(&client, carol).await, native(100));
+
+    assert!(matches!(
+        run_payouts(&client, &sender_key, &payouts[..2], &journal, &config).await,
+        Err(PayoutError::JournalMismatch)
+    ));
+}
+
+#[tokio::test]
+async fn rows_stay_unresolved_without_a_certificate_of_their_transaction() {
+    let (client, _handle) = start().await;
+    let (sender, sender_key) = get_key_pair();
+    let (alice, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(100))
+        .await
+        .unwrap();
+    let csv = format!("recipient,amount\n{alice},1\n");
+    let payouts = parse_payouts(&client, &csv, 2).await.unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let journal = dir.path().join("payouts.journal");
+    let config = PayoutConfig::default();
+    let unresolved = |journal: &std::path::Path| {
+        let text = std::fs::read_to_string(journal).unwrap();
+        text.contains("\"signed\"")
+            && !text.contains("\"settled\"")
+            && !text.contains("\"abandoned\"")
+    };
+
+    // A proxy that answers with the certificate of another transaction of the sender.
+    let info = get_account_info(&client, sender).await;
+    let other = TransactionCertificate {
+        envelope: transfer(&sender_key, alice, Nonce(0), 99),
+        signatures: Vec::new(),
+    };
+    let submitted = ProxySubmitTransactionResult::Success(other);
+    let (lying, _lying_handle) = start_canned(info.clone(), Some(submitted)).await;
+    let report = run_payouts(&lying, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert!(matches!(report.rows[0].status, RowStatus::Failed(_)));
+    assert!(unresolved(&journal));
+
+    // A proxy that pruned the certificate of the nonce, which was maybe used by the row.
+    let mut pruned = info;
+    pruned.next_nonce = Nonce(1);
+    pruned.requested_certificates = Some(Vec::new());
+    let (pruned, _pruned_handle) = start_canned(pruned, None).await;
+    let report = run_payouts(&pruned, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    let RowStatus::Failed(reason) = &report.rows[0].status else {
+        panic!("unexpected report {report:?}");
+    };
+    assert!(reason.contains("consumed"), "{reason}");
+    assert!(unresolved(&journal));
+
+    // The same envelope settles on an honest proxy.
+    let report = run_payouts(&client, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert!(report.is_complete());
+    assert!(matches!(
+        report.rows[0].status,
+        RowStatus::Paid {
+            nonce: Nonce(0),
+            ..
+        }
+    ));
+    assert_eq!(balance(&client, alice).await, native(100));
+}
+
+#[tokio::test]
+async fn rows_are_paid_in_batches() {
+    let (client, _handle) = start().await;
+    let (sender, sender_key) = get_key_pair();
+    let recipients: Vec<_> = (0..5).map(|_| get_key_pair().0).collect();
+    let csv: String = recipients
+        .iter()
+        .enumerate()
+        .map(|(index, recipient)| format!("{recipient},{}\n", index + 1))
+        .collect();
+    let payouts = parse_payouts(&client, &format!("recipient,amount\n{csv}"), 2)
+        .await
+        .unwrap();
+    let info = get_account_info(&client, sender).await;
+    let (certifying, _certifying_handle, submitted) = start_certifying(info).await;
+    let dir = tempfile::tempdir().unwrap();
+    let journal = dir.path().join("payouts.journal");
+    let config = PayoutConfig {
+        batch_size: 2,
+        ..PayoutConfig::default()
+    };
+
+    let report = run_payouts(&certifying, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert!(report.is_complete());
+    let nonces: Vec<_> = report
+        .rows
+        .iter()
+        .map(|row| match row.status {
+            RowStatus::Paid { nonce, .. } => nonce.0,
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(nonces, [0, 0, 1, 1, 2]);
+    assert_eq!(report.rows[0].status, report.rows[1].status);
+
+    let transactions = submitted.lock().unwrap().clone();
+    assert_eq!(transactions.len(), 3);
+    for (transaction, chunk) in transactions[..2].iter().zip(payouts.chunks(2)) {
+        let ClaimType::Batch(bundle) = &transaction.claim else {
+            panic!("unexpected claim {:?}", transaction.claim);
+        };
+        let paid: Vec<_> = bundle
+            .0
+            .iter()
+            .map(|operation| match operation {
+                Operation::TokenTransfer(transfer) => (transfer.recipient, transfer.amount),
+                _ => unreachable!(),
+            })
+            .collect();
+        let expected: Vec<_> = chunk
+            .iter()
+            .map(|payout| (payout.recipient, payout.amount))
+            .collect();
+        assert_eq!(paid, expected);
+    }
+    // The last chunk has a single row, paid with a plain transfer.
+    assert!(matches!(transactions[2].claim, ClaimType::TokenTransfer(_)));
+    assert_eq!(transactions[2].recipient, recipients[4]);
+
+    // Resuming pays nothing again, and only with the batch size the journal was created for.
+    let resumed = run_payouts(&certifying, &sender_key, &payouts, &journal, &config)
+        .await
+        .unwrap();
+    assert_eq!(resumed, report);
+    assert_eq!(submitted.lock().unwrap().len(), 3);
+    assert!(matches!(
+        run_payouts(
+            &certifying,
+            &sender_key,
+            &payouts,
+            &journal,
+            &PayoutConfig::default()
+        )
+        .await,
+        Err(PayoutError::JournalMismatch)
+    ));
+}
@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+use jsonrpsee::RpcModule;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::server::{Server, ServerHandle};
+use jsonrpsee::types::ErrorObjectOwned;
+use rust_examples::api::faucet_drip;
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::devnet::Devnet;
+use rust_examples::fastset_types::{AccountInfoResponse, Amount, Nonce, NonceRange, get_key_pair};
+use rust_examples::retry::{
+    RetryConfig, SubmissionStatus, SubmitError, SubmitOutcome, check_submission_status,
+    submit_transaction_with_retry,
+};
+
+mod common;
+use common::transfer;
+
+async fn start(devnet: &Devnet) -> (HttpClient, ServerHandle) {
+    let (addr, handle) = devnet.proxy().start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, handle)
+}
+
+/// A proxy that rejects every submission and always answers `info` to account lookups.
+async fn start_canned(info: AccountInfoResponse) -> (HttpClient, ServerHandle) {
+    let mut module = RpcModule::new(info);
+    module
+        .register_method("proxy_getAccountInfo", |_, info, _| {
+            Ok::<_, ErrorObjectOwned>(info.clone())
+        })
+        .unwrap();
+    module
+        .register_method("proxy_submitTransaction", |_, _, _| {
+            Err::<(), _>(ErrorObjectOwned::owned(
+                -32000,
+                "validator timeout",
+                None::<()>,
+            ))
+        })
+        .unwrap();
+    let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, server.start(module))
+}
+
+#[tokio::test]
+async fn status_tells_unseen_pending_settled_and_conflicting_transactions_apart() {
+    let devnet = Devnet::new(1);
+    let (client, _handle) = start(&devnet).await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(100))
+        .await
+        .unwrap();
+
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    let conflicting = transfer(&sender_key, recipient, Nonce(0), 20);
+    assert!(matches!(
+        check_submission_status(&client, &envelope).await,
+        Ok(SubmissionStatus::Unseen)
+    ));
+
+    // The validator has voted for the transaction, but no certificate was formed yet.
+    devnet.validators()[0]
+        .handle_transaction(&envelope)
+        .await
+        .unwrap();
+    assert!(matches!(
+        check_submission_status(&client, &envelope).await,
+        Ok(SubmissionStatus::Pending)
+    ));
+    assert!(matches!(
+        check_submission_status(&client, &conflicting).await,
+        Err(SubmitError::NonceConflict(Nonce(0)))
+    ));
+
+    client
+        .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+    let Ok(SubmissionStatus::Settled(certificate)) =
+        check_submission_status(&client, &envelope).await
+    else {
+        panic!("the transaction should be settled");
+    };
+    assert_eq!(certificate.envelope, envelope);
+    assert!(matches!(
+        check_submission_status(&client, &conflicting).await,
+        Err(SubmitError::NonceConflict(Nonce(0)))
+    ));
+}
+
+#[tokio::test]
+async fn consumed_nonce_without_certificate_is_not_success() {
+    let devnet = Devnet::new(1);
+    let (client, _handle) = start(&devnet).await;
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(100))
+        .await
+        .unwrap();
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    let result = client
+        .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+    assert!(matches!(result, ProxySubmitTransactionResult::Success(_)));
+
+    // A proxy that pruned the certificate only shows that the nonce was used.
+    let mut info = client
+        .get_account_info(
+            sender,
+            None,
+            None,
+            Some(NonceRange {
+                start: Nonce(0),
+                limit: 1,
+            }),
+        )
+        .await
+        .unwrap();
+    info.requested_certificates = Some(Vec::new());
+    let (pruned, _pruned_handle) = start_canned(info).await;
+    assert!(matches!(
+        check_submission_status(&pruned, &envelope).await,
+        Ok(SubmissionStatus::NonceConsumed)
+    ));
+    assert!(matches!(
+        submit_transaction_with_retry(&pruned, &envelope, &RetryConfig::default()).await,
+        Err(SubmitError::NonceConsumed(Nonce(0)))
+    ));
+
+    // With the certificate, the rejected resubmission is recognized as settled.
+    let outcome = submit_transaction_with_retry(&client, &envelope, &RetryConfig::default())
+        .await
+        .unwrap();
+    let SubmitOutcome::AlreadySettled(certificate) = outcome else {
+        panic!("unexpected outcome {outcome:?}");
+    };
+    assert_eq!(certificate.envelope, envelope);
+}
+
+#[test]
+fn backoff_grows_from_the_initial_delay_up_to_the_cap() {
+    let config = RetryConfig {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(500),
+        multiplier: 2,
+        ..RetryConfig::default()
+    };
+    for _ in 0..100 {
+        assert!(config.backoff(0) <= Duration::from_millis(100));
+        assert!(config.backoff(2) <= Duration::from_millis(400));
+        assert!(config.backoff(3) <= Duration::from_millis(500));
+        assert!(config.backoff(u32::MAX) <= Duration::from_millis(500));
+    }
+}
+
+#[tokio::test]
+async fn unreachable_proxies_exhaust_the_attempts() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let down = HttpClientBuilder::default()
+        .build(format!("http://{}", listener.local_addr().unwrap()))
+        .unwrap();
+    drop(listener);
+    let (recipient, _) = get_key_pair();
+    let (_, sender_key) = get_key_pair();
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 10);
+    let config = RetryConfig {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(40),
+        multiplier: 2,
+        max_status_checks: 1,
+    };
+    let start = Instant::now();
+    let result = submit_transaction_with_retry(&down, &envelope, &config).await;
+    assert!(
+        matches!(result, Err(SubmitError::Exhausted { attempts: 3, .. })),
+        "{result:?}"
+    );
+    // Two delays of at most 20 and 40 ms separate the three attempts.
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
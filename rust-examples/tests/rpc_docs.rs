@@ -0,0 +1,90 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use rust_examples::rpc_docs::{EndpointKind, endpoints, render};
+
+#[test]
+fn rpc_docs_are_up_to_date() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../docs/proxy/rpc.md");
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        render().unwrap(),
+        "{} is out of date, run `cargo run --features docs --bin fastset-rpc-docs`",
+        path.display()
+    );
+}
+
+#[test]
+fn endpoints_follow_the_rpc_trait() {
+    let endpoints = endpoints().unwrap();
+    let names: Vec<_> = endpoints.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "proxy_submitTransaction",
+            "proxy_faucetDrip",
+            "proxy_getAccountInfo",
+            "proxy_getTokenInfo",
+            "proxy_evmSignCertificate",
+        ]
+    );
+    let params: Vec<_> = endpoints[2].params.iter().map(|(name, _)| name).collect();
+    assert_eq!(
+        params,
+        [
+            "address",
+            "token_balances_filter",
+            "state_key_filter",
+            "certificate_by_nonce"
+        ]
+    );
+    assert!(
+        endpoints[0]
+            .docs
+            .starts_with("Submit a signed transaction to the proxy")
+    );
+    // The hypothetical subscriptions of the mock proxy are not part of the documented API.
+    assert!(
+        endpoints
+            .iter()
+            .all(|endpoint| matches!(endpoint.kind, EndpointKind::Method { .. }))
+    );
+}
+
+#[test]
+fn every_link_has_a_section() {
+    let docs = render().unwrap();
+    let anchors: BTreeSet<_> = docs
+        .lines()
+        .filter_map(|line| line.strip_prefix("## ").or(line.strip_prefix("### ")))
+        .map(|heading| heading.trim_matches('`').to_lowercase())
+        .collect();
+    for link in docs.split("](#").skip(1) {
+        let anchor = &link[..link.find(')').unwrap()];
+        assert!(anchors.contains(anchor), "no section for #{anchor}");
+    }
+}
+
+#[test]
+fn every_claim_type_has_a_section() {
+    let docs = render().unwrap();
+    for name in [
+        "TokenCreation",
+        "TokenManagement",
+        "Mint",
+        "StateInitialization",
+        "StateUpdate",
+        "ExternalClaim",
+        "StateReset",
+        "OperationBundle",
+        "Operation",
+        "TransactionInfo",
+        "Page<T>",
+        "Timed<T>",
+    ] {
+        assert!(
+            docs.contains(&format!("\n### {name}\n")),
+            "no section for {name}"
+        );
+    }
+}
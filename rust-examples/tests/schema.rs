@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use jsonrpsee::http_client::HttpClientBuilder;
+use rust_examples::api::faucet_drip;
+use rust_examples::client::{ProxyRpcClient, ProxySubmitTransactionResult};
+use rust_examples::fastset_types::{Amount, Balance, Nonce, NonceRange, TokenId, get_key_pair};
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::schema::{all_schemas, to_file_contents};
+use serde_json::{Value, json};
+
+mod common;
+use common::transfer;
+
+fn validator(name: &str) -> jsonschema::Validator {
+    let (_, schema) = all_schemas()
+        .into_iter()
+        .find(|(schema_name, _)| *schema_name == name)
+        .unwrap();
+    jsonschema::validator_for(schema.as_value()).unwrap()
+}
+
+#[test]
+fn schema_files_are_up_to_date() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../docs/proxy");
+    for (name, schema) in all_schemas() {
+        let path = dir.join(format!("{name}.json"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            to_file_contents(&schema),
+            "{} is out of date, run `cargo run --bin fastset-schemas`",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn amounts_and_balances_are_hex_strings() {
+    let amount = validator("Amount");
+    let balance = validator("Balance");
+    let max: Amount = "f".repeat(64).parse().unwrap();
+    for value in [Amount::from(0), Amount::from(65535), max] {
+        assert!(amount.is_valid(&serde_json::to_value(value).unwrap()));
+        assert!(balance.is_valid(&serde_json::to_value(Balance::from(value)).unwrap()));
+    }
+    let negative: Balance = "-ff".parse().unwrap();
+    assert!(balance.is_valid(&serde_json::to_value(negative).unwrap()));
+    assert!(!amount.is_valid(&json!("-ff")));
+    for invalid in [
+        json!(""),
+        json!("0x10"),
+        json!("1.5"),
+        json!(16),
+        json!("f".repeat(65)),
+    ] {
+        assert!(!amount.is_valid(&invalid), "{invalid}");
+        assert!(!balance.is_valid(&invalid), "{invalid}");
+    }
+}
+
+#[tokio::test]
+async fn wire_payloads_match_their_schemas() {
+    let (addr, _handle) = MockProxy::new(3).start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    faucet_drip(&client, sender, Amount::from(10_000))
+        .await
+        .unwrap();
+    let envelope = transfer(&sender_key, recipient, Nonce(0), 1250);
+    let envelope_json = serde_json::to_value(&envelope).unwrap();
+    assert!(validator("TransactionEnvelope").is_valid(&envelope_json));
+
+    let result = client
+        .submit_transaction(envelope.transaction.clone(), envelope.signature.clone())
+        .await
+        .unwrap();
+    let result_json = serde_json::to_value(&result).unwrap();
+    assert!(validator("ProxySubmitTransactionResult").is_valid(&result_json));
+    let ProxySubmitTransactionResult::Success(certificate) = result else {
+        panic!("unexpected result {result:?}");
+    };
+    assert!(
+        validator("TransactionCertificate").is_valid(&serde_json::to_value(&certificate).unwrap())
+    );
+
+    let info = client
+        .get_account_info(
+            sender,
+            Some(vec![]),
+            None,
+            Some(NonceRange {
+                start: Nonce(0),
+                limit: 10,
+            }),
+        )
+        .await
+        .unwrap();
+    assert!(validator("AccountInfoResponse").is_valid(&serde_json::to_value(&info).unwrap()));
+    let tokens = client
+        .get_token_info(vec![TokenId::native()])
+        .await
+        .unwrap();
+    assert!(validator("TokenInfoResponse").is_valid(&serde_json::to_value(&tokens).unwrap()));
+
+    let mut tampered = envelope_json;
+    tampered["transaction"]["claim"]["TokenTransfer"]["amount"] = json!("0x4e2");
+    assert!(!validator("TransactionEnvelope").is_valid(&tampered));
+    let mut truncated: Value = serde_json::to_value(&envelope).unwrap();
+    truncated["signature"]["Signature"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    assert!(!validator("TransactionEnvelope").is_valid(&truncated));
+}
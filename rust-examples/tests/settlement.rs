@@ -0,0 +1,258 @@
+use k256::ecdsa::SigningKey;
+use rust_examples::abi::encode_certificate;
+use rust_examples::evm::{CrossSignError, EvmAddress, eip191_digest};
+use rust_examples::fastset_types::{
+    ClaimType, CrossSignResponse, Nonce, TransactionCertificate, UserData, get_key_pair,
+};
+use rust_examples::settlement::{
+    BURN_ADDRESS, Eip1559Params, Eip1559Transaction, Rlp, SettlementArg, SettlementError,
+    SettlementFunction, selector,
+};
+use serde_json::json;
+
+mod common;
+use common::transfer;
+
+fn proxy_key() -> SigningKey {
+    SigningKey::from_slice(&[0x42; 32]).unwrap()
+}
+
+fn burn() -> TransactionCertificate {
+    let (_, sender) = get_key_pair();
+    let mut envelope = transfer(&sender, BURN_ADDRESS, Nonce(3), 500);
+    let ClaimType::TokenTransfer(transfer) = &mut envelope.transaction.claim else {
+        panic!("a transfer");
+    };
+    transfer.user_data = UserData(Some([0xee; 32]));
+    TransactionCertificate {
+        envelope,
+        signatures: Vec::new(),
+    }
+}
+
+/// What a proxy following the assumed ABI layout would return for `certificate`.
+fn cross_sign(key: &SigningKey, certificate: &TransactionCertificate) -> CrossSignResponse {
+    let transaction = encode_certificate(certificate).unwrap();
+    let (signature, recovery_id) = key
+        .sign_prehash_recoverable(&eip191_digest(&transaction))
+        .unwrap();
+    let mut bytes = signature.to_vec();
+    bytes.push(27 + recovery_id.to_byte());
+    CrossSignResponse {
+        format: "eip191-abi".to_string(),
+        signature: format!("0x{}", hex::encode(bytes)),
+        transaction,
+    }
+}
+
+fn word(value: usize) -> Vec<u8> {
+    let mut word = vec![0; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+#[test]
+fn settlement_calldata_is_abi_encoded() {
+    let certificate = burn();
+    let key = proxy_key();
+    let trusted = EvmAddress::from_public_key(key.verifying_key());
+    let response = cross_sign(&key, &certificate);
+    let signature = hex::decode(&response.signature[2..]).unwrap();
+
+    let function = SettlementFunction::default();
+    assert_eq!(function.signature(), "settle(bytes,bytes)");
+    let settlement = function
+        .calldata(&certificate, &response, &trusted)
+        .unwrap();
+    assert_eq!(settlement.check.unchecked, ["user_data", "archival"]);
+    let expected = [
+        selector("settle(bytes,bytes)").to_vec(),
+        word(0x40),
+        word(0x40 + 32 + 224),
+        word(224),
+        response.transaction.clone(),
+        word(65),
+        signature,
+        vec![0; 31],
+    ]
+    .concat();
+    assert_eq!(settlement.calldata, expected);
+
+    let function: SettlementFunction =
+        "bridgeIn(bytes transaction, bytes32 user_data, uint256 amount, uint64 nonce)"
+            .parse()
+            .unwrap();
+    assert_eq!(
+        function.signature(),
+        "bridgeIn(bytes,bytes32,uint256,uint64)"
+    );
+    let settlement = function
+        .calldata(&certificate, &response, &trusted)
+        .unwrap();
+    let expected = [
+        selector("bridgeIn(bytes,bytes32,uint256,uint64)").to_vec(),
+        word(0x80),
+        vec![0xee; 32],
+        word(500),
+        word(3),
+        word(224),
+        response.transaction,
+    ]
+    .concat();
+    assert_eq!(settlement.calldata, expected);
+}
+
+#[test]
+fn unverified_responses_are_not_settled() {
+    let certificate = burn();
+    let key = proxy_key();
+    let trusted = EvmAddress::from_public_key(key.verifying_key());
+    let function = SettlementFunction::default();
+
+    let forged = cross_sign(&SigningKey::from_slice(&[0x43; 32]).unwrap(), &certificate);
+    assert!(matches!(
+        function.calldata(&certificate, &forged, &trusted),
+        Err(SettlementError::CrossSign(
+            CrossSignError::UntrustedSigner { .. }
+        ))
+    ));
+
+    let other = cross_sign(&key, &burn());
+    assert!(matches!(
+        function.calldata(&certificate, &other, &trusted),
+        Err(SettlementError::Abi(_))
+    ));
+}
+
+#[test]
+fn only_burns_are_settled() {
+    let mut certificate = burn();
+    let (recipient, _) = get_key_pair();
+    certificate.envelope.transaction.recipient = recipient;
+    let key = proxy_key();
+    let trusted = EvmAddress::from_public_key(key.verifying_key());
+    let response = cross_sign(&key, &certificate);
+    assert!(matches!(
+        SettlementFunction::default().calldata(&certificate, &response, &trusted),
+        Err(SettlementError::NotBurn(found)) if found == recipient
+    ));
+}
+
+#[test]
+fn settlement_functions_must_match_their_arguments() {
+    assert_eq!(
+        hex::encode(selector("transfer(address,uint256)")),
+        "a9059cbb"
+    );
+    assert!(
+        SettlementFunction::new(
+            "settle(bytes,uint256)",
+            vec![SettlementArg::Transaction, SettlementArg::Amount]
+        )
+        .is_ok()
+    );
+    assert!(matches!(
+        SettlementFunction::new("settle(bytes,bytes)", vec![SettlementArg::Transaction]),
+        Err(SettlementError::ArgumentCount {
+            expected: 2,
+            found: 1
+        })
+    ));
+    assert!(matches!(
+        "settle(bytes transaction, uint128 amount)".parse::<SettlementFunction>(),
+        Err(SettlementError::ArgumentType { index: 1, found, .. }) if found == "uint128"
+    ));
+    for malformed in [
+        "settle",
+        "settle(bytes",
+        "1settle(bytes transaction)",
+        "settle(bytes)",
+        "settle(bytes calldata)",
+    ] {
+        assert!(
+            matches!(
+                malformed.parse::<SettlementFunction>(),
+                Err(SettlementError::MalformedSignature(_))
+            ),
+            "{malformed}"
+        );
+    }
+}
+
+#[test]
+fn rlp_matches_the_specification_examples() {
+    let dog = Rlp::Bytes(b"dog".to_vec());
+    let cat = Rlp::Bytes(b"cat".to_vec());
+    let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec();
+    for (item, encoding) in [
+        (dog.clone(), "83646f67"),
+        (Rlp::List(vec![cat, dog]), "c88363617483646f67"),
+        (Rlp::Bytes(Vec::new()), "80"),
+        (Rlp::List(Vec::new()), "c0"),
+        (Rlp::uint(0), "80"),
+        (Rlp::uint(15), "0f"),
+        (Rlp::uint(1024), "820400"),
+        (
+            Rlp::List(vec![
+                Rlp::List(Vec::new()),
+                Rlp::List(vec![Rlp::List(Vec::new())]),
+                Rlp::List(vec![
+                    Rlp::List(Vec::new()),
+                    Rlp::List(vec![Rlp::List(Vec::new())]),
+                ]),
+            ]),
+            "c7c0c1c0c3c0c1c0",
+        ),
+    ] {
+        assert_eq!(hex::encode(item.encode()), encoding);
+    }
+    assert_eq!(
+        Rlp::Bytes(lorem.clone()).encode(),
+        [vec![0xb8, 0x38], lorem].concat()
+    );
+}
+
+#[test]
+fn eip1559_skeletons_call_the_contract() {
+    let contract: EvmAddress = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        .parse()
+        .unwrap();
+    let params = Eip1559Params {
+        chain_id: 1,
+        nonce: 9,
+        max_priority_fee_per_gas: 2_000_000_000,
+        max_fee_per_gas: 30_000_000_000,
+        gas_limit: 200_000,
+    };
+    let transaction = Eip1559Transaction::call(contract, vec![0xde, 0xad], params);
+    assert_eq!(
+        serde_json::to_value(&transaction).unwrap(),
+        json!({
+            "type": "0x2",
+            "chainId": "0x1",
+            "nonce": "0x9",
+            "maxPriorityFeePerGas": "0x77359400",
+            "maxFeePerGas": "0x6fc23ac00",
+            "gas": "0x30d40",
+            "to": "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf",
+            "value": "0x0",
+            "data": "0xdead",
+        })
+    );
+
+    let fields = Rlp::List(vec![
+        Rlp::uint(1),
+        Rlp::uint(9),
+        Rlp::uint(2_000_000_000),
+        Rlp::uint(30_000_000_000),
+        Rlp::uint(200_000),
+        Rlp::Bytes(contract.0.to_vec()),
+        Rlp::Bytes(Vec::new()),
+        Rlp::Bytes(vec![0xde, 0xad]),
+        Rlp::List(Vec::new()),
+    ]);
+    assert_eq!(
+        transaction.signing_payload(),
+        [vec![2], fields.encode()].concat()
+    );
+}
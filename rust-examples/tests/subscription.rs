@@ -0,0 +1,204 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::StreamExt;
+use jsonrpsee::RpcModule;
+use jsonrpsee::server::{Server, ServerHandle};
+use jsonrpsee::types::ErrorObjectOwned;
+use rust_examples::api::connect_ws;
+use rust_examples::fastset_types::{
+    AccountInfoResponse, Amount, Balance, FastSetAddress, Nonce, NonceRange, TokenId,
+    TransactionCertificate, get_key_pair,
+};
+use rust_examples::subscription::{
+    AccountChange, AccountEvent, account_events, account_updates, settled_certificates,
+};
+use serde::Deserialize;
+
+mod common;
+use common::transfer;
+
+/// The account as a proxy without subscriptions sees it.
+struct Account {
+    info: AccountInfoResponse,
+    /// Certificates the proxy still has, as opposed to pruned ones
+    certificates: Vec<TransactionCertificate>,
+}
+
+#[derive(Deserialize)]
+struct GetAccountInfoParams {
+    certificate_by_nonce: Option<NonceRange>,
+}
+
+/// A proxy that only answers `proxy_getAccountInfo`, like one without subscription support.
+async fn start_polling_only(account: Arc<Mutex<Account>>) -> (String, ServerHandle) {
+    let mut module = RpcModule::new(account);
+    module
+        .register_method("proxy_getAccountInfo", |params, account, _| {
+            let params: GetAccountInfoParams = params.parse()?;
+            let account = account.lock().unwrap();
+            let mut info = account.info.clone();
+            info.requested_certificates = params.certificate_by_nonce.map(|range| {
+                let end = range.start.0 + range.limit as u64;
+                account
+                    .certificates
+                    .iter()
+                    .filter(|cert| {
+                        (range.start.0..end).contains(&cert.envelope.transaction.nonce.0)
+                    })
+                    .cloned()
+                    .collect()
+            });
+            Ok::<_, ErrorObjectOwned>(info)
+        })
+        .unwrap();
+    let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    (format!("ws://{addr}"), server.start(module))
+}
+
+/// A proxy answering `proxy_getAccountInfo` with each of `views` in turn, like one reading from a
+/// different validator every time.
+async fn start_round_robin(views: Vec<AccountInfoResponse>) -> (String, ServerHandle) {
+    let mut module = RpcModule::new((views, AtomicUsize::new(0)));
+    module
+        .register_method("proxy_getAccountInfo", |_, state, _| {
+            let (views, next) = state;
+            let view = views[next.fetch_add(1, Ordering::Relaxed) % views.len()].clone();
+            Ok::<_, ErrorObjectOwned>(view)
+        })
+        .unwrap();
+    let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    (format!("ws://{addr}"), server.start(module))
+}
+
+fn new_account(address: FastSetAddress) -> Account {
+    Account {
+        info: AccountInfoResponse {
+            sender: address,
+            balance: Balance::default(),
+            next_nonce: Nonce(0),
+            pending_confirmation: None,
+            requested_state: Vec::new(),
+            requested_certificates: None,
+            requested_validated_transaction: None,
+            token_balance: Vec::new(),
+        },
+        certificates: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn account_updates_fall_back_to_polling_and_skip_unchanged_states() {
+    let (address, _) = get_key_pair();
+    let account = Arc::new(Mutex::new(new_account(address)));
+    let (url, _handle) = start_polling_only(account.clone()).await;
+    let client = Arc::new(connect_ws(&url).await.unwrap());
+
+    let mut updates = account_updates(client, address, None, Duration::from_millis(20))
+        .await
+        .unwrap();
+    assert_eq!(
+        updates.next().await.unwrap().unwrap().balance,
+        Balance::default()
+    );
+    // Polls that see the same state yield nothing.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), updates.next())
+            .await
+            .is_err()
+    );
+
+    account.lock().unwrap().info.balance = Balance::from(Amount::from(42));
+    assert_eq!(
+        updates.next().await.unwrap().unwrap().balance,
+        Balance::from(Amount::from(42))
+    );
+}
+
+#[tokio::test]
+async fn settled_certificates_fall_back_to_polling_and_skip_pruned_ones() {
+    let (sender, sender_key) = get_key_pair();
+    let (recipient, _) = get_key_pair();
+    let certificate = |nonce| TransactionCertificate {
+        envelope: transfer(&sender_key, recipient, Nonce(nonce), 1),
+        signatures: Vec::new(),
+    };
+    let account = Arc::new(Mutex::new(new_account(sender)));
+    {
+        let mut account = account.lock().unwrap();
+        account.info.next_nonce = Nonce(3);
+        // The certificate of nonce 1 was pruned.
+        account.certificates = vec![certificate(0), certificate(2)];
+    }
+    let (url, _handle) = start_polling_only(account.clone()).await;
+    let client = Arc::new(connect_ws(&url).await.unwrap());
+    let period = Duration::from_millis(20);
+
+    let mut replayed = settled_certificates(client.clone(), sender, Some(Nonce(0)), period)
+        .await
+        .unwrap();
+    let mut live = settled_certificates(client, sender, None, period)
+        .await
+        .unwrap();
+    let nonce = |cert: TransactionCertificate| cert.envelope.transaction.nonce;
+    assert_eq!(nonce(replayed.next().await.unwrap().unwrap()), Nonce(0));
+    assert_eq!(nonce(replayed.next().await.unwrap().unwrap()), Nonce(2));
+
+    // Only transactions settled after the stream started are yielded live, even if the
+    // certificates in between were all pruned.
+    {
+        let mut account = account.lock().unwrap();
+        account.info.next_nonce = Nonce(6);
+        account.certificates = vec![certificate(5)];
+    }
+    assert_eq!(nonce(replayed.next().await.unwrap().unwrap()), Nonce(5));
+    assert_eq!(nonce(live.next().await.unwrap().unwrap()), Nonce(5));
+}
+
+#[tokio::test]
+async fn account_events_ignore_stale_balances_at_the_same_nonce() {
+    let (address, _) = get_key_pair();
+    let usdx = TokenId([1; 32]);
+    let balance = |amount| Balance::from(Amount::from(amount));
+    // One validator has seen two incoming transfers, the other none.
+    let stale = new_account(address).info;
+    let mut fresh = stale.clone();
+    fresh.balance = balance(42);
+    fresh.token_balance = vec![(usdx, balance(7))];
+    let (url, _handle) = start_round_robin(vec![stale, fresh]).await;
+    let client = Arc::new(connect_ws(&url).await.unwrap());
+
+    let mut events = account_events(client, vec![address], None, Duration::from_millis(20))
+        .await
+        .unwrap();
+    assert_eq!(
+        events.next().await.unwrap().unwrap(),
+        AccountEvent {
+            address,
+            change: AccountChange::BalanceChanged {
+                before: balance(0),
+                after: balance(42),
+            },
+        }
+    );
+    assert_eq!(
+        events.next().await.unwrap().unwrap(),
+        AccountEvent {
+            address,
+            change: AccountChange::TokenBalanceChanged {
+                token_id: usdx,
+                before: balance(0),
+                after: balance(7),
+            },
+        }
+    );
+    // Later polls alternate between the two validators without flip-flopping.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(150), events.next())
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use rust_examples::fastset_types::{
+    Amount, Balance, MultiSigConfig, PublicKeyBytes, Signable, Signature, SignatureOrMultiSig,
+    UserData,
+};
+use rust_examples::test_vectors::{TestVectors, generate, to_file_contents};
+use serde::{Serialize, de::DeserializeOwned};
+
+fn vectors_path() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../test-vectors/signing.json")
+}
+
+#[test]
+fn test_vectors_are_up_to_date() {
+    assert_eq!(
+        std::fs::read_to_string(vectors_path()).unwrap(),
+        to_file_contents(&generate()),
+        "{} is out of date, run `cargo run --bin fastset-test-vectors`",
+        vectors_path().display()
+    );
+}
+
+fn reencode<T: Serialize + DeserializeOwned>(json: &serde_json::Value) -> String {
+    let value: T = serde_json::from_value(json.clone()).unwrap();
+    let bytes = bcs::to_bytes(&value).unwrap();
+    assert_eq!(
+        serde_json::to_value(bcs::from_bytes::<T>(&bytes).unwrap()).unwrap(),
+        *json
+    );
+    hex::encode(bytes)
+}
+
+#[test]
+fn test_vectors_are_consistent() {
+    let vectors: TestVectors =
+        serde_json::from_str(&std::fs::read_to_string(vectors_path()).unwrap()).unwrap();
+    for case in &vectors.signing {
+        let mut message = Vec::new();
+        case.transaction.write(&mut message);
+        assert_eq!(hex::encode(&message), case.signing_bytes, "{}", case.name);
+        assert!(message.starts_with(b"Transaction::"));
+        assert_eq!(case.envelope.transaction, case.transaction);
+
+        let mut signatures = Vec::new();
+        for (signer, signature) in &case.signatures {
+            assert!(vectors.keys.iter().any(|key| key.public_key == *signer));
+            let signer = PublicKeyBytes(hex::decode(signer).unwrap().try_into().unwrap());
+            let signature = Signature(hex::decode(signature).unwrap().try_into().unwrap());
+            assert_eq!(signature.verify(&case.transaction, &signer), Ok(()));
+            signatures.push((signer, signature));
+        }
+        match &case.envelope.signature {
+            SignatureOrMultiSig::Signature(signature) => {
+                assert_eq!(signatures, [(case.transaction.sender, *signature)]);
+                assert_eq!(case.envelope.verify(), Ok(()));
+            }
+            SignatureOrMultiSig::MultiSig(multisig) => {
+                assert_eq!(multisig.signatures, signatures);
+                assert!(signatures.len() as u64 >= multisig.config.quorum.0);
+            }
+        }
+    }
+
+    for case in &vectors.encoding {
+        let bcs = match case.type_name.as_str() {
+            "Amount" => reencode::<Amount>(&case.json),
+            "Balance" => reencode::<Balance>(&case.json),
+            "UserData" => reencode::<UserData>(&case.json),
+            "MultiSigConfig" => reencode::<MultiSigConfig>(&case.json),
+            other => panic!("unexpected type {other}"),
+        };
+        assert_eq!(bcs, case.bcs, "{}", case.name);
+    }
+}
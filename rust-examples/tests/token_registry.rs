@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::server::ServerHandle;
+use rust_examples::fastset_types::{Amount, Nonce, PublicKeyBytes, TokenId, TokenMetadata};
+use rust_examples::inspect::TokenLabel;
+use rust_examples::mock_proxy::MockProxy;
+use rust_examples::token_registry::{TokenRegistry, TokenRegistryError};
+
+const USDX: TokenId = TokenId([0x11; 32]);
+
+fn metadata(update_id: u64, token_name: &str, decimals: u8) -> TokenMetadata {
+    TokenMetadata {
+        update_id: Nonce(update_id),
+        admin: PublicKeyBytes([0xad; 32]),
+        token_name: token_name.to_string(),
+        decimals,
+        total_supply: Amount::from(1_000_000),
+        mints: vec![],
+    }
+}
+
+async fn start(proxy: &MockProxy) -> (HttpClient, ServerHandle) {
+    let (addr, handle) = proxy.clone().start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{addr}"))
+        .unwrap();
+    (client, handle)
+}
+
+#[tokio::test]
+async fn cached_metadata_is_refetched_on_newer_update_ids() {
+    let proxy = MockProxy::new(1);
+    proxy.add_token(USDX, metadata(0, "USDX", 2));
+    let (client, _handle) = start(&proxy).await;
+    let registry = TokenRegistry::new(Duration::from_secs(3600));
+    let unknown = TokenId([0x22; 32]);
+
+    let found = registry.metadata(&client, &[USDX, unknown]).await.unwrap();
+    assert_eq!(found[&USDX], Some(metadata(0, "USDX", 2)));
+    assert_eq!(found[&unknown], None);
+
+    // A management operation, not yet known to the registry
+    proxy.add_token(USDX, metadata(1, "USD X", 6));
+    assert_eq!(
+        registry.name(&client, USDX).await.unwrap().as_deref(),
+        Some("USDX")
+    );
+    registry.note_update_id(USDX, Nonce(0));
+    assert_eq!(registry.decimals(&client, USDX).await.unwrap(), Some(2));
+
+    registry.note_update_id(USDX, Nonce(1));
+    assert_eq!(registry.decimals(&client, USDX).await.unwrap(), Some(6));
+    let labels = registry
+        .labels(&client, &[USDX, unknown, TokenId::native()], 18)
+        .await
+        .unwrap();
+    assert_eq!(
+        labels.into_iter().collect::<Vec<_>>(),
+        vec![
+            (
+                USDX,
+                TokenLabel {
+                    name: "USD X".to_string(),
+                    decimals: 6
+                }
+            ),
+            (
+                TokenId::native(),
+                TokenLabel {
+                    name: "SET".to_string(),
+                    decimals: 18
+                }
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn expired_metadata_is_refetched_but_never_downgraded() {
+    let proxy = MockProxy::new(1);
+    proxy.add_token(USDX, metadata(3, "USDX", 2));
+    let (client, _handle) = start(&proxy).await;
+    let registry = TokenRegistry::new(Duration::ZERO);
+    assert_eq!(registry.decimals(&client, USDX).await.unwrap(), Some(2));
+
+    proxy.add_token(USDX, metadata(4, "USDX", 4));
+    assert_eq!(registry.decimals(&client, USDX).await.unwrap(), Some(4));
+
+    // A proxy lagging behind the one the metadata came from
+    let lagging = MockProxy::new(1);
+    lagging.add_token(USDX, metadata(3, "USDX", 2));
+    let (lagging, _lagging_handle) = start(&lagging).await;
+    assert_eq!(registry.decimals(&lagging, USDX).await.unwrap(), Some(4));
+}
+
+#[tokio::test]
+async fn saved_registries_start_warm() {
+    let proxy = MockProxy::new(1);
+    proxy.add_token(USDX, metadata(0, "USDX", 2));
+    let (client, _handle) = start(&proxy).await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tokens.json");
+
+    let registry = TokenRegistry::load(&path, Duration::from_secs(3600)).unwrap();
+    registry.metadata(&client, &[USDX]).await.unwrap();
+    registry.save(&path).unwrap();
+    registry.save(&path).unwrap();
+    let files: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(files, ["tokens.json"]);
+
+    // No proxy is listening there, so lookups must come from the file.
+    let offline = HttpClientBuilder::default()
+        .build("http://127.0.0.1:9")
+        .unwrap();
+    let registry = TokenRegistry::load(&path, Duration::from_secs(3600)).unwrap();
+    assert_eq!(
+        registry.name(&offline, USDX).await.unwrap().as_deref(),
+        Some("USDX")
+    );
+    registry.note_update_id(USDX, Nonce(1));
+    assert!(registry.name(&offline, USDX).await.is_err());
+
+    let expired = TokenRegistry::load(&path, Duration::ZERO).unwrap();
+    assert!(expired.name(&offline, USDX).await.is_err());
+
+    std::fs::write(&path, r#"{"version": 2, "tokens": []}"#).unwrap();
+    assert!(matches!(
+        TokenRegistry::load(&path, Duration::ZERO),
+        Err(TokenRegistryError::UnsupportedVersion(2))
+    ));
+}
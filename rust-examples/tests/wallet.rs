@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use rust_examples::fastset_types::{
+    Amount, FastSetAddress, Nonce, TokenId, TokenMetadata, get_key_pair,
+};
+use rust_examples::keystore::Keystore;
+use rust_examples::mock_proxy::MockProxy;
+use tokio::process::Command;
+
+/// Run the wallet with `home` as the home directory, returning its standard output.
+async fn wallet(home: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_fastset"))
+        .args(args)
+        .env("HOME", home)
+        .env_remove("FASTSET_CONFIG")
+        .env_remove("FASTSET_PROFILE")
+        .env_remove("FASTSET_PROXY_URL")
+        .env_remove("FASTSET_KEYSTORE")
+        .env_remove("FASTSET_KEY")
+        .output()
+        .await
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "fastset {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[tokio::test]
+async fn keygen_faucet_transfer_and_balance() {
+    let home = tempfile::tempdir().unwrap();
+    let proxy = MockProxy::new(1);
+    let token_id = TokenId([7; 32]);
+    let (admin, _) = get_key_pair();
+    proxy.add_token(
+        token_id,
+        TokenMetadata {
+            update_id: Nonce(0),
+            admin,
+            token_name: "USDX".to_string(),
+            decimals: 2,
+            total_supply: Amount::from(1_000_000),
+            mints: vec![],
+        },
+    );
+    let (addr, _handle) = proxy.start().await.unwrap();
+    std::fs::create_dir_all(home.path().join(".fastset")).unwrap();
+    std::fs::write(
+        home.path().join(".fastset/config.toml"),
+        format!("[profiles.ops]\nproxy_urls = [\"http://{addr}\"]\nkey = \"treasury\"\nnative_decimals = 6\n"),
+    )
+    .unwrap();
+    let home = home.path();
+
+    let sender = wallet(home, &["--profile", "ops", "keygen"]).await;
+    assert_eq!(wallet(home, &["--profile", "ops", "address"]).await, sender);
+    let sender: FastSetAddress = sender.trim().parse().unwrap();
+    let (recipient, _) = get_key_pair();
+    let recipient = recipient.to_string();
+    let token = token_id.to_string();
+
+    wallet(home, &["--profile", "ops", "faucet", "100"]).await;
+    wallet(
+        home,
+        &["--profile", "ops", "faucet", "50", "--token", &token],
+    )
+    .await;
+    let output = wallet(home, &["--profile", "ops", "transfer", &recipient, "12.5"]).await;
+    assert!(output.contains("12.5 SET"), "{output}");
+    wallet(
+        home,
+        &[
+            "--profile",
+            "ops",
+            "transfer",
+            &recipient,
+            "0.25",
+            "--token",
+            &token,
+        ],
+    )
+    .await;
+
+    let output = wallet(home, &["--profile", "ops", "balance"]).await;
+    assert!(
+        output.starts_with(&format!("{sender} (next nonce 2)")),
+        "{output}"
+    );
+    assert!(output.contains("  87.5 SET\n"), "{output}");
+    assert!(
+        output.contains(&format!("  49.75 USDX ({token})\n")),
+        "{output}"
+    );
+
+    let output = wallet(
+        home,
+        &["--profile", "ops", "balance", &recipient, "--token", &token],
+    )
+    .await;
+    assert!(!output.contains("SET"), "{output}");
+    assert!(
+        output.contains(&format!("  0.25 USDX ({token})\n")),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn imported_key_is_not_overwritten() {
+    let home = tempfile::tempdir().unwrap();
+    let (address, key) = get_key_pair();
+    let keystore = home.path().join("keys.json");
+    let keystore = keystore.to_str().unwrap();
+
+    let mut import = Command::new(env!("CARGO_BIN_EXE_fastset"))
+        .args(["--keystore", keystore, "--key", "imported", "import"])
+        .env("HOME", home.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = import.stdin.take().unwrap();
+    tokio::io::AsyncWriteExt::write_all(&mut stdin, hex::encode(key.secret_bytes()).as_bytes())
+        .await
+        .unwrap();
+    drop(stdin);
+    let output = import.wait_with_output().await.unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        address.to_string()
+    );
+
+    let status = Command::new(env!("CARGO_BIN_EXE_fastset"))
+        .args(["--keystore", keystore, "--key", "imported", "keygen"])
+        .env("HOME", home.path())
+        .output()
+        .await
+        .unwrap()
+        .status;
+    assert!(!status.success());
+    let output = wallet(
+        home.path(),
+        &["--keystore", keystore, "--key", "imported", "address"],
+    )
+    .await;
+    assert_eq!(output.trim(), address.to_string());
+}
+
+#[test]
+fn keystore_is_replaced_whole_and_kept_private() {
+    let home = tempfile::tempdir().unwrap();
+    let path = home.path().join("keys.json");
+    let (_, first) = get_key_pair();
+    let (_, second) = get_key_pair();
+    let mut keystore = Keystore::open(&path).unwrap();
+    keystore.insert("first", &first, false).unwrap();
+    keystore.save().unwrap();
+
+    // A readable keystore and a temporary file left by an interrupted save are both made private.
+    let temp = home.path().join("keys.json.tmp");
+    std::fs::write(&temp, "{").unwrap();
+    #[cfg(unix)]
+    for file in [&path, &temp] {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(file, std::fs::Permissions::from_mode(0o644)).unwrap();
+    }
+    let mut keystore = Keystore::open(&path).unwrap();
+    keystore.insert("second", &second, false).unwrap();
+    keystore.save().unwrap();
+
+    assert!(!temp.exists());
+    let keystore = Keystore::open(&path).unwrap();
+    assert_eq!(keystore.names().collect::<Vec<_>>(), ["first", "second"]);
+    assert_eq!(keystore.get("second").unwrap().public(), second.public());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
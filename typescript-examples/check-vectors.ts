@@ -0,0 +1,53 @@
+import { readFileSync } from "fs";
+import { fromHex, toHex } from "@mysten/bcs";
+import * as ed from "@noble/ed25519";
+import { ENCODING_TYPES, transactionSigningBytes } from "./fastset-types.ts";
+
+// Checks this example against the signing test vectors generated by the Rust examples
+// (`cargo run --bin fastset-test-vectors`), so that both implement the same BCS signing rules.
+
+const log = console.log;
+const path = new URL("../test-vectors/signing.json", import.meta.url);
+
+// Nonces and timestamps may exceed the safe integer range of JavaScript numbers, so large integers
+// are read as strings, which the BCS integer types accept.
+const vectors = JSON.parse(readFileSync(path, "utf8").replace(/(:\s*)(\d{16,})/g, '$1"$2"'));
+
+const secretKeys = new Map<string, string>(
+    vectors.keys.map((key: any) => [key.public_key, key.secret_key]),
+);
+let failures = 0;
+
+function check(name: string, actual: string, expected: string) {
+    if (actual !== expected) {
+        failures += 1;
+        log(`FAIL ${name}\n  expected ${expected}\n  actual   ${actual}`);
+    }
+}
+
+for (const testCase of vectors.signing) {
+    const message = transactionSigningBytes(testCase.transaction);
+    check(`${testCase.name}: signing bytes`, toHex(message), testCase.signing_bytes);
+    for (const [publicKey, signature] of testCase.signatures) {
+        const secretKey = fromHex(secretKeys.get(publicKey)!);
+        check(`${testCase.name}: public key`, toHex(ed.getPublicKey(secretKey)), publicKey);
+        check(`${testCase.name}: signature`, toHex(ed.sign(message, secretKey)), signature);
+    }
+}
+
+for (const testCase of vectors.encoding) {
+    const type = ENCODING_TYPES[testCase.type];
+    if (type === undefined) {
+        failures += 1;
+        log(`FAIL ${testCase.name}: no BCS type for ${testCase.type}`);
+        continue;
+    }
+    check(testCase.name, toHex(type.serialize(testCase.json).toBytes()), testCase.bcs);
+}
+
+const total = vectors.signing.length + vectors.encoding.length;
+if (failures > 0) {
+    log(`${failures} check(s) failed`);
+    process.exit(1);
+}
+log(`All ${total} test vectors passed`);
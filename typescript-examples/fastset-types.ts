@@ -8,6 +8,8 @@ export {
     randomPrivateKey,
     getPublicKey,
     signTransaction,
+    transactionSigningBytes,
+    ENCODING_TYPES,
     proxy_getAccountInfo,
     proxy_submitTransaction,
     proxy_faucetDrip,
@@ -81,8 +83,11 @@ const AmountBcs = bcs.u256().transform({
 });
 type Amount = InferBcsInput<typeof AmountBcs>;
 
-// Balance of an account (can temporarily be negative)
-const Balance = bcs.string();
+// Balance of an account (can temporarily be negative). Like amounts, balances are hex strings in
+// JSON, with a leading `-` when negative. In BCS, they are 320-bit two's complement integers.
+const BalanceBcs = bcs.bytes(40).transform({
+    input: (val: string) => balanceToBytes(val),
+});
 
 // Optional data that can be attached to a transaction
 const UserData = bcs.option(Bytes32);
@@ -118,6 +123,7 @@ const TransactionBcs = bcs.struct("Transaction", {
     nonce: NonceBcs,
     timestamp_nanos: bcs.u128(),
     claim: ClaimType,
+    archival: bcs.bool(),
 });
 type Transaction = InferBcsInput<typeof TransactionBcs>;
 
@@ -140,6 +146,14 @@ const ProxySubmitTransactionResult = bcs.enum("ProxySubmitTransactionResult", {
     Success: TransactionCertificateBcs,
 });
 
+// Types whose BCS encoding is checked against the shared test vectors, by Rust type name
+const ENCODING_TYPES: Record<string, BcsType<any>> = {
+    Amount: AmountBcs,
+    Balance: BalanceBcs,
+    UserData: UserData,
+    MultiSigConfig: MultiSigConfig,
+};
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 // Proxy RPC Wrappers
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -239,6 +253,20 @@ function hexToDecimal(hex: string) {
     return BigInt(`0x${hex}`).toString();
 }
 
+function balanceToBytes(hex: string): Uint8Array {
+    const negative = hex.startsWith("-");
+    let value = BigInt(`0x${negative ? hex.slice(1) : hex}`);
+    if (negative) {
+        value = (1n << 320n) - value;
+    }
+    const bytes = new Uint8Array(40);
+    for (let i = 0; i < bytes.length; i++) {
+        bytes[i] = Number(value & 0xffn);
+        value >>= 8n;
+    }
+    return bytes;
+}
+
 // The ed25519 library requires this configuration
 ed.etc.sha512Sync = (...m) => sha512(ed.etc.concatBytes(...m));
 
@@ -250,14 +278,19 @@ function getPublicKey(key: any) {
     return ed.getPublicKey(key);
 }
 
+// The signed message is the type name and `::`, followed by the BCS serialization of the value
+function signing_message(type: BcsType<any>, value: any): Uint8Array {
+    const msghead = new TextEncoder().encode(type.name + "::");
+    const msgbody = type.serialize(value).toBytes();
+    var msg = new Uint8Array(msghead.length + msgbody.length);
+    msg.set(msghead, 0);
+    msg.set(msgbody, msghead.length);
+    return msg;
+}
+
 function sign_value(signing_key: ed.Bytes, type: BcsType<any>, value: any): Uint8Array | null {
     try {
-        const msghead = new TextEncoder().encode(type.name + "::");
-        const msgbody = type.serialize(value).toBytes();
-        var msg = new Uint8Array(msghead.length + msgbody.length);
-        msg.set(msghead, 0);
-        msg.set(msgbody, msghead.length);
-        return ed.sign(msg, signing_key);
+        return ed.sign(signing_message(type, value), signing_key);
     } catch (err) {
         console.log(`Failed to sign msg of type ${type.name} due to error: ${err}`);
         return null;
@@ -276,3 +309,7 @@ function makeSignature(signing_key: ed.Bytes, type: BcsType<any>, value: any): S
 function signTransaction(signing_key: ed.Bytes, value: Transaction): SignatureOrMultiSig {
     return makeSignature(signing_key, TransactionBcs, value);
 }
+
+function transactionSigningBytes(value: Transaction): Uint8Array {
+    return signing_message(TransactionBcs, value);
+}
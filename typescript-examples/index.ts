@@ -72,6 +72,7 @@ const transaction = {
             user_data: null, // optional
         },
     },
+    archival: false,
 };
 
 const signature = signTransaction(senderPrivKey, transaction);